@@ -1,35 +1,78 @@
+#![allow(clippy::result_large_err)]
+
 use criterion::{criterion_group, criterion_main, Criterion};
 
+use binance::model::*;
 use binance::websockets::*;
+use serde::Deserialize;
 
 use core::time::Duration;
 
-fn criterion_benchmark(c: &mut Criterion) {
-    let mut group = c.benchmark_group("websockets-decoder");
+const SPOT_EVENTS: &str = include_str!("../tests/mocks/websockets/spot_events.jsonl");
 
-    let all_symbols_json = reqwest::blocking::get("https://api.binance.com/api/v3/ticker/price")
-        .unwrap()
-        .text()
-        .unwrap();
+// Decoding used before dispatching on the event type, kept as a reference
+#[derive(Deserialize)]
+#[serde(untagged)]
+#[allow(dead_code, clippy::large_enum_variant)]
+enum UntaggedEvents {
+    Vec(Vec<DayTickerEvent>),
+    BalanceUpdateEvent(BalanceUpdateEvent),
+    DayTickerEvent(DayTickerEvent),
+    BookTickerEvent(BookTickerEvent),
+    AccountUpdateEvent(AccountUpdateEvent),
+    OrderTradeEvent(OrderTradeEvent),
+    AggrTradesEvent(AggrTradesEvent),
+    TradeEvent(TradeEvent),
+    KlineEvent(KlineEvent),
+    OrderBook(OrderBook),
+    DepthOrderBookEvent(DepthOrderBookEvent),
+}
+
+fn untagged_decode(msg: &str) -> Option<UntaggedEvents> {
+    let value: serde_json::Value = serde_json::from_str(msg).unwrap();
+    match value.get("data") {
+        Some(data) => serde_json::from_value(data.clone()).ok(),
+        None => serde_json::from_value(value).ok(),
+    }
+}
 
-    let btc_symbol_json =
-        reqwest::blocking::get("https://api.binance.com/api/v3/ticker/price?symbol=BTCUSDT")
-            .unwrap()
-            .text()
-            .unwrap();
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("websockets-decoder");
 
-    let mut web_socket_subscribed: WebSockets<'_> =
-        WebSockets::new_with_subscription("!ticker@arr", |_event: WebsocketEvent| Ok(()));
+    let events: Vec<&str> = SPOT_EVENTS.lines().collect();
+    let day_ticker = events[0];
+    let depth_update = events[6];
+    let all_tickers = format!("[{}]", vec![day_ticker; 500].join(","));
 
     let mut web_socket: WebSockets<'_> = WebSockets::new(|_event: WebsocketEvent| Ok(()));
 
     group.sample_size(200);
-    group.measurement_time(Duration::new(35, 0));
+    group.measurement_time(Duration::new(10, 0));
     group.bench_function("handle_msg all symbols", |b| {
-        b.iter(|| web_socket_subscribed.test_handle_msg(&all_symbols_json))
+        b.iter(|| web_socket.test_handle_msg(&all_tickers))
+    });
+    group.bench_function("untagged all symbols", |b| {
+        b.iter(|| untagged_decode(&all_tickers))
+    });
+    group.bench_function("handle_msg depth update", |b| {
+        b.iter(|| web_socket.test_handle_msg(depth_update))
+    });
+    group.bench_function("untagged depth update", |b| {
+        b.iter(|| untagged_decode(depth_update))
+    });
+    group.bench_function("handle_msg every event", |b| {
+        b.iter(|| {
+            for msg in &events {
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+        })
     });
-    group.bench_function("handle_msg BTCUSDT symbol", |b| {
-        b.iter(|| web_socket.test_handle_msg(&btc_symbol_json))
+    group.bench_function("untagged every event", |b| {
+        b.iter(|| {
+            for msg in &events {
+                untagged_decode(msg);
+            }
+        })
     });
     group.finish();
 }
@@ -242,4 +242,18 @@ fn market_data() {
         }
         Err(e) => println!("Error: {}", e),
     }
+
+    // all 1h klines of a day, fetched page by page:
+    let history = market.get_klines_history(
+        "BNBETH",
+        KlineInterval::OneHour,
+        1609459200000,
+        1609545600000,
+    );
+    for kline in history {
+        match kline {
+            Ok(kline) => println!("Open time: {}, Close: {}", kline.open_time, kline.close),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
 }
@@ -1,3 +1,5 @@
+#![allow(clippy::result_large_err)]
+
 use binance::futures::websockets::*;
 use std::sync::atomic::{AtomicBool, Ordering};
 
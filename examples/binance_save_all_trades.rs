@@ -1,3 +1,5 @@
+#![allow(clippy::result_large_err)]
+
 use std::error::Error;
 use std::fs::File;
 use csv::Writer;
@@ -13,7 +15,7 @@ fn main() {
 fn save_all_trades_websocket() {
     struct WebSocketHandler {
         wrt: Writer<File>,
-    };
+    }
 
     impl WebSocketHandler {
         pub fn new(local_wrt: Writer<File>) -> Self {
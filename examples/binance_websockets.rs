@@ -1,4 +1,9 @@
+#![allow(clippy::result_large_err)]
+
 use binance::api::*;
+use binance::market::*;
+use binance::orderbook::*;
+use binance::streams::*;
 use binance::userstream::*;
 use binance::websockets::*;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -10,9 +15,11 @@ fn main() {
     //kline_websocket();
     //all_trades_websocket();
     //last_price_for_one_symbol();
+    //local_order_book();
     multiple_streams();
 }
 
+#[allow(dead_code)]
 fn user_stream() {
     let api_key_user = Some("YOUR_API_KEY".into());
     let user_stream: UserStream = Binance::new(api_key_user, None);
@@ -35,6 +42,7 @@ fn user_stream() {
     }
 }
 
+#[allow(dead_code)]
 fn user_stream_websocket() {
     let keep_running = AtomicBool::new(true); // Used to control the event loop
     let api_key_user = Some("YOUR_KEY".into());
@@ -77,6 +85,7 @@ fn user_stream_websocket() {
     }
 }
 
+#[allow(dead_code)]
 fn market_websocket() {
     let keep_running = AtomicBool::new(true); // Used to control the event loop
     let agg_trade: String = String::from("ethbtc@aggTrade");
@@ -114,6 +123,7 @@ fn market_websocket() {
     println!("disconnected");
 }
 
+#[allow(dead_code)]
 fn all_trades_websocket() {
     let keep_running = AtomicBool::new(true); // Used to control the event loop
     let agg_trade: String = String::from("!ticker@arr");
@@ -138,9 +148,10 @@ fn all_trades_websocket() {
     println!("disconnected");
 }
 
+#[allow(dead_code)]
 fn kline_websocket() {
     let keep_running = AtomicBool::new(true);
-    let kline: String = String::from("ethbtc@kline_1m");
+    let kline = Stream::Kline("ETHBTC".into(), KlineInterval::OneMinute);
     let mut web_socket: WebSockets<'_> = WebSockets::new(|event: WebsocketEvent| {
         if let WebsocketEvent::Kline(kline_event) = event {
             println!(
@@ -152,7 +163,7 @@ fn kline_websocket() {
         Ok(())
     });
 
-    web_socket.connect(&kline).unwrap(); // check error
+    web_socket.connect(kline).unwrap(); // check error
     if let Err(e) = web_socket.event_loop(&keep_running) {
         println!("Error: {}", e);
     }
@@ -160,6 +171,7 @@ fn kline_websocket() {
     println!("disconnected");
 }
 
+#[allow(dead_code)]
 fn last_price_for_one_symbol() {
     let keep_running = AtomicBool::new(true);
     let agg_trade: String = String::from("btcusdt@ticker");
@@ -214,3 +226,31 @@ fn multiple_streams() {
     }
     web_socket.disconnect().unwrap();
 }
+
+#[allow(dead_code)]
+fn local_order_book() {
+    let keep_running = AtomicBool::new(true);
+    let market: Market = Binance::new(None, None);
+    let mut order_book = OrderBookManager::new(market, "BNBBTC");
+
+    let mut web_socket: WebSockets<'_> = WebSockets::new(|event: WebsocketEvent| {
+        if let WebsocketEvent::DepthOrderBook(depth_order_book) = event {
+            order_book.update(&depth_order_book)?;
+            if let Some(book) = order_book.book() {
+                println!(
+                    "Best bid: {:?}, best ask: {:?}",
+                    book.best_bid(),
+                    book.best_ask()
+                );
+            }
+        }
+
+        Ok(())
+    });
+
+    web_socket.connect("bnbbtc@depth@100ms").unwrap(); // check error
+    if let Err(e) = web_socket.event_loop(&keep_running) {
+        println!("Error: {}", e);
+    }
+    web_socket.disconnect().unwrap();
+}
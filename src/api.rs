@@ -2,19 +2,26 @@ use crate::account::*;
 use crate::client::*;
 use crate::config::*;
 use crate::futures::account::FuturesAccount;
+use crate::futures::coin::account::CoinFuturesAccount;
+use crate::futures::coin::general::CoinFuturesGeneral;
+use crate::futures::coin::market::CoinFuturesMarket;
+use crate::futures::coin::userstream::CoinFuturesUserStream;
 use crate::futures::general::*;
 use crate::futures::market::*;
 use crate::futures::userstream::*;
 use crate::general::*;
 use crate::market::*;
 use crate::userstream::*;
+use crate::websocket_api::*;
 use crate::savings::*;
+use std::time::Duration;
 
 #[allow(clippy::all)]
 pub enum API {
     Spot(Spot),
     Savings(Sapi),
     Futures(Futures),
+    CoinFutures(CoinFutures),
 }
 
 /// Endpoint for production and test orders.
@@ -29,8 +36,11 @@ pub enum Spot {
     HistoricalTrades,
     AggTrades,
     Klines,
+    UiKlines,
     AvgPrice,
     Ticker24hr,
+    Ticker,
+    TickerTradingDay,
     Price,
     BookTicker,
     Order,
@@ -50,6 +60,7 @@ pub enum Sapi {
     AllCoins,
     AssetDetail,
     DepositAddress,
+    UserDataStream,
 }
 
 pub enum Futures {
@@ -90,6 +101,36 @@ pub enum Futures {
     UserDataStream,
 }
 
+/// COIN-M futures endpoint, the `dapi` counterpart of `Futures`.
+pub enum CoinFutures {
+    Ping,
+    Time,
+    ExchangeInfo,
+    Depth,
+    Trades,
+    HistoricalTrades,
+    AggTrades,
+    Klines,
+    ContinuousKlines,
+    IndexPriceKlines,
+    MarkPriceKlines,
+    PremiumIndex,
+    Ticker24hr,
+    TickerPrice,
+    BookTicker,
+    OpenInterest,
+    OpenInterestHist,
+    Order,
+    AllOpenOrders,
+    OpenOrders,
+    AllOrders,
+    PositionRisk,
+    Balance,
+    PositionSide,
+    ChangeInitialLeverage,
+    UserDataStream,
+}
+
 impl From<API> for String {
     fn from(item: API) -> Self {
         String::from(match item {
@@ -102,8 +143,11 @@ impl From<API> for String {
                 Spot::HistoricalTrades => "/api/v3/historicalTrades",
                 Spot::AggTrades => "/api/v3/aggTrades",
                 Spot::Klines => "/api/v3/klines",
+                Spot::UiKlines => "/api/v3/uiKlines",
                 Spot::AvgPrice => "/api/v3/avgPrice",
                 Spot::Ticker24hr => "/api/v3/ticker/24hr",
+                Spot::Ticker => "/api/v3/ticker",
+                Spot::TickerTradingDay => "/api/v3/ticker/tradingDay",
                 Spot::Price => "/api/v3/ticker/price",
                 Spot::BookTicker => "/api/v3/ticker/bookTicker",
                 Spot::Order => "/api/v3/order",
@@ -122,6 +166,7 @@ impl From<API> for String {
                 Sapi::AllCoins => "/sapi/v1/capital/config/getall",
                 Sapi::AssetDetail => "/sapi/v1/asset/assetDetail",
                 Sapi::DepositAddress => "/sapi/v1/capital/deposit/address",
+                Sapi::UserDataStream => "/sapi/v1/userDataStream",
             },
             API::Futures(route) => match route {
                 Futures::Ping => "/fapi/v1/ping",
@@ -160,6 +205,34 @@ impl From<API> for String {
                 Futures::AllOrders => "/fapi/v1/allOrders",
                 Futures::UserDataStream => "/fapi/v1/listenKey",
             },
+            API::CoinFutures(route) => match route {
+                CoinFutures::Ping => "/dapi/v1/ping",
+                CoinFutures::Time => "/dapi/v1/time",
+                CoinFutures::ExchangeInfo => "/dapi/v1/exchangeInfo",
+                CoinFutures::Depth => "/dapi/v1/depth",
+                CoinFutures::Trades => "/dapi/v1/trades",
+                CoinFutures::HistoricalTrades => "/dapi/v1/historicalTrades",
+                CoinFutures::AggTrades => "/dapi/v1/aggTrades",
+                CoinFutures::Klines => "/dapi/v1/klines",
+                CoinFutures::ContinuousKlines => "/dapi/v1/continuousKlines",
+                CoinFutures::IndexPriceKlines => "/dapi/v1/indexPriceKlines",
+                CoinFutures::MarkPriceKlines => "/dapi/v1/markPriceKlines",
+                CoinFutures::PremiumIndex => "/dapi/v1/premiumIndex",
+                CoinFutures::Ticker24hr => "/dapi/v1/ticker/24hr",
+                CoinFutures::TickerPrice => "/dapi/v1/ticker/price",
+                CoinFutures::BookTicker => "/dapi/v1/ticker/bookTicker",
+                CoinFutures::OpenInterest => "/dapi/v1/openInterest",
+                CoinFutures::OpenInterestHist => "/futures/data/openInterestHist",
+                CoinFutures::Order => "/dapi/v1/order",
+                CoinFutures::AllOpenOrders => "/dapi/v1/allOpenOrders",
+                CoinFutures::OpenOrders => "/dapi/v1/openOrders",
+                CoinFutures::AllOrders => "/dapi/v1/allOrders",
+                CoinFutures::PositionRisk => "/dapi/v1/positionRisk",
+                CoinFutures::Balance => "/dapi/v1/balance",
+                CoinFutures::PositionSide => "/dapi/v1/positionSide/dual",
+                CoinFutures::ChangeInitialLeverage => "/dapi/v1/leverage",
+                CoinFutures::UserDataStream => "/dapi/v1/listenKey",
+            },
         })
     }
 }
@@ -245,6 +318,27 @@ impl Binance for UserStream {
     }
 }
 
+impl Binance for WebsocketApi {
+    fn new(api_key: Option<String>, secret_key: Option<String>) -> WebsocketApi {
+        Self::new_with_config(api_key, secret_key, &Config::default())
+    }
+
+    fn new_with_config(
+        api_key: Option<String>, secret_key: Option<String>, config: &Config,
+    ) -> WebsocketApi {
+        WebsocketApi {
+            socket: None,
+            client: Client::new(api_key, secret_key, config.rest_api_endpoint.clone()),
+            endpoint: config.ws_api_endpoint.clone(),
+            recv_window: config.recv_window,
+            request_timeout: Duration::from_secs(10),
+            next_request_id: 1,
+            rate_limits: Vec::new(),
+            logged_on: false,
+        }
+    }
+}
+
 // *****************************************************
 //              Binance Futures API
 // *****************************************************
@@ -323,3 +417,82 @@ impl Binance for FuturesUserStream {
         }
     }
 }
+
+// *****************************************************
+//              Binance COIN-M Futures API
+// *****************************************************
+
+impl Binance for CoinFuturesGeneral {
+    fn new(api_key: Option<String>, secret_key: Option<String>) -> CoinFuturesGeneral {
+        Self::new_with_config(api_key, secret_key, &Config::default())
+    }
+
+    fn new_with_config(
+        api_key: Option<String>, secret_key: Option<String>, config: &Config,
+    ) -> CoinFuturesGeneral {
+        CoinFuturesGeneral {
+            client: Client::new(
+                api_key,
+                secret_key,
+                config.coin_futures_rest_api_endpoint.clone(),
+            ),
+        }
+    }
+}
+
+impl Binance for CoinFuturesMarket {
+    fn new(api_key: Option<String>, secret_key: Option<String>) -> CoinFuturesMarket {
+        Self::new_with_config(api_key, secret_key, &Config::default())
+    }
+
+    fn new_with_config(
+        api_key: Option<String>, secret_key: Option<String>, config: &Config,
+    ) -> CoinFuturesMarket {
+        CoinFuturesMarket {
+            client: Client::new(
+                api_key,
+                secret_key,
+                config.coin_futures_rest_api_endpoint.clone(),
+            ),
+            recv_window: config.recv_window,
+        }
+    }
+}
+
+impl Binance for CoinFuturesAccount {
+    fn new(api_key: Option<String>, secret_key: Option<String>) -> Self {
+        Self::new_with_config(api_key, secret_key, &Config::default())
+    }
+
+    fn new_with_config(
+        api_key: Option<String>, secret_key: Option<String>, config: &Config,
+    ) -> Self {
+        Self {
+            client: Client::new(
+                api_key,
+                secret_key,
+                config.coin_futures_rest_api_endpoint.clone(),
+            ),
+            recv_window: config.recv_window,
+        }
+    }
+}
+
+impl Binance for CoinFuturesUserStream {
+    fn new(api_key: Option<String>, secret_key: Option<String>) -> CoinFuturesUserStream {
+        Self::new_with_config(api_key, secret_key, &Config::default())
+    }
+
+    fn new_with_config(
+        api_key: Option<String>, secret_key: Option<String>, config: &Config,
+    ) -> CoinFuturesUserStream {
+        CoinFuturesUserStream {
+            client: Client::new(
+                api_key,
+                secret_key,
+                config.coin_futures_rest_api_endpoint.clone(),
+            ),
+            recv_window: config.recv_window,
+        }
+    }
+}
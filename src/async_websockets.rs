@@ -0,0 +1,215 @@
+use crate::config::*;
+use crate::errors::*;
+use crate::futures::websockets::{FuturesMarket, FuturesWebsocketAPI, FuturesWebsocketEvent};
+use crate::streams::stream_names;
+use crate::websockets::{self as spot_websockets, WebsocketAPI, WebsocketEvent};
+pub use crate::websockets::StreamEvent;
+
+use futures_util::{Sink, Stream};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+/// Control message sent on an open connection, answered by a
+/// `LiveResponse` event with the same id.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LiveRequest {
+    pub method: String,
+    pub params: Vec<serde_json::Value>,
+    pub id: u64,
+}
+
+impl LiveRequest {
+    pub fn subscribe<S>(id: u64, streams: &[S]) -> LiveRequest
+    where
+        S: Clone + Into<String>,
+    {
+        LiveRequest::new(id, "SUBSCRIBE", stream_params(streams))
+    }
+
+    pub fn unsubscribe<S>(id: u64, streams: &[S]) -> LiveRequest
+    where
+        S: Clone + Into<String>,
+    {
+        LiveRequest::new(id, "UNSUBSCRIBE", stream_params(streams))
+    }
+
+    pub fn list_subscriptions(id: u64) -> LiveRequest {
+        LiveRequest::new(id, "LIST_SUBSCRIPTIONS", vec![])
+    }
+
+    pub fn set_property(id: u64, property: &str, value: bool) -> LiveRequest {
+        LiveRequest::new(id, "SET_PROPERTY", vec![property.into(), value.into()])
+    }
+
+    pub fn get_property(id: u64, property: &str) -> LiveRequest {
+        LiveRequest::new(id, "GET_PROPERTY", vec![property.into()])
+    }
+
+    fn new(id: u64, method: &str, params: Vec<serde_json::Value>) -> LiveRequest {
+        LiveRequest {
+            method: method.into(),
+            params,
+            id,
+        }
+    }
+}
+
+fn stream_params<S>(streams: &[S]) -> Vec<serde_json::Value>
+where
+    S: Clone + Into<String>,
+{
+    spot_websockets::stream_params(&stream_names(streams))
+}
+
+/// Websocket connection for async runtimes (tokio).
+///
+/// The connection is a `Stream` of decoded events and a `Sink` of
+/// `LiveRequest`s; use `StreamExt::split` to hand both halves to different tasks.
+/// A closed connection ends the stream after a `WebSocketDisconnected` error.
+pub struct AsyncWebSocketConnection<E> {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    closed: bool,
+    event: PhantomData<fn() -> E>,
+}
+
+/// Async counterpart of `WebSockets`.
+pub type AsyncWebSockets = AsyncWebSocketConnection<WebsocketEvent>;
+
+/// Async counterpart of `FuturesWebSockets`.
+pub type AsyncFuturesWebSockets = AsyncWebSocketConnection<FuturesWebsocketEvent>;
+
+impl AsyncWebSocketConnection<WebsocketEvent> {
+    pub async fn connect<S>(subscription: S) -> Result<Self>
+    where
+        S: Into<String>,
+    {
+        Self::connect_wss(WebsocketAPI::Default.params(&subscription.into())).await
+    }
+
+    pub async fn connect_with_config<S>(subscription: S, config: &Config) -> Result<Self>
+    where
+        S: Into<String>,
+    {
+        Self::connect_wss(
+            WebsocketAPI::Custom(config.ws_endpoint.clone()).params(&subscription.into()),
+        )
+        .await
+    }
+
+    pub async fn connect_multiple_streams<S>(endpoints: &[S]) -> Result<Self>
+    where
+        S: Clone + Into<String>,
+    {
+        Self::connect_wss(WebsocketAPI::MultiStream.params(&stream_names(endpoints).join("/")))
+            .await
+    }
+}
+
+impl AsyncWebSocketConnection<FuturesWebsocketEvent> {
+    pub async fn connect<S>(market: FuturesMarket, subscription: S) -> Result<Self>
+    where
+        S: Into<String>,
+    {
+        Self::connect_wss(FuturesWebsocketAPI::Default.params(market, &subscription.into())).await
+    }
+
+    pub async fn connect_with_config<S>(
+        market: FuturesMarket, subscription: S, config: &Config,
+    ) -> Result<Self>
+    where
+        S: Into<String>,
+    {
+        Self::connect_wss(
+            FuturesWebsocketAPI::Custom(market.ws_endpoint(config).into())
+                .params(market, &subscription.into()),
+        )
+        .await
+    }
+
+    pub async fn connect_multiple_streams<S>(market: FuturesMarket, endpoints: &[S]) -> Result<Self>
+    where
+        S: Clone + Into<String>,
+    {
+        Self::connect_wss(
+            FuturesWebsocketAPI::MultiStream.params(market, &stream_names(endpoints).join("/")),
+        )
+        .await
+    }
+}
+
+impl<E: StreamEvent> AsyncWebSocketConnection<E> {
+    async fn connect_wss(wss: String) -> Result<Self> {
+        let url = url::Url::parse(&wss)?;
+        match connect_async(url).await {
+            Ok((socket, _)) => Ok(AsyncWebSocketConnection {
+                socket,
+                closed: false,
+                event: PhantomData,
+            }),
+            Err(e) => bail!(format!("Error during handshake {}", e)),
+        }
+    }
+}
+
+impl<E: StreamEvent> Stream for AsyncWebSocketConnection<E> {
+    type Item = Result<E>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.closed {
+            return Poll::Ready(None);
+        }
+        loop {
+            let message = match Pin::new(&mut self.socket).poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Ready(Some(Ok(message))) => message,
+            };
+            match message {
+                Message::Text(msg) => return Poll::Ready(Some(Ok(E::parse(&msg)))),
+                // Pings are answered by tungstenite
+                Message::Ping(_) | Message::Pong(_) | Message::Binary(_) => (),
+                Message::Close(e) => {
+                    self.closed = true;
+                    let frame = e.map(CloseFrame::into_owned);
+                    return Poll::Ready(Some(Err(ErrorKind::WebSocketDisconnected(frame).into())));
+                }
+            }
+        }
+    }
+}
+
+impl<E> Sink<LiveRequest> for AsyncWebSocketConnection<E> {
+    type Error = Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.socket)
+            .poll_ready(cx)
+            .map_err(Error::from)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, request: LiveRequest) -> Result<()> {
+        let message = Message::Text(serde_json::to_string(&request)?);
+        Pin::new(&mut self.socket)
+            .start_send(message)
+            .map_err(Error::from)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.socket)
+            .poll_flush(cx)
+            .map_err(Error::from)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.socket)
+            .poll_close(cx)
+            .map_err(Error::from)
+    }
+}
@@ -0,0 +1,118 @@
+use crate::errors::*;
+use serde::{Deserialize, Serialize};
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A websocket text frame as stored in a capture file, one JSON object per line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CapturedFrame {
+    /// Local receive time, in microseconds since the Unix epoch
+    pub received_at: u64,
+    pub message: String,
+}
+
+/// Pace at which a capture is replayed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReplaySpeed {
+    AsFastAsPossible,
+    /// Wait between frames as long as between their receive times.
+    Recorded,
+}
+
+/// Writes every text frame received by a websocket to a capture file.
+///
+/// Each frame is written out as soon as it is received, so that a capture is
+/// complete up to the last frame even when the process dies.
+pub struct Capture {
+    writer: LineWriter<File>,
+}
+
+impl Capture {
+    /// Create the capture file, truncating an existing one.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Capture> {
+        Ok(Capture {
+            writer: LineWriter::new(File::create(path)?),
+        })
+    }
+
+    /// Append to an existing capture file, or create it.
+    pub fn append<P: AsRef<Path>>(path: P) -> Result<Capture> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Capture {
+            writer: LineWriter::new(file),
+        })
+    }
+
+    pub(crate) fn record(&mut self, received_at: u64, message: &str) -> Result<()> {
+        let frame = CapturedFrame {
+            received_at,
+            message: message.to_string(),
+        };
+        serde_json::to_writer(&mut self.writer, &frame)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+/// Reads the frames of a capture file in order.
+pub struct CaptureReader {
+    lines: std::io::Lines<BufReader<File>>,
+}
+
+impl CaptureReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<CaptureReader> {
+        Ok(CaptureReader {
+            lines: BufReader::new(File::open(path)?).lines(),
+        })
+    }
+}
+
+impl Iterator for CaptureReader {
+    type Item = Result<CapturedFrame>;
+
+    fn next(&mut self) -> Option<Result<CapturedFrame>> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if !line.trim().is_empty() {
+                return Some(serde_json::from_str(&line).map_err(Error::from));
+            }
+        }
+    }
+}
+
+/// Feed the frames of a capture to `handle_msg`, returns the number of frames.
+pub(crate) fn replay<P, F>(path: P, speed: ReplaySpeed, mut handle_msg: F) -> Result<usize>
+where
+    P: AsRef<Path>,
+    F: FnMut(&CapturedFrame) -> Result<()>,
+{
+    // Frames are scheduled relative to the first one, so that the time spent
+    // handling them does not add up
+    let started = Instant::now();
+    let mut first: Option<u64> = None;
+    let mut frames = 0;
+    for frame in CaptureReader::open(path)? {
+        let frame = frame?;
+        if speed == ReplaySpeed::Recorded {
+            let first = *first.get_or_insert(frame.received_at);
+            let due = started + Duration::from_micros(frame.received_at.saturating_sub(first));
+            let now = Instant::now();
+            if due > now {
+                thread::sleep(due - now);
+            }
+        }
+        handle_msg(&frame)?;
+        frames += 1;
+    }
+    Ok(frames)
+}
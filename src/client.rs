@@ -67,19 +67,27 @@ impl Client {
     }
 
     pub fn get<T: DeserializeOwned>(&self, endpoint: API, request: Option<String>) -> Result<T> {
-        let mut url: String = format!("{}{}", self.host, String::from(endpoint));
-        if let Some(request) = request {
-            if !request.is_empty() {
-                url.push_str(format!("?{}", request).as_str());
-            }
-        }
-
+        let url = self.build_url(endpoint, request);
         let client = &self.inner_client;
         let response = client.get(url.as_str()).send()?;
 
         self.handler(response)
     }
 
+    // MARKET_DATA endpoints are not signed, but require the API key header
+    pub fn get_with_api_key<T: DeserializeOwned>(
+        &self, endpoint: API, request: Option<String>,
+    ) -> Result<T> {
+        let url = self.build_url(endpoint, request);
+        let client = &self.inner_client;
+        let response = client
+            .get(url.as_str())
+            .headers(self.build_headers(false)?)
+            .send()?;
+
+        self.handler(response)
+    }
+
     pub fn post<T: DeserializeOwned>(&self, endpoint: API) -> Result<T> {
         let url: String = format!("{}{}", self.host, String::from(endpoint));
 
@@ -120,27 +128,44 @@ impl Client {
         self.handler(response)
     }
 
+    // Unsigned request, the query string is optional
+    fn build_url(&self, endpoint: API, request: Option<String>) -> String {
+        let mut url: String = format!("{}{}", self.host, String::from(endpoint));
+        if let Some(request) = request {
+            if !request.is_empty() {
+                url.push_str(format!("?{}", request).as_str());
+            }
+        }
+        url
+    }
+
     // Request must be signed
     fn sign_request(&self, endpoint: API, request: Option<String>) -> String {
         match request {
             Some(request) => {
-                let mut signed_key =
-                    Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes()).unwrap();
-                signed_key.update(request.as_bytes());
-                let signature = hex_encode(signed_key.finalize().into_bytes());
+                let signature = self.signature(&request);
                 let request_body: String = format!("{}&signature={}", request, signature);
                 format!("{}{}?{}", self.host, String::from(endpoint), request_body)
             }
             None => {
-                let signed_key =
-                    Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes()).unwrap();
-                let signature = hex_encode(signed_key.finalize().into_bytes());
+                let signature = self.signature("");
                 let request_body: String = format!("&signature={}", signature);
                 format!("{}{}?{}", self.host, String::from(endpoint), request_body)
             }
         }
     }
 
+    /// HMAC SHA256 signature of `payload` with the secret key, hex encoded.
+    pub(crate) fn signature(&self, payload: &str) -> String {
+        let mut signed_key = Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes()).unwrap();
+        signed_key.update(payload.as_bytes());
+        hex_encode(signed_key.finalize().into_bytes())
+    }
+
+    pub(crate) fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
     fn build_headers(&self, content_type: bool) -> Result<HeaderMap> {
         let mut custom_headers = HeaderMap::new();
 
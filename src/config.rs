@@ -2,21 +2,34 @@
 pub struct Config {
     pub rest_api_endpoint: String,
     pub ws_endpoint: String,
+    pub ws_api_endpoint: String,
 
     pub futures_rest_api_endpoint: String,
+    /// COIN-M futures REST API
+    pub coin_futures_rest_api_endpoint: String,
+    /// USD-M futures streams
     pub futures_ws_endpoint: String,
+    /// COIN-M futures streams
+    pub coin_futures_ws_endpoint: String,
+    /// Vanilla options streams
+    pub options_ws_endpoint: String,
 
     pub recv_window: u64,
 }
 
 impl Config {
+    #[allow(clippy::should_implement_trait)]
     pub fn default() -> Config {
         Config {
             rest_api_endpoint: "https://api.binance.com".into(),
             ws_endpoint: "wss://stream.binance.com:9443/ws/".into(),
+            ws_api_endpoint: "wss://ws-api.binance.com:443/ws-api/v3".into(),
 
             futures_rest_api_endpoint: "https://fapi.binance.com".into(),
+            coin_futures_rest_api_endpoint: "https://dapi.binance.com".into(),
             futures_ws_endpoint: "wss://fstream.binance.com/ws".into(),
+            coin_futures_ws_endpoint: "wss://dstream.binance.com/ws".into(),
+            options_ws_endpoint: "wss://vstream.binance.com/ws".into(),
 
             recv_window: 5000,
         }
@@ -26,8 +39,11 @@ impl Config {
         Config::default()
             .set_rest_api_endpoint("https://testnet.binance.vision")
             .set_ws_endpoint("wss://testnet.binance.vision/ws")
+            .set_ws_api_endpoint("wss://ws-api.testnet.binance.vision/ws-api/v3")
             .set_futures_rest_api_endpoint("https://testnet.binancefuture.com")
-            .set_futures_ws_endpoint("https://testnet.binancefuture.com/ws")
+            .set_coin_futures_rest_api_endpoint("https://testnet.binancefuture.com")
+            .set_futures_ws_endpoint("wss://stream.binancefuture.com/ws")
+            .set_coin_futures_ws_endpoint("wss://dstream.binancefuture.com/ws")
     }
 
     pub fn set_rest_api_endpoint<T: Into<String>>(mut self, rest_api_endpoint: T) -> Self {
@@ -39,6 +55,11 @@ impl Config {
         self.ws_endpoint = ws_endpoint.into();
         self
     }
+
+    pub fn set_ws_api_endpoint<T: Into<String>>(mut self, ws_api_endpoint: T) -> Self {
+        self.ws_api_endpoint = ws_api_endpoint.into();
+        self
+    }
     pub fn set_futures_rest_api_endpoint<T: Into<String>>(
         mut self, futures_rest_api_endpoint: T,
     ) -> Self {
@@ -46,11 +67,30 @@ impl Config {
         self
     }
 
+    pub fn set_coin_futures_rest_api_endpoint<T: Into<String>>(
+        mut self, coin_futures_rest_api_endpoint: T,
+    ) -> Self {
+        self.coin_futures_rest_api_endpoint = coin_futures_rest_api_endpoint.into();
+        self
+    }
+
     pub fn set_futures_ws_endpoint<T: Into<String>>(mut self, futures_ws_endpoint: T) -> Self {
         self.futures_ws_endpoint = futures_ws_endpoint.into();
         self
     }
 
+    pub fn set_coin_futures_ws_endpoint<T: Into<String>>(
+        mut self, coin_futures_ws_endpoint: T,
+    ) -> Self {
+        self.coin_futures_ws_endpoint = coin_futures_ws_endpoint.into();
+        self
+    }
+
+    pub fn set_options_ws_endpoint<T: Into<String>>(mut self, options_ws_endpoint: T) -> Self {
+        self.options_ws_endpoint = options_ws_endpoint.into();
+        self
+    }
+
     pub fn set_recv_window(mut self, recv_window: u64) -> Self {
         self.recv_window = recv_window;
         self
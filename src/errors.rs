@@ -1,6 +1,9 @@
-use serde::Deserialize;
+// error_chain checks a cfg of its own build script
+#![allow(unexpected_cfgs)]
 
-#[derive(Debug, Deserialize)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BinanceContentError {
     pub code: i16,
     pub msg: String,
@@ -19,6 +22,26 @@ error_chain! {
             description("WebSocket stream disconnected"),
             display("WebSocket was closed: {:?}", frame),
         }
+
+        WebSocketStale(elapsed: std::time::Duration) {
+            description("WebSocket stream stale"),
+            display("No data received on the WebSocket for {:?}", elapsed),
+        }
+
+        WebSocketUnknownEvent(raw: String, error: String) {
+            description("WebSocket message could not be decoded"),
+            display("Cannot decode WebSocket message {}: {}", raw, error),
+        }
+
+        WebSocketApiTimeout(method: String, timeout: std::time::Duration) {
+            description("WebSocket API request timed out"),
+            display("No response to {} within {:?}", method, timeout),
+        }
+
+        WebSocketLiveRequestTimeout(method: String, id: u64, timeout: std::time::Duration) {
+            description("WebSocket live request timed out"),
+            display("No response to {} request {} within {:?}", method, id, timeout),
+        }
      }
 
     foreign_links {
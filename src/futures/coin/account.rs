@@ -0,0 +1,372 @@
+use std::collections::BTreeMap;
+
+use crate::util::*;
+use crate::errors::*;
+use crate::client::Client;
+use crate::api::{API, CoinFutures};
+use crate::model::Empty;
+use crate::account::{OrderSide, TimeInForce};
+use crate::futures::account::{build_order, CustomOrderRequest, OrderRequest, OrderType};
+use super::model::{AccountBalance, ChangeLeverageResponse, Order, Position};
+
+/// COIN-M futures trading.
+///
+/// Order quantities are numbers of contracts, see `Symbol::contract_size`.
+#[derive(Clone)]
+pub struct CoinFuturesAccount {
+    pub client: Client,
+    pub recv_window: u64,
+}
+
+impl CoinFuturesAccount {
+    /// Place a LIMIT order - BUY, `qty` is a number of contracts
+    pub fn limit_buy<S>(
+        &self, symbol: S, qty: u64, price: f64, time_in_force: TimeInForce,
+    ) -> Result<Order>
+    where
+        S: Into<String>,
+    {
+        let buy = OrderRequest {
+            symbol: symbol.into(),
+            side: OrderSide::Buy,
+            position_side: None,
+            order_type: OrderType::Limit,
+            time_in_force: Some(time_in_force),
+            qty: Some(qty as f64),
+            reduce_only: None,
+            price: Some(price),
+            stop_price: None,
+            close_position: None,
+            activation_price: None,
+            callback_rate: None,
+            working_type: None,
+            price_protect: None,
+        };
+        self.post_order(buy)
+    }
+
+    /// Place a LIMIT order - SELL, `qty` is a number of contracts
+    pub fn limit_sell<S>(
+        &self, symbol: S, qty: u64, price: f64, time_in_force: TimeInForce,
+    ) -> Result<Order>
+    where
+        S: Into<String>,
+    {
+        let sell = OrderRequest {
+            symbol: symbol.into(),
+            side: OrderSide::Sell,
+            position_side: None,
+            order_type: OrderType::Limit,
+            time_in_force: Some(time_in_force),
+            qty: Some(qty as f64),
+            reduce_only: None,
+            price: Some(price),
+            stop_price: None,
+            close_position: None,
+            activation_price: None,
+            callback_rate: None,
+            working_type: None,
+            price_protect: None,
+        };
+        self.post_order(sell)
+    }
+
+    // Place a MARKET order - BUY
+    pub fn market_buy<S>(&self, symbol: S, qty: u64) -> Result<Order>
+    where
+        S: Into<String>,
+    {
+        let buy = OrderRequest {
+            symbol: symbol.into(),
+            side: OrderSide::Buy,
+            position_side: None,
+            order_type: OrderType::Market,
+            time_in_force: None,
+            qty: Some(qty as f64),
+            reduce_only: None,
+            price: None,
+            stop_price: None,
+            close_position: None,
+            activation_price: None,
+            callback_rate: None,
+            working_type: None,
+            price_protect: None,
+        };
+        self.post_order(buy)
+    }
+
+    // Place a MARKET order - SELL
+    pub fn market_sell<S>(&self, symbol: S, qty: u64) -> Result<Order>
+    where
+        S: Into<String>,
+    {
+        let sell = OrderRequest {
+            symbol: symbol.into(),
+            side: OrderSide::Sell,
+            position_side: None,
+            order_type: OrderType::Market,
+            time_in_force: None,
+            qty: Some(qty as f64),
+            reduce_only: None,
+            price: None,
+            stop_price: None,
+            close_position: None,
+            activation_price: None,
+            callback_rate: None,
+            working_type: None,
+            price_protect: None,
+        };
+        self.post_order(sell)
+    }
+
+    // Place a STOP_MARKET close - BUY
+    pub fn stop_market_close_buy<S, F>(&self, symbol: S, stop_price: F) -> Result<Order>
+    where
+        S: Into<String>,
+        F: Into<f64>,
+    {
+        let buy = OrderRequest {
+            symbol: symbol.into(),
+            side: OrderSide::Buy,
+            position_side: None,
+            order_type: OrderType::StopMarket,
+            time_in_force: None,
+            qty: None,
+            reduce_only: None,
+            price: None,
+            stop_price: Some(stop_price.into()),
+            close_position: Some(true),
+            activation_price: None,
+            callback_rate: None,
+            working_type: None,
+            price_protect: None,
+        };
+        self.post_order(buy)
+    }
+
+    // Place a STOP_MARKET close - SELL
+    pub fn stop_market_close_sell<S, F>(&self, symbol: S, stop_price: F) -> Result<Order>
+    where
+        S: Into<String>,
+        F: Into<f64>,
+    {
+        let sell = OrderRequest {
+            symbol: symbol.into(),
+            side: OrderSide::Sell,
+            position_side: None,
+            order_type: OrderType::StopMarket,
+            time_in_force: None,
+            qty: None,
+            reduce_only: None,
+            price: None,
+            stop_price: Some(stop_price.into()),
+            close_position: Some(true),
+            activation_price: None,
+            callback_rate: None,
+            working_type: None,
+            price_protect: None,
+        };
+        self.post_order(sell)
+    }
+
+    // Custom order for for professional traders, `qty` is a number of contracts
+    pub fn custom_order(&self, order_request: CustomOrderRequest) -> Result<Order> {
+        let order = OrderRequest {
+            symbol: order_request.symbol,
+            side: order_request.side,
+            position_side: order_request.position_side,
+            order_type: order_request.order_type,
+            time_in_force: order_request.time_in_force,
+            qty: order_request.qty,
+            reduce_only: order_request.reduce_only,
+            price: order_request.price,
+            stop_price: order_request.stop_price,
+            close_position: order_request.close_position,
+            activation_price: order_request.activation_price,
+            callback_rate: order_request.callback_rate,
+            working_type: order_request.working_type,
+            price_protect: order_request.price_protect,
+        };
+        self.post_order(order)
+    }
+
+    fn post_order(&self, order: OrderRequest) -> Result<Order> {
+        let request = build_signed_request(build_order(order), self.recv_window)?;
+        self.client
+            .post_signed(API::CoinFutures(CoinFutures::Order), request)
+    }
+
+    pub fn get_order<S>(&self, symbol: S, order_id: u64) -> Result<Order>
+    where
+        S: Into<String>,
+    {
+        let mut parameters = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("orderId".into(), order_id.to_string());
+
+        let request = build_signed_request(parameters, self.recv_window)?;
+        self.client
+            .get_signed(API::CoinFutures(CoinFutures::Order), Some(request))
+    }
+
+    pub fn cancel_order<S>(&self, symbol: S, order_id: u64) -> Result<Order>
+    where
+        S: Into<String>,
+    {
+        let mut parameters = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("orderId".into(), order_id.to_string());
+
+        let request = build_signed_request(parameters, self.recv_window)?;
+        self.client
+            .delete_signed(API::CoinFutures(CoinFutures::Order), Some(request))
+    }
+
+    // Positions of all contracts of a pair, e.g. BTCUSD
+    pub fn position_information<S>(&self, pair: S) -> Result<Vec<Position>>
+    where
+        S: Into<String>,
+    {
+        let mut parameters = BTreeMap::new();
+        parameters.insert("pair".into(), pair.into());
+
+        let request = build_signed_request(parameters, self.recv_window)?;
+        self.client
+            .get_signed(API::CoinFutures(CoinFutures::PositionRisk), Some(request))
+    }
+
+    pub fn get_all_positions(&self) -> Result<Vec<Position>> {
+        let request = build_signed_request(BTreeMap::new(), self.recv_window)?;
+        self.client
+            .get_signed(API::CoinFutures(CoinFutures::PositionRisk), Some(request))
+    }
+
+    pub fn account_balance(&self) -> Result<Vec<AccountBalance>> {
+        let request = build_signed_request(BTreeMap::new(), self.recv_window)?;
+        self.client
+            .get_signed(API::CoinFutures(CoinFutures::Balance), Some(request))
+    }
+
+    pub fn change_initial_leverage<S>(
+        &self, symbol: S, leverage: u8,
+    ) -> Result<ChangeLeverageResponse>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("leverage".into(), leverage.to_string());
+
+        let request = build_signed_request(parameters, self.recv_window)?;
+        self.client.post_signed(
+            API::CoinFutures(CoinFutures::ChangeInitialLeverage),
+            request,
+        )
+    }
+
+    pub fn change_position_mode(&self, dual_side_position: bool) -> Result<()> {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        let dual_side = if dual_side_position { "true" } else { "false" };
+        parameters.insert("dualSidePosition".into(), dual_side.into());
+
+        let request = build_signed_request(parameters, self.recv_window)?;
+        self.client
+            .post_signed::<Empty>(API::CoinFutures(CoinFutures::PositionSide), request)
+            .map(|_| ())
+    }
+
+    pub fn cancel_all_open_orders<S>(&self, symbol: S) -> Result<()>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        let request = build_signed_request(parameters, self.recv_window)?;
+        self.client
+            .delete_signed::<Empty>(API::CoinFutures(CoinFutures::AllOpenOrders), Some(request))
+            .map(|_| ())
+    }
+
+    pub fn get_all_open_orders<S>(&self, symbol: S) -> Result<Vec<Order>>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        let request = build_signed_request(parameters, self.recv_window)?;
+        self.client
+            .get_signed(API::CoinFutures(CoinFutures::OpenOrders), Some(request))
+    }
+
+    // Open orders of all contracts of a pair
+    pub fn get_all_open_orders_for_pair<S>(&self, pair: S) -> Result<Vec<Order>>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("pair".into(), pair.into());
+        let request = build_signed_request(parameters, self.recv_window)?;
+        self.client
+            .get_signed(API::CoinFutures(CoinFutures::OpenOrders), Some(request))
+    }
+
+    /// Get all open orders. **Be careful** when accessing this.
+    /// Weight: 40.
+    pub fn get_all_open_orders_for_all_symbols(&self) -> Result<Vec<Order>> {
+        let request = build_signed_request(BTreeMap::new(), self.recv_window)?;
+        self.client
+            .get_signed(API::CoinFutures(CoinFutures::OpenOrders), Some(request))
+    }
+
+    pub fn get_all_orders<S1, S2, S3, S4, S5>(
+        &self, symbol: S1, from_id: S2, start_time: S3, end_time: S4, limit: S5,
+    ) -> Result<Vec<Order>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<u64>>,
+        S3: Into<Option<u64>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u16>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        if let Some(order_id) = from_id.into() {
+            parameters.insert("orderId".into(), order_id.to_string());
+        }
+        self.get_orders(parameters, start_time.into(), end_time.into(), limit.into())
+    }
+
+    // Orders of all contracts of a pair, which cannot be paged by order id
+    pub fn get_all_orders_for_pair<S1, S2, S3, S4>(
+        &self, pair: S1, start_time: S2, end_time: S3, limit: S4,
+    ) -> Result<Vec<Order>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<u64>>,
+        S3: Into<Option<u64>>,
+        S4: Into<Option<u16>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("pair".into(), pair.into());
+        self.get_orders(parameters, start_time.into(), end_time.into(), limit.into())
+    }
+
+    fn get_orders(
+        &self, mut parameters: BTreeMap<String, String>, start_time: Option<u64>,
+        end_time: Option<u64>, limit: Option<u16>,
+    ) -> Result<Vec<Order>> {
+        if let Some(start_time) = start_time {
+            parameters.insert("startTime".into(), start_time.to_string());
+        }
+        if let Some(end_time) = end_time {
+            parameters.insert("endTime".into(), end_time.to_string());
+        }
+        if let Some(limit) = limit {
+            parameters.insert("limit".into(), limit.to_string());
+        }
+
+        let request = build_signed_request(parameters, self.recv_window)?;
+        self.client
+            .get_signed(API::CoinFutures(CoinFutures::AllOrders), Some(request))
+    }
+}
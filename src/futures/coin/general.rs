@@ -0,0 +1,49 @@
+use crate::futures::coin::model::*;
+use crate::model::Empty;
+use crate::client::*;
+use crate::errors::*;
+use crate::api::API;
+use crate::api::CoinFutures;
+
+#[derive(Clone)]
+pub struct CoinFuturesGeneral {
+    pub client: Client,
+}
+
+impl CoinFuturesGeneral {
+    // Test connectivity
+    pub fn ping(&self) -> Result<String> {
+        self.client
+            .get::<Empty>(API::CoinFutures(CoinFutures::Ping), None)?;
+        Ok("pong".into())
+    }
+
+    // Check server time
+    pub fn get_server_time(&self) -> Result<ServerTime> {
+        self.client.get(API::CoinFutures(CoinFutures::Time), None)
+    }
+
+    // Obtain exchange information
+    // - Current exchange trading rules and symbol information
+    pub fn exchange_info(&self) -> Result<ExchangeInformation> {
+        self.client
+            .get(API::CoinFutures(CoinFutures::ExchangeInfo), None)
+    }
+
+    // Get Symbol information, e.g. BTCUSD_PERP or BTCUSD_250328
+    pub fn get_symbol_info<S>(&self, symbol: S) -> Result<Symbol>
+    where
+        S: Into<String>,
+    {
+        let upper_symbol = symbol.into().to_uppercase();
+        let info = self.exchange_info()?;
+        match info
+            .symbols
+            .into_iter()
+            .find(|item| item.symbol == upper_symbol)
+        {
+            Some(symbol) => Ok(symbol),
+            None => bail!("Symbol not found"),
+        }
+    }
+}
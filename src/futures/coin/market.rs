@@ -0,0 +1,433 @@
+use crate::util::*;
+use crate::futures::coin::model::*;
+use crate::client::*;
+use crate::errors::*;
+use std::collections::BTreeMap;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use crate::api::API;
+use crate::api::CoinFutures;
+use crate::futures::account::ContractType;
+use crate::futures::market::insert_kline_range;
+use crate::market::{KlineHistory, KlineInterval};
+use std::convert::TryInto;
+
+/// COIN-M futures market data.
+///
+/// Symbols name a contract, e.g. `BTCUSD_PERP` or `BTCUSD_250328`, while
+/// pairs name all the contracts of an underlying, e.g. `BTCUSD`. Ticker
+/// endpoints return every matching contract, the `_for_pair` methods expose
+/// that for a pair.
+#[derive(Clone)]
+pub struct CoinFuturesMarket {
+    pub client: Client,
+    pub recv_window: u64,
+}
+
+impl CoinFuturesMarket {
+    // Order book (Default 500; max 1000)
+    pub fn get_depth<S>(&self, symbol: S) -> Result<OrderBook>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        let request = build_request(parameters);
+        self.client
+            .get(API::CoinFutures(CoinFutures::Depth), Some(request))
+    }
+
+    // Order book at a custom depth. Currently supported values
+    // are 5, 10, 20, 50, 100, 500, 1000
+    pub fn get_custom_depth<S>(&self, symbol: S, depth: u64) -> Result<OrderBook>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("limit".into(), depth.to_string());
+        let request = build_request(parameters);
+        self.client
+            .get(API::CoinFutures(CoinFutures::Depth), Some(request))
+    }
+
+    pub fn get_trades<S>(&self, symbol: S) -> Result<Vec<Trade>>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        let request = build_request(parameters);
+        self.client
+            .get(API::CoinFutures(CoinFutures::Trades), Some(request))
+    }
+
+    // Older trades, requires an API key
+    pub fn get_historical_trades<S1, S2, S3>(
+        &self, symbol: S1, from_id: S2, limit: S3,
+    ) -> Result<Vec<Trade>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<u64>>,
+        S3: Into<Option<u16>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        if let Some(lt) = limit.into() {
+            parameters.insert("limit".into(), format!("{}", lt));
+        }
+        if let Some(fi) = from_id.into() {
+            parameters.insert("fromId".into(), format!("{}", fi));
+        }
+        let request = build_request(parameters);
+        self.client.get_with_api_key(
+            API::CoinFutures(CoinFutures::HistoricalTrades),
+            Some(request),
+        )
+    }
+
+    pub fn get_agg_trades<S1, S2, S3, S4, S5>(
+        &self, symbol: S1, from_id: S2, start_time: S3, end_time: S4, limit: S5,
+    ) -> Result<AggTrades>
+    where
+        S1: Into<String>,
+        S2: Into<Option<u64>>,
+        S3: Into<Option<u64>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u16>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        if let Some(fi) = from_id.into() {
+            parameters.insert("fromId".into(), format!("{}", fi));
+        }
+        insert_kline_range(
+            &mut parameters,
+            limit.into(),
+            start_time.into(),
+            end_time.into(),
+        );
+        let request = build_request(parameters);
+        self.client
+            .get(API::CoinFutures(CoinFutures::AggTrades), Some(request))
+    }
+
+    // Returns up to 'limit' klines for given symbol and interval ("1m", "5m", ...)
+    // https://binance-docs.github.io/apidocs/delivery/en/#kline-candlestick-data
+    pub fn get_klines<S1, S2, S3, S4, S5>(
+        &self, symbol: S1, interval: S2, limit: S3, start_time: S4, end_time: S5,
+    ) -> Result<KlineSummaries>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("interval".into(), interval.into());
+        insert_kline_range(
+            &mut parameters,
+            limit.into(),
+            start_time.into(),
+            end_time.into(),
+        );
+
+        let request = build_request(parameters);
+        self.get_kline_summaries(CoinFutures::Klines, request)
+    }
+
+    // Returns up to 'limit' klines of a continuous contract for given pair and interval
+    // https://binance-docs.github.io/apidocs/delivery/en/#continuous-contract-kline-candlestick-data
+    pub fn get_continuous_klines<S1, S2, S3, S4, S5>(
+        &self, pair: S1, contract_type: ContractType, interval: S2, limit: S3, start_time: S4,
+        end_time: S5,
+    ) -> Result<KlineSummaries>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("pair".into(), pair.into());
+        parameters.insert("contractType".into(), contract_type.to_string());
+        parameters.insert("interval".into(), interval.into());
+        insert_kline_range(
+            &mut parameters,
+            limit.into(),
+            start_time.into(),
+            end_time.into(),
+        );
+
+        let request = build_request(parameters);
+        self.get_kline_summaries(CoinFutures::ContinuousKlines, request)
+    }
+
+    // Returns up to 'limit' index price klines for given pair and interval
+    // https://binance-docs.github.io/apidocs/delivery/en/#index-price-kline-candlestick-data
+    pub fn get_index_price_klines<S1, S2, S3, S4, S5>(
+        &self, pair: S1, interval: S2, limit: S3, start_time: S4, end_time: S5,
+    ) -> Result<KlineSummaries>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("pair".into(), pair.into());
+        parameters.insert("interval".into(), interval.into());
+        insert_kline_range(
+            &mut parameters,
+            limit.into(),
+            start_time.into(),
+            end_time.into(),
+        );
+
+        let request = build_request(parameters);
+        self.get_kline_summaries(CoinFutures::IndexPriceKlines, request)
+    }
+
+    // Returns up to 'limit' mark price klines for given symbol and interval
+    // https://binance-docs.github.io/apidocs/delivery/en/#mark-price-kline-candlestick-data
+    pub fn get_mark_price_klines<S1, S2, S3, S4, S5>(
+        &self, symbol: S1, interval: S2, limit: S3, start_time: S4, end_time: S5,
+    ) -> Result<KlineSummaries>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("interval".into(), interval.into());
+        insert_kline_range(
+            &mut parameters,
+            limit.into(),
+            start_time.into(),
+            end_time.into(),
+        );
+
+        let request = build_request(parameters);
+        self.get_kline_summaries(CoinFutures::MarkPriceKlines, request)
+    }
+
+    /// Iterate over all klines of `symbol` opened within `[start_time, end_time)`.
+    ///
+    /// Futures have no `KlineInterval::OneSecond` klines, it yields a single error.
+    pub fn get_klines_history<S>(
+        &self, symbol: S, interval: KlineInterval, start_time: u64, end_time: u64,
+    ) -> KlineHistory<'_>
+    where
+        S: Into<String>,
+    {
+        let symbol = symbol.into();
+        KlineHistory::new(
+            move |start, end, limit| self.get_klines(symbol.as_str(), interval, limit, start, end),
+            interval,
+            start_time,
+            end_time,
+        )
+        .reject_one_second()
+        .set_max_limit(1500)
+    }
+
+    /// Iterate over all continuous contract klines of `pair` opened within `[start_time, end_time)`.
+    pub fn get_continuous_klines_history<S>(
+        &self, pair: S, contract_type: ContractType, interval: KlineInterval, start_time: u64,
+        end_time: u64,
+    ) -> KlineHistory<'_>
+    where
+        S: Into<String>,
+    {
+        let pair = pair.into();
+        KlineHistory::new(
+            move |start, end, limit| {
+                self.get_continuous_klines(
+                    pair.as_str(),
+                    contract_type,
+                    interval,
+                    limit,
+                    start,
+                    end,
+                )
+            },
+            interval,
+            start_time,
+            end_time,
+        )
+        .reject_one_second()
+        .set_max_limit(1500)
+    }
+
+    fn get_kline_summaries(&self, route: CoinFutures, request: String) -> Result<KlineSummaries> {
+        let data: Vec<Vec<Value>> = self.client.get(API::CoinFutures(route), Some(request))?;
+
+        let klines = KlineSummaries::AllKlineSummaries(
+            data.iter()
+                .map(|row| row.try_into())
+                .collect::<Result<Vec<KlineSummary>>>()?,
+        );
+
+        Ok(klines)
+    }
+
+    // 24hr ticker price change statistics
+    pub fn get_24h_price_stats<S>(&self, symbol: S) -> Result<PriceStats>
+    where
+        S: Into<String>,
+    {
+        self.get_symbol_ticker(CoinFutures::Ticker24hr, symbol.into())
+    }
+
+    // 24hr ticker price change statistics of all contracts of a pair
+    pub fn get_24h_price_stats_for_pair<S>(&self, pair: S) -> Result<Vec<PriceStats>>
+    where
+        S: Into<String>,
+    {
+        self.get_pair_tickers(CoinFutures::Ticker24hr, pair.into())
+    }
+
+    // 24hr ticker price change statistics for all symbols
+    pub fn get_all_24h_price_stats(&self) -> Result<Vec<PriceStats>> {
+        self.client
+            .get(API::CoinFutures(CoinFutures::Ticker24hr), None)
+    }
+
+    // Latest price for ONE symbol.
+    pub fn get_price<S>(&self, symbol: S) -> Result<SymbolPrice>
+    where
+        S: Into<String>,
+    {
+        self.get_symbol_ticker(CoinFutures::TickerPrice, symbol.into())
+    }
+
+    // Latest prices of all contracts of a pair
+    pub fn get_prices_for_pair<S>(&self, pair: S) -> Result<Vec<SymbolPrice>>
+    where
+        S: Into<String>,
+    {
+        self.get_pair_tickers(CoinFutures::TickerPrice, pair.into())
+    }
+
+    // Latest price for all symbols.
+    pub fn get_all_prices(&self) -> Result<Vec<SymbolPrice>> {
+        self.client
+            .get(API::CoinFutures(CoinFutures::TickerPrice), None)
+    }
+
+    // -> Best price/qty on the order book for ONE symbol
+    pub fn get_book_ticker<S>(&self, symbol: S) -> Result<BookTicker>
+    where
+        S: Into<String>,
+    {
+        self.get_symbol_ticker(CoinFutures::BookTicker, symbol.into())
+    }
+
+    // -> Best price/qty on the order book for all contracts of a pair
+    pub fn get_book_tickers_for_pair<S>(&self, pair: S) -> Result<Vec<BookTicker>>
+    where
+        S: Into<String>,
+    {
+        self.get_pair_tickers(CoinFutures::BookTicker, pair.into())
+    }
+
+    // -> Best price/qty on the order book for ALL symbols.
+    pub fn get_all_book_tickers(&self) -> Result<Vec<BookTicker>> {
+        self.client
+            .get(API::CoinFutures(CoinFutures::BookTicker), None)
+    }
+
+    // Mark price for ONE symbol.
+    pub fn get_mark_price<S>(&self, symbol: S) -> Result<MarkPrice>
+    where
+        S: Into<String>,
+    {
+        self.get_symbol_ticker(CoinFutures::PremiumIndex, symbol.into())
+    }
+
+    // Mark prices of all contracts of a pair
+    pub fn get_mark_prices_for_pair<S>(&self, pair: S) -> Result<Vec<MarkPrice>>
+    where
+        S: Into<String>,
+    {
+        self.get_pair_tickers(CoinFutures::PremiumIndex, pair.into())
+    }
+
+    pub fn get_mark_prices(&self) -> Result<Vec<MarkPrice>> {
+        self.client
+            .get(API::CoinFutures(CoinFutures::PremiumIndex), None)
+    }
+
+    pub fn open_interest<S>(&self, symbol: S) -> Result<OpenInterest>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        let request = build_request(parameters);
+        self.client
+            .get(API::CoinFutures(CoinFutures::OpenInterest), Some(request))
+    }
+
+    // Open interest of the contracts of a pair, e.g. all the quarterly ones
+    pub fn open_interest_statistics<S1, S2, S3, S4, S5>(
+        &self, pair: S1, contract_type: ContractType, period: S2, limit: S3, start_time: S4,
+        end_time: S5,
+    ) -> Result<Vec<OpenInterestHist>>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("pair".into(), pair.into());
+        parameters.insert("contractType".into(), contract_type.to_string());
+        parameters.insert("period".into(), period.into());
+        insert_kline_range(
+            &mut parameters,
+            limit.into(),
+            start_time.into(),
+            end_time.into(),
+        );
+
+        let request = build_request(parameters);
+        self.client.get(
+            API::CoinFutures(CoinFutures::OpenInterestHist),
+            Some(request),
+        )
+    }
+
+    // Ticker endpoints answer with a list even for a single symbol
+    fn get_symbol_ticker<T>(&self, route: CoinFutures, symbol: String) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol);
+        let request = build_request(parameters);
+        let tickers: Vec<T> = self.client.get(API::CoinFutures(route), Some(request))?;
+        match tickers.into_iter().next() {
+            Some(ticker) => Ok(ticker),
+            None => bail!("Symbol not found"),
+        }
+    }
+
+    fn get_pair_tickers<T>(&self, route: CoinFutures, pair: String) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("pair".into(), pair);
+        let request = build_request(parameters);
+        self.client.get(API::CoinFutures(route), Some(request))
+    }
+}
@@ -0,0 +1,21 @@
+//! COIN-M futures REST API (`dapi`), the coin-margined counterpart of the
+//! USD-M futures modules. Its market streams are served by
+//! `futures::websockets` with `FuturesMarket::COINM`, its user data stream by
+//! `userstream::UserDataStreamManager` with `UserDataStreamMarket::CoinMFutures`.
+//!
+//! ```no_run
+//! use binance::api::*;
+//! use binance::futures::coin::market::*;
+//!
+//! let market: CoinFuturesMarket = Binance::new(None, None);
+//! match market.get_mark_prices_for_pair("BTCUSD") {
+//!     Ok(prices) => println!("{:?}", prices),
+//!     Err(e) => println!("Error: {}", e),
+//! }
+//! ```
+
+pub mod account;
+pub mod general;
+pub mod market;
+pub mod model;
+pub mod userstream;
@@ -0,0 +1,29 @@
+use crate::model::*;
+use crate::client::*;
+use crate::errors::*;
+use crate::api::API;
+use crate::api::CoinFutures;
+
+#[derive(Clone)]
+pub struct CoinFuturesUserStream {
+    pub client: Client,
+    pub recv_window: u64,
+}
+
+impl CoinFuturesUserStream {
+    // User Stream
+    pub fn start(&self) -> Result<UserDataStream> {
+        self.client
+            .post(API::CoinFutures(CoinFutures::UserDataStream))
+    }
+
+    pub fn keep_alive(&self, listen_key: &str) -> Result<Success> {
+        self.client
+            .put(API::CoinFutures(CoinFutures::UserDataStream), listen_key)
+    }
+
+    pub fn close(&self, listen_key: &str) -> Result<Success> {
+        self.client
+            .delete(API::CoinFutures(CoinFutures::UserDataStream), listen_key)
+    }
+}
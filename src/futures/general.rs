@@ -1,4 +1,5 @@
 use crate::futures::model::*;
+use crate::model::Empty;
 use crate::client::*;
 use crate::errors::*;
 use crate::api::API;
@@ -12,7 +13,8 @@ pub struct FuturesGeneral {
 impl FuturesGeneral {
     // Test connectivity
     pub fn ping(&self) -> Result<String> {
-        self.client.get(API::Futures(Futures::Ping), None)?;
+        self.client
+            .get::<Empty>(API::Futures(Futures::Ping), None)?;
         Ok("pong".into())
     }
 
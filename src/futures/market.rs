@@ -28,6 +28,8 @@ use std::collections::BTreeMap;
 use serde_json::Value;
 use crate::api::API;
 use crate::api::Futures;
+use crate::futures::account::ContractType;
+use crate::market::{KlineHistory, KlineInterval};
 use std::convert::TryInto;
 
 // TODO
@@ -169,10 +171,162 @@ impl FuturesMarket {
         }
 
         let request = build_request(parameters);
+        self.get_kline_summaries(Futures::Klines, request)
+    }
 
-        let data: Vec<Vec<Value>> = self
-            .client
-            .get(API::Futures(Futures::Klines), Some(request))?;
+    // Returns up to 'limit' klines of a continuous contract for given pair and interval
+    // https://binance-docs.github.io/apidocs/futures/en/#continuous-contract-kline-candlestick-data
+    pub fn get_continuous_klines<S1, S2, S3, S4, S5>(
+        &self, pair: S1, contract_type: ContractType, interval: S2, limit: S3, start_time: S4,
+        end_time: S5,
+    ) -> Result<KlineSummaries>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("pair".into(), pair.into());
+        parameters.insert("contractType".into(), contract_type.to_string());
+        parameters.insert("interval".into(), interval.into());
+        insert_kline_range(&mut parameters, limit.into(), start_time.into(), end_time.into());
+
+        let request = build_request(parameters);
+        self.get_kline_summaries(Futures::ContinuousKlines, request)
+    }
+
+    // Returns up to 'limit' index price klines for given pair and interval
+    // https://binance-docs.github.io/apidocs/futures/en/#index-price-kline-candlestick-data
+    pub fn get_index_price_klines<S1, S2, S3, S4, S5>(
+        &self, pair: S1, interval: S2, limit: S3, start_time: S4, end_time: S5,
+    ) -> Result<KlineSummaries>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("pair".into(), pair.into());
+        parameters.insert("interval".into(), interval.into());
+        insert_kline_range(&mut parameters, limit.into(), start_time.into(), end_time.into());
+
+        let request = build_request(parameters);
+        self.get_kline_summaries(Futures::IndexPriceKlines, request)
+    }
+
+    // Returns up to 'limit' mark price klines for given symbol and interval
+    // https://binance-docs.github.io/apidocs/futures/en/#mark-price-kline-candlestick-data
+    pub fn get_mark_price_klines<S1, S2, S3, S4, S5>(
+        &self, symbol: S1, interval: S2, limit: S3, start_time: S4, end_time: S5,
+    ) -> Result<KlineSummaries>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("interval".into(), interval.into());
+        insert_kline_range(&mut parameters, limit.into(), start_time.into(), end_time.into());
+
+        let request = build_request(parameters);
+        self.get_kline_summaries(Futures::MarkPriceKlines, request)
+    }
+
+    /// Iterate over all klines of `symbol` opened within `[start_time, end_time)`.
+    ///
+    /// Futures have no `KlineInterval::OneSecond` klines, it yields a single error.
+    pub fn get_klines_history<S>(
+        &self, symbol: S, interval: KlineInterval, start_time: u64, end_time: u64,
+    ) -> KlineHistory<'_>
+    where
+        S: Into<String>,
+    {
+        let symbol = symbol.into();
+        KlineHistory::new(
+            move |start, end, limit| {
+                self.get_klines(symbol.as_str(), interval, limit, start, end)
+            },
+            interval,
+            start_time,
+            end_time,
+        )
+        .reject_one_second()
+        .set_max_limit(1500)
+    }
+
+    /// Iterate over all continuous contract klines of `pair` opened within `[start_time, end_time)`.
+    pub fn get_continuous_klines_history<S>(
+        &self, pair: S, contract_type: ContractType, interval: KlineInterval, start_time: u64,
+        end_time: u64,
+    ) -> KlineHistory<'_>
+    where
+        S: Into<String>,
+    {
+        let pair = pair.into();
+        KlineHistory::new(
+            move |start, end, limit| {
+                self.get_continuous_klines(pair.as_str(), contract_type, interval, limit, start, end)
+            },
+            interval,
+            start_time,
+            end_time,
+        )
+        .reject_one_second()
+        .set_max_limit(1500)
+    }
+
+    /// Iterate over all index price klines of `pair` opened within `[start_time, end_time)`.
+    pub fn get_index_price_klines_history<S>(
+        &self, pair: S, interval: KlineInterval, start_time: u64, end_time: u64,
+    ) -> KlineHistory<'_>
+    where
+        S: Into<String>,
+    {
+        let pair = pair.into();
+        KlineHistory::new(
+            move |start, end, limit| {
+                self.get_index_price_klines(pair.as_str(), interval, limit, start, end)
+            },
+            interval,
+            start_time,
+            end_time,
+        )
+        .reject_one_second()
+        .set_max_limit(1500)
+    }
+
+    /// Iterate over all mark price klines of `symbol` opened within `[start_time, end_time)`.
+    pub fn get_mark_price_klines_history<S>(
+        &self, symbol: S, interval: KlineInterval, start_time: u64, end_time: u64,
+    ) -> KlineHistory<'_>
+    where
+        S: Into<String>,
+    {
+        let symbol = symbol.into();
+        KlineHistory::new(
+            move |start, end, limit| {
+                self.get_mark_price_klines(symbol.as_str(), interval, limit, start, end)
+            },
+            interval,
+            start_time,
+            end_time,
+        )
+        .reject_one_second()
+        .set_max_limit(1500)
+    }
+
+    fn get_kline_summaries(&self, route: Futures, request: String) -> Result<KlineSummaries> {
+        let data: Vec<Vec<Value>> = self.client.get(API::Futures(route), Some(request))?;
 
         let klines = KlineSummaries::AllKlineSummaries(
             data.iter()
@@ -301,3 +455,18 @@ impl FuturesMarket {
             .get(API::Futures(Futures::OpenInterestHist), Some(request))
     }
 }
+
+pub(crate) fn insert_kline_range(
+    parameters: &mut BTreeMap<String, String>, limit: Option<u16>, start_time: Option<u64>,
+    end_time: Option<u64>,
+) {
+    if let Some(lt) = limit {
+        parameters.insert("limit".into(), format!("{}", lt));
+    }
+    if let Some(st) = start_time {
+        parameters.insert("startTime".into(), format!("{}", st));
+    }
+    if let Some(et) = end_time {
+        parameters.insert("endTime".into(), format!("{}", et));
+    }
+}
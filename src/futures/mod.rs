@@ -1,6 +1,8 @@
 pub mod account;
+pub mod coin;
 pub mod general;
 pub mod market;
 pub mod model;
+pub mod orderbook;
 pub mod userstream;
 pub mod websockets;
@@ -458,7 +458,17 @@ pub struct OrderUpdate {
     pub ss_ignore: i32,
 
     #[serde(rename = "rp")]
-    pub rp_ignore: String,
+    pub realized_profit: String,
+
+    #[serde(rename = "V")]
+    pub self_trade_prevention_mode: Option<String>,
+
+    #[serde(rename = "pm")]
+    pub price_match: Option<String>,
+
+    /// Auto cancel time of a GTD order, 0 for other orders
+    #[serde(rename = "gtd")]
+    pub good_till_date: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -630,3 +640,158 @@ pub struct MultiAssetsMarginUpdate {
     #[serde(rename = "j")]
     pub enabled: bool,
 }
+
+/// Lighter and faster counterpart of `OrderTradeEvent`, for trades only.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TradeLiteEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "T")]
+    pub transaction_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "q")]
+    pub qty: String,
+
+    #[serde(rename = "p")]
+    pub price: String,
+
+    #[serde(rename = "m")]
+    pub is_maker: bool,
+
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+
+    #[serde(rename = "S")]
+    pub side: String,
+
+    #[serde(rename = "L")]
+    pub price_last_filled_trade: String,
+
+    #[serde(rename = "l")]
+    pub qty_last_filled_trade: String,
+
+    #[serde(rename = "t")]
+    pub trade_id: u64,
+
+    #[serde(rename = "i")]
+    pub order_id: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StrategyUpdateEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "T")]
+    pub transaction_time: u64,
+
+    #[serde(rename = "su")]
+    pub strategy: StrategyUpdate,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StrategyUpdate {
+    #[serde(rename = "si")]
+    pub strategy_id: u64,
+
+    #[serde(rename = "st")]
+    pub strategy_type: String,
+
+    #[serde(rename = "ss")]
+    pub strategy_status: String,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "ut")]
+    pub update_time: u64,
+
+    /// Reason of the update, e.g. 8001 when the strategy parameters are updated
+    #[serde(rename = "c")]
+    pub op_code: u32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GridUpdateEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "T")]
+    pub transaction_time: u64,
+
+    #[serde(rename = "gu")]
+    pub grid: GridUpdate,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GridUpdate {
+    #[serde(rename = "si")]
+    pub strategy_id: u64,
+
+    #[serde(rename = "st")]
+    pub strategy_type: String,
+
+    #[serde(rename = "ss")]
+    pub strategy_status: String,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "r")]
+    pub realized_profit: String,
+
+    #[serde(rename = "up")]
+    pub unmatched_average_price: String,
+
+    #[serde(rename = "uq")]
+    pub unmatched_qty: String,
+
+    #[serde(rename = "uf")]
+    pub unmatched_fee: String,
+
+    #[serde(rename = "mp")]
+    pub matched_profit: String,
+
+    #[serde(rename = "ut")]
+    pub update_time: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConditionalOrderTriggerRejectEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "T")]
+    pub transaction_time: u64,
+
+    #[serde(rename = "or")]
+    pub order: ConditionalOrderTriggerReject,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConditionalOrderTriggerReject {
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "i")]
+    pub order_id: u64,
+
+    #[serde(rename = "r")]
+    pub reason: String,
+}
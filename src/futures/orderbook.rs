@@ -0,0 +1,67 @@
+use crate::errors::*;
+use crate::futures::coin::market::CoinFuturesMarket;
+use crate::futures::market::FuturesMarket;
+use crate::futures::model;
+use crate::model::{DepthOrderBookEvent, OrderBook};
+use crate::orderbook::{DepthSnapshotSource, OrderBookManager, UpdateContinuity};
+
+/// Futures order book kept in sync with the `<symbol>@depth` diff stream.
+///
+/// Feed every `DepthOrderBookEvent` of the symbol received from
+/// `FuturesWebSockets` to `update`. The snapshots are fetched from a USD-M
+/// `FuturesMarket` or a COIN-M `CoinFuturesMarket`. Unlike spot, futures
+/// events carry the final update id of the previous event (`pu`), which is
+/// used to detect dropped messages, following
+/// https://binance-docs.github.io/apidocs/futures/en/#how-to-manage-a-local-order-book-correctly
+pub type FuturesOrderBookManager<S = FuturesMarket> = OrderBookManager<S>;
+
+/// Futures rule: the first event after the snapshot must satisfy
+/// `U <= lastUpdateId <= u`, every following event must have `pu` equal to
+/// the `u` of the previous one.
+#[derive(Debug, Copy, Clone)]
+pub struct FuturesContinuity;
+
+impl UpdateContinuity for FuturesContinuity {
+    fn is_stale(snapshot_update_id: u64, first: &DepthOrderBookEvent) -> bool {
+        snapshot_update_id < first.first_update_id
+    }
+
+    fn is_outdated(event: &DepthOrderBookEvent, last_update_id: u64, bridged: bool) -> bool {
+        event.final_update_id < last_update_id
+            || (bridged && event.final_update_id == last_update_id)
+    }
+
+    fn continues(event: &DepthOrderBookEvent, last_update_id: u64, bridged: bool) -> bool {
+        if bridged {
+            event.previous_final_update_id == Some(last_update_id)
+        } else {
+            event.first_update_id <= last_update_id && last_update_id <= event.final_update_id
+        }
+    }
+}
+
+impl DepthSnapshotSource for FuturesMarket {
+    type Continuity = FuturesContinuity;
+
+    fn depth_snapshot(&self, symbol: &str, depth: u64) -> Result<OrderBook> {
+        self.get_custom_depth(symbol, depth).map(OrderBook::from)
+    }
+}
+
+impl DepthSnapshotSource for CoinFuturesMarket {
+    type Continuity = FuturesContinuity;
+
+    fn depth_snapshot(&self, symbol: &str, depth: u64) -> Result<OrderBook> {
+        self.get_custom_depth(symbol, depth).map(OrderBook::from)
+    }
+}
+
+impl From<model::OrderBook> for OrderBook {
+    fn from(book: model::OrderBook) -> Self {
+        OrderBook {
+            last_update_id: book.last_update_id,
+            bids: book.bids,
+            asks: book.asks,
+        }
+    }
+}
@@ -2,45 +2,63 @@ use crate::errors::*;
 use crate::config::*;
 use crate::model::*;
 use crate::futures::model;
-use url::Url;
-use serde::Deserialize;
+use crate::streams::{stream_names, StreamMessage};
+use crate::websockets::{
+    array_event_type, decode, parse_live_response, unknown_event_type, EventHeader,
+    StreamConnection, StreamEvent,
+};
 
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::net::TcpStream;
-use tungstenite::{connect, Message};
-use tungstenite::protocol::WebSocket;
-use tungstenite::stream::MaybeTlsStream;
-use tungstenite::handshake::client::Response;
+use std::borrow::Cow;
 
 #[allow(clippy::all)]
-enum FuturesWebsocketAPI {
+pub(crate) enum FuturesWebsocketAPI {
     Default,
     MultiStream,
     Custom(String),
+    CustomMultiStream(String),
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum FuturesMarket {
     USDM,
     COINM,
     Vanilla,
 }
 
+impl FuturesMarket {
+    /// Raw stream endpoint of the market in `config`.
+    pub fn ws_endpoint(self, config: &Config) -> &str {
+        match self {
+            FuturesMarket::USDM => &config.futures_ws_endpoint,
+            FuturesMarket::COINM => &config.coin_futures_ws_endpoint,
+            FuturesMarket::Vanilla => &config.options_ws_endpoint,
+        }
+    }
+}
+
 impl FuturesWebsocketAPI {
-    fn params(self, market: FuturesMarket, subscription: &str) -> String {
-        let baseurl = match market {
-            FuturesMarket::USDM => "wss://fstream.binance.com",
-            FuturesMarket::COINM => "wss://dstream.binance.com",
-            FuturesMarket::Vanilla => "wss://vstream.binance.com",
+    pub(crate) fn params(self, market: FuturesMarket, subscription: &str) -> String {
+        let endpoint = match self {
+            FuturesWebsocketAPI::Default | FuturesWebsocketAPI::MultiStream => {
+                market.ws_endpoint(&Config::default()).to_string()
+            }
+            FuturesWebsocketAPI::Custom(ref url)
+            | FuturesWebsocketAPI::CustomMultiStream(ref url) => url.clone(),
         };
+        let endpoint = endpoint.trim_end_matches('/');
 
         match self {
-            FuturesWebsocketAPI::Default => {
-                format!("{}/ws/{}", baseurl, subscription)
+            FuturesWebsocketAPI::Default | FuturesWebsocketAPI::Custom(_) => {
+                format!("{}/{}", endpoint, subscription)
             }
-            FuturesWebsocketAPI::MultiStream => {
-                format!("{}/stream?streams={}", baseurl, subscription)
+            // The combined stream endpoint is next to the raw stream one, `/ws`
+            FuturesWebsocketAPI::MultiStream | FuturesWebsocketAPI::CustomMultiStream(_) => {
+                format!(
+                    "{}/stream?streams={}",
+                    endpoint.trim_end_matches("/ws"),
+                    subscription
+                )
             }
-            FuturesWebsocketAPI::Custom(url) => url,
         }
     }
 }
@@ -64,154 +82,194 @@ pub enum FuturesWebsocketEvent {
     ContinuousKline(ContinuousKlineEvent),
     IndexKline(IndexKlineEvent),
     Liquidation(LiquidationEvent),
+    CompositeIndex(CompositeIndexEvent),
+    ContractInfo(ContractInfoEvent),
+    AssetIndex(AssetIndexEvent),
+    AssetIndexAll(Vec<AssetIndexEvent>),
     DepthOrderBook(DepthOrderBookEvent),
     BookTicker(BookTickerEvent),
     MarginCall(model::MarginCallEvent),
     LeverageUpdate(model::LeverageUpdateEvent),
     MarginUpdate(model::MultiAssetsMarginUpdateEvent),
+    TradeLite(model::TradeLiteEvent),
+    StrategyUpdate(model::StrategyUpdateEvent),
+    GridUpdate(model::GridUpdateEvent),
+    ConditionalOrderTriggerReject(model::ConditionalOrderTriggerRejectEvent),
     ListenKeyExpired(model::ListenKeyExpiredEvent),
+    Reconnected(ReconnectedEvent),
+    /// Response to a live request which was not waited for
+    LiveResponse(LiveResponse),
+    /// Message which could not be decoded, see `set_strict`
+    Unknown { raw: String, error: String },
 }
 
-pub struct FuturesWebSockets<'a> {
-    pub socket: Option<(WebSocket<MaybeTlsStream<TcpStream>>, Response)>,
-    handler: Box<dyn FnMut(FuturesWebsocketEvent) -> Result<()> + 'a>,
-}
+/// Connection to futures market or user data streams.
+///
+/// The handler must be `Send`, so that a `FuturesWebSockets<'static>` can be
+/// moved into a spawned thread.
+pub type FuturesWebSockets<'a> = StreamConnection<
+    FuturesWebsocketEvent,
+    Box<dyn FnMut(StreamMessage<FuturesWebsocketEvent>) -> Result<()> + Send + 'a>,
+>;
 
-#[derive(Deserialize, Debug)]
-#[serde(untagged)]
-enum FuturesEvents {
-    Vec(Vec<DayTickerEvent>),
-    DayTickerEvent(DayTickerEvent),
-    BookTickerEvent(BookTickerEvent),
-    MiniTickerEvent(MiniTickerEvent),
-    VecMiniTickerEvent(Vec<MiniTickerEvent>),
-    AccountUpdateEvent(model::AccountUpdateEvent),
-    OrderTradeEvent(model::OrderTradeEvent),
-    AggrTradesEvent(AggrTradesEvent),
-    IndexPriceEvent(IndexPriceEvent),
-    MarkPriceEvent(MarkPriceEvent),
-    VecMarkPriceEvent(Vec<MarkPriceEvent>),
-    TradeEvent(TradeEvent),
-    KlineEvent(KlineEvent),
-    ContinuousKlineEvent(ContinuousKlineEvent),
-    IndexKlineEvent(IndexKlineEvent),
-    LiquidationEvent(LiquidationEvent),
-    OrderBook(OrderBook),
-    DepthOrderBookEvent(DepthOrderBookEvent),
-    MarginCallEvent(model::MarginCallEvent),
-    LeverageUpdateEvent(model::LeverageUpdateEvent),
-    MarginUpdateEvent(model::MultiAssetsMarginUpdateEvent),
-    ListenKeyExpiredEvent(model::ListenKeyExpiredEvent),
-}
+impl StreamEvent for FuturesWebsocketEvent {
+    fn parse_message(msg: &str) -> StreamMessage<Self> {
+        parse_message(msg)
+    }
 
-impl<'a> FuturesWebSockets<'a> {
-    pub fn new<Callback>(handler: Callback) -> FuturesWebSockets<'a>
-    where
-        Callback: FnMut(FuturesWebsocketEvent) -> Result<()> + 'a,
-    {
-        FuturesWebSockets {
-            socket: None,
-            handler: Box::new(handler),
-        }
+    fn reconnected(event: ReconnectedEvent) -> Self {
+        FuturesWebsocketEvent::Reconnected(event)
     }
 
-    pub fn connect(&mut self, market: FuturesMarket, subscription: &'a str) -> Result<()> {
-        self.connect_wss(FuturesWebsocketAPI::Default.params(market, subscription))
+    fn as_unknown(&self) -> Option<(&str, &str)> {
+        match self {
+            FuturesWebsocketEvent::Unknown { raw, error } => Some((raw, error)),
+            _ => None,
+        }
     }
+}
 
-    pub fn connect_with_config(
-        &mut self, market: FuturesMarket, subscription: &'a str, config: &'a Config,
-    ) -> Result<()> {
-        self.connect_wss(
-            FuturesWebsocketAPI::Custom(config.ws_endpoint.clone()).params(market, subscription),
-        )
+/// Decode a stream message along with the stream name of a combined stream,
+/// `FuturesWebsocketEvent::Unknown` if it is not a known event.
+///
+/// The event is picked by its `e` field and decoded straight from the text.
+pub(crate) fn parse_message(msg: &str) -> StreamMessage<FuturesWebsocketEvent> {
+    let mut stream = None;
+    let mut event_time = None;
+    let event = decode_event(msg, &mut stream, &mut event_time).unwrap_or_else(|error| {
+        FuturesWebsocketEvent::Unknown {
+            raw: msg.into(),
+            error,
+        }
+    });
+    StreamMessage {
+        stream,
+        event,
+        event_time,
+        received_at: None,
     }
+}
 
-    pub fn connect_multiple_streams(
-        &mut self, market: FuturesMarket, endpoints: &[String],
-    ) -> Result<()> {
-        self.connect_wss(FuturesWebsocketAPI::MultiStream.params(market, &endpoints.join("/")))
+fn decode_event(
+    msg: &str, stream: &mut Option<String>, event_time: &mut Option<u64>,
+) -> std::result::Result<FuturesWebsocketEvent, String> {
+    if let Some((event_type, time)) = array_event_type(msg).map_err(|e| e.to_string())? {
+        *event_time = time;
+        return match event_type.as_str() {
+            "24hrTicker" => decode(msg).map(FuturesWebsocketEvent::DayTickerAll),
+            "24hrMiniTicker" => decode(msg).map(FuturesWebsocketEvent::MiniTickerAll),
+            "markPriceUpdate" => decode(msg).map(FuturesWebsocketEvent::MarkPriceAll),
+            "assetIndexUpdate" => decode(msg).map(FuturesWebsocketEvent::AssetIndexAll),
+            _ => Err(unknown_event_type(&event_type)),
+        };
     }
 
-    fn connect_wss(&mut self, wss: String) -> Result<()> {
-        let url = Url::parse(&wss)?;
-        match connect(url) {
-            Ok(answer) => {
-                self.socket = Some(answer);
-                Ok(())
-            }
-            Err(e) => bail!(format!("Error during handshake {}", e)),
+    let header = EventHeader::parse(msg).map_err(|e| e.to_string())?;
+    if let Some(data) = header.data {
+        *stream = header.stream.map(Cow::into_owned);
+        return decode_event(data.get(), stream, event_time);
+    }
+    *event_time = header.time();
+    match header.event_type.as_deref() {
+        Some("24hrTicker") => decode(msg).map(FuturesWebsocketEvent::DayTicker),
+        Some("bookTicker") => decode(msg).map(FuturesWebsocketEvent::BookTicker),
+        Some("24hrMiniTicker") => decode(msg).map(FuturesWebsocketEvent::MiniTicker),
+        Some("ACCOUNT_UPDATE") => decode(msg).map(FuturesWebsocketEvent::AccountUpdate),
+        Some("ORDER_TRADE_UPDATE") => decode(msg).map(FuturesWebsocketEvent::OrderTrade),
+        Some("indexPriceUpdate") => decode(msg).map(FuturesWebsocketEvent::IndexPrice),
+        Some("markPriceUpdate") => decode(msg).map(FuturesWebsocketEvent::MarkPrice),
+        Some("trade") => decode(msg).map(FuturesWebsocketEvent::Trade),
+        Some("aggTrade") => decode(msg).map(FuturesWebsocketEvent::AggrTrades),
+        Some("kline") => decode(msg).map(FuturesWebsocketEvent::Kline),
+        Some("continuous_kline") => decode(msg).map(FuturesWebsocketEvent::ContinuousKline),
+        Some("indexPrice_kline") | Some("markPrice_kline") => {
+            decode(msg).map(FuturesWebsocketEvent::IndexKline)
+        }
+        Some("forceOrder") => decode(msg).map(FuturesWebsocketEvent::Liquidation),
+        Some("compositeIndex") => decode(msg).map(FuturesWebsocketEvent::CompositeIndex),
+        Some("contractInfo") => decode(msg).map(FuturesWebsocketEvent::ContractInfo),
+        Some("assetIndexUpdate") => decode(msg).map(FuturesWebsocketEvent::AssetIndex),
+        Some("depthUpdate") => decode(msg).map(FuturesWebsocketEvent::DepthOrderBook),
+        Some("listenKeyExpired") => decode(msg).map(FuturesWebsocketEvent::ListenKeyExpired),
+        Some("MARGIN_CALL") => decode(msg).map(FuturesWebsocketEvent::MarginCall),
+        // Carries either a leverage (`ac`) or a multi-assets mode (`ai`) update
+        Some("ACCOUNT_CONFIG_UPDATE") => decode(msg)
+            .map(FuturesWebsocketEvent::LeverageUpdate)
+            .or_else(|_| decode(msg).map(FuturesWebsocketEvent::MarginUpdate)),
+        Some("TRADE_LITE") => decode(msg).map(FuturesWebsocketEvent::TradeLite),
+        Some("STRATEGY_UPDATE") => decode(msg).map(FuturesWebsocketEvent::StrategyUpdate),
+        Some("GRID_UPDATE") => decode(msg).map(FuturesWebsocketEvent::GridUpdate),
+        Some("CONDITIONAL_ORDER_TRIGGER_REJECT") => {
+            decode(msg).map(FuturesWebsocketEvent::ConditionalOrderTriggerReject)
         }
+        Some(event_type) => Err(unknown_event_type(event_type)),
+        None if header.is_live_response() => {
+            parse_live_response(msg).map(FuturesWebsocketEvent::LiveResponse)
+        }
+        None if header.last_update_id => decode(msg).map(FuturesWebsocketEvent::OrderBook),
+        None => Err("missing event type".into()),
     }
+}
 
-    pub fn disconnect(&mut self) -> Result<()> {
-        if let Some(ref mut socket) = self.socket {
-            socket.0.close(None)?;
-            return Ok(());
-        }
-        bail!("Not able to close the connection");
+impl<'a> FuturesWebSockets<'a> {
+    pub fn new<Callback>(mut handler: Callback) -> FuturesWebSockets<'a>
+    where
+        Callback: FnMut(FuturesWebsocketEvent) -> Result<()> + Send + 'a,
+    {
+        FuturesWebSockets::new_with_stream_names(
+            move |message: StreamMessage<FuturesWebsocketEvent>| handler(message.event),
+        )
     }
 
-    pub fn test_handle_msg(&mut self, msg: &str) -> Result<()> {
-        self.handle_msg(msg)
+    /// Like `new`, the handler also receives the stream name of combined
+    /// stream messages.
+    pub fn new_with_stream_names<Callback>(handler: Callback) -> FuturesWebSockets<'a>
+    where
+        Callback: FnMut(StreamMessage<FuturesWebsocketEvent>) -> Result<()> + Send + 'a,
+    {
+        StreamConnection::with_handler(Box::new(handler))
     }
 
-    fn handle_msg(&mut self, msg: &str) -> Result<()> {
-        let value: serde_json::Value = serde_json::from_str(msg)?;
+    /// Connect to a stream, given either as a `Stream` or by its name.
+    pub fn connect<S>(&mut self, market: FuturesMarket, subscription: S) -> Result<()>
+    where
+        S: Into<String>,
+    {
+        self.connect_wss(FuturesWebsocketAPI::Default.params(market, &subscription.into()))
+    }
 
-        if let Some(data) = value.get("data") {
-            self.handle_msg(&data.to_string())?;
-            return Ok(());
-        }
+    pub fn connect_with_config<S>(
+        &mut self, market: FuturesMarket, subscription: S, config: &Config,
+    ) -> Result<()>
+    where
+        S: Into<String>,
+    {
+        self.connect_wss(
+            FuturesWebsocketAPI::Custom(market.ws_endpoint(config).into())
+                .params(market, &subscription.into()),
+        )
+    }
 
-        if let Ok(events) = serde_json::from_value::<FuturesEvents>(value.clone()) {
-            let action = match events {
-                FuturesEvents::Vec(v) => FuturesWebsocketEvent::DayTickerAll(v),
-                FuturesEvents::DayTickerEvent(v) => FuturesWebsocketEvent::DayTicker(v),
-                FuturesEvents::BookTickerEvent(v) => FuturesWebsocketEvent::BookTicker(v),
-                FuturesEvents::MiniTickerEvent(v) => FuturesWebsocketEvent::MiniTicker(v),
-                FuturesEvents::VecMiniTickerEvent(v) => FuturesWebsocketEvent::MiniTickerAll(v),
-                FuturesEvents::AccountUpdateEvent(v) => FuturesWebsocketEvent::AccountUpdate(v),
-                FuturesEvents::OrderTradeEvent(v) => FuturesWebsocketEvent::OrderTrade(v),
-                FuturesEvents::IndexPriceEvent(v) => FuturesWebsocketEvent::IndexPrice(v),
-                FuturesEvents::MarkPriceEvent(v) => FuturesWebsocketEvent::MarkPrice(v),
-                FuturesEvents::VecMarkPriceEvent(v) => FuturesWebsocketEvent::MarkPriceAll(v),
-                FuturesEvents::TradeEvent(v) => FuturesWebsocketEvent::Trade(v),
-                FuturesEvents::ContinuousKlineEvent(v) => FuturesWebsocketEvent::ContinuousKline(v),
-                FuturesEvents::IndexKlineEvent(v) => FuturesWebsocketEvent::IndexKline(v),
-                FuturesEvents::LiquidationEvent(v) => FuturesWebsocketEvent::Liquidation(v),
-                FuturesEvents::KlineEvent(v) => FuturesWebsocketEvent::Kline(v),
-                FuturesEvents::OrderBook(v) => FuturesWebsocketEvent::OrderBook(v),
-                FuturesEvents::DepthOrderBookEvent(v) => FuturesWebsocketEvent::DepthOrderBook(v),
-                FuturesEvents::AggrTradesEvent(v) => FuturesWebsocketEvent::AggrTrades(v),
-                FuturesEvents::ListenKeyExpiredEvent(v) => FuturesWebsocketEvent::ListenKeyExpired(v),
-                FuturesEvents::MarginCallEvent(v) => FuturesWebsocketEvent::MarginCall(v),
-                FuturesEvents::LeverageUpdateEvent(v) => FuturesWebsocketEvent::LeverageUpdate(v),
-                FuturesEvents::MarginUpdateEvent(v) => FuturesWebsocketEvent::MarginUpdate(v),
-            };
-            (self.handler)(action)?;
-        }
-        else {
-            log::warn!("The event {:?} cannot be handled", value);
-        }
-        Ok(())
+    pub fn connect_multiple_streams<S>(
+        &mut self, market: FuturesMarket, endpoints: &[S],
+    ) -> Result<()>
+    where
+        S: Clone + Into<String>,
+    {
+        self.connect_wss(
+            FuturesWebsocketAPI::MultiStream.params(market, &stream_names(endpoints).join("/")),
+        )
     }
 
-    pub fn event_loop(&mut self, running: &AtomicBool) -> Result<()> {
-        while running.load(Ordering::Relaxed) {
-            if let Some(ref mut socket) = self.socket {
-                let message = socket.0.read_message()?;
-                match message {
-                    Message::Text(msg) => {
-                        if let Err(e) = self.handle_msg(&msg) {
-                            bail!(format!("Error on handling stream message: {}", e));
-                        }
-                    }
-                    Message::Ping(_) | Message::Pong(_) | Message::Binary(_) => (),
-                    Message::Close(e) => bail!(ErrorKind::WebSocketDisconnected(e)),
-                }
-            }
-        }
-        Ok(())
+    pub fn connect_multiple_streams_with_config<S>(
+        &mut self, market: FuturesMarket, endpoints: &[S], config: &Config,
+    ) -> Result<()>
+    where
+        S: Clone + Into<String>,
+    {
+        self.connect_wss(
+            FuturesWebsocketAPI::CustomMultiStream(market.ws_endpoint(config).into())
+                .params(market, &stream_names(endpoints).join("/")),
+        )
     }
 }
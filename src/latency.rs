@@ -0,0 +1,166 @@
+//! Latency between the time Binance stamps an event with and the time it is
+//! received locally, to watch for network or processing lag.
+//!
+//! Latencies are in microseconds. They depend on the local clock being in sync
+//! with the Binance servers, and are negative when it is behind.
+
+use crate::streams::StreamMessage;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// Upper bounds of the histogram buckets, in microseconds
+const BUCKET_BOUNDS: [i64; 15] = [
+    250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000,
+    2_500_000, 5_000_000, 10_000_000,
+];
+
+/// Running histogram of latencies.
+///
+/// Latencies are counted in buckets from 250us to 10s, percentiles are
+/// therefore approximated by the upper bound of their bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    // One more bucket for the latencies above the last bound
+    buckets: [u64; BUCKET_BOUNDS.len() + 1],
+    count: u64,
+    sum: i128,
+    min: i64,
+    max: i64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        LatencyHistogram {
+            buckets: [0; BUCKET_BOUNDS.len() + 1],
+            count: 0,
+            sum: 0,
+            min: i64::MAX,
+            max: i64::MIN,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: i64) {
+        let bucket = BUCKET_BOUNDS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(BUCKET_BOUNDS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum += i128::from(latency);
+        self.min = self.min.min(latency);
+        self.max = self.max.max(latency);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.recorded(self.min)
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.recorded(self.max)
+    }
+
+    pub fn mean(&self) -> Option<i64> {
+        self.recorded((self.sum / i128::from(self.count.max(1))) as i64)
+    }
+
+    /// Latency below which `percentile` percent of the latencies are, e.g.
+    /// `percentile(99.0)`. Never above the largest latency recorded.
+    pub fn percentile(&self, percentile: f64) -> Option<i64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0) * self.count as f64).ceil() as u64;
+        let mut counted = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            counted += count;
+            if counted >= rank.max(1) {
+                let bound = BUCKET_BOUNDS.get(bucket).copied().unwrap_or(i64::MAX);
+                return Some(bound.min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// Count of each bucket along with its upper bound, `None` for the
+    /// latencies above 10s.
+    pub fn buckets(&self) -> Vec<(Option<i64>, u64)> {
+        BUCKET_BOUNDS
+            .iter()
+            .map(|bound| Some(*bound))
+            .chain(std::iter::once(None))
+            .zip(self.buckets.iter().copied())
+            .collect()
+    }
+
+    fn recorded(&self, value: i64) -> Option<i64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+/// Latency histograms per stream, shared between the websockets recording
+/// into it and whoever watches it. Clones record into the same histograms.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyMonitor {
+    histograms: Arc<Mutex<BTreeMap<String, LatencyHistogram>>>,
+}
+
+impl LatencyMonitor {
+    pub fn new() -> LatencyMonitor {
+        LatencyMonitor::default()
+    }
+
+    pub fn record(&self, stream: &str, latency: i64) {
+        let mut histograms = self.lock();
+        match histograms.get_mut(stream) {
+            Some(histogram) => histogram.record(latency),
+            None => {
+                let mut histogram = LatencyHistogram::default();
+                histogram.record(latency);
+                histograms.insert(stream.to_string(), histogram);
+            }
+        }
+    }
+
+    /// Histogram of a stream so far.
+    pub fn histogram(&self, stream: &str) -> Option<LatencyHistogram> {
+        self.lock().get(stream).cloned()
+    }
+
+    /// Histograms of all streams so far.
+    pub fn histograms(&self) -> BTreeMap<String, LatencyHistogram> {
+        self.lock().clone()
+    }
+
+    /// Histograms of all streams since the last call, to watch the latency
+    /// over successive periods.
+    pub fn take_histograms(&self) -> BTreeMap<String, LatencyHistogram> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// Record the latency of a received message under its stream name, or
+    /// under the stream of `url` for a raw stream connection.
+    pub(crate) fn record_message<E>(&self, url: Option<&str>, message: &StreamMessage<E>) {
+        let latency = match message.latency() {
+            Some(latency) => latency,
+            None => return,
+        };
+        let raw_stream = || Some(url?.rsplit_once("/ws/")?.1);
+        if let Some(stream) = message.stream.as_deref().or_else(raw_stream) {
+            self.record(stream, latency);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, LatencyHistogram>> {
+        self.histograms.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
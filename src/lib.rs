@@ -6,6 +6,8 @@
     unused_import_braces
 )]
 #![allow(clippy::needless_doctest_main)]
+// The error_chain `Error` is large and returned everywhere
+#![allow(clippy::result_large_err)]
 
 #[macro_use]
 extern crate error_chain;
@@ -18,11 +20,22 @@ pub mod model;
 
 pub mod account;
 pub mod api;
+pub mod capture;
 pub mod config;
 pub mod general;
+pub mod latency;
 pub mod market;
+pub mod orderbook;
 pub mod savings;
+pub mod streams;
 pub mod userstream;
+pub mod websocket_api;
 pub mod websockets;
 
+#[cfg(feature = "async-websocket")]
+pub mod async_websockets;
+
+#[cfg(feature = "test-support")]
+pub mod test_support;
+
 pub mod futures;
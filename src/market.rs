@@ -6,7 +6,12 @@ use std::collections::BTreeMap;
 use serde_json::Value;
 use crate::api::API;
 use crate::api::Spot;
+use std::collections::VecDeque;
 use std::convert::TryInto;
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[derive(Clone)]
 pub struct Market {
@@ -56,6 +61,16 @@ impl Market {
         self.client.get(API::Spot(Spot::Price), Some(request))
     }
 
+    // Latest price for SEVERAL symbols.
+    pub fn get_multiple_prices<I, S>(&self, symbols: I) -> Result<Vec<SymbolPrice>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let request = build_symbols_request(symbols, BTreeMap::new());
+        self.client.get(API::Spot(Spot::Price), Some(request))
+    }
+
     // Average price for ONE symbol.
     pub fn get_average_price<S>(&self, symbol: S) -> Result<AveragePrice>
     where
@@ -84,6 +99,16 @@ impl Market {
         self.client.get(API::Spot(Spot::BookTicker), Some(request))
     }
 
+    // -> Best price/qty on the order book for SEVERAL symbols
+    pub fn get_multiple_book_tickers<I, S>(&self, symbols: I) -> Result<Vec<Tickers>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let request = build_symbols_request(symbols, BTreeMap::new());
+        self.client.get(API::Spot(Spot::BookTicker), Some(request))
+    }
+
     // 24hr ticker price change statistics
     pub fn get_24h_price_stats<S>(&self, symbol: S) -> Result<PriceStats>
     where
@@ -100,6 +125,172 @@ impl Market {
         self.client.get(API::Spot(Spot::Ticker24hr), None)
     }
 
+    // 24hr ticker price change statistics for SEVERAL symbols
+    pub fn get_multiple_24h_price_stats<I, S>(&self, symbols: I) -> Result<Vec<PriceStats>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let request = build_symbols_request(symbols, BTreeMap::new());
+        self.client.get(API::Spot(Spot::Ticker24hr), Some(request))
+    }
+
+    // 24hr ticker statistics without the price change fields (type=MINI)
+    pub fn get_24h_mini_price_stats<S>(&self, symbol: S) -> Result<MiniPriceStats>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("type".into(), "MINI".into());
+        let request = build_request(parameters);
+        self.client.get(API::Spot(Spot::Ticker24hr), Some(request))
+    }
+
+    // 24hr ticker statistics (type=MINI) for SEVERAL symbols
+    pub fn get_multiple_24h_mini_price_stats<I, S>(&self, symbols: I) -> Result<Vec<MiniPriceStats>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("type".into(), "MINI".into());
+        let request = build_symbols_request(symbols, parameters);
+        self.client.get(API::Spot(Spot::Ticker24hr), Some(request))
+    }
+
+    // 24hr ticker statistics (type=MINI) for all symbols
+    pub fn get_all_24h_mini_price_stats(&self) -> Result<Vec<MiniPriceStats>> {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("type".into(), "MINI".into());
+        let request = build_request(parameters);
+        self.client.get(API::Spot(Spot::Ticker24hr), Some(request))
+    }
+
+    /// Price change statistics over a rolling window ending now.
+    ///
+    /// `window_size` is "1m" to "59m", "1h" to "23h" or "1d" to "7d".
+    pub fn get_rolling_window_price_stats<I, S1, S2>(
+        &self, symbols: I, window_size: S2,
+    ) -> Result<Vec<RollingWindowPriceStats>>
+    where
+        I: IntoIterator<Item = S1>,
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("windowSize".into(), window_size.into());
+        let request = build_symbols_request(symbols, parameters);
+        self.client.get(API::Spot(Spot::Ticker), Some(request))
+    }
+
+    /// Rolling window statistics without the price change fields (type=MINI).
+    pub fn get_rolling_window_mini_price_stats<I, S1, S2>(
+        &self, symbols: I, window_size: S2,
+    ) -> Result<Vec<MiniPriceStats>>
+    where
+        I: IntoIterator<Item = S1>,
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("type".into(), "MINI".into());
+        parameters.insert("windowSize".into(), window_size.into());
+        let request = build_symbols_request(symbols, parameters);
+        self.client.get(API::Spot(Spot::Ticker), Some(request))
+    }
+
+    /// Price change statistics of the current trading day (UTC by default).
+    pub fn get_trading_day_price_stats<I, S>(
+        &self, symbols: I,
+    ) -> Result<Vec<RollingWindowPriceStats>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let request = build_symbols_request(symbols, BTreeMap::new());
+        self.client
+            .get(API::Spot(Spot::TickerTradingDay), Some(request))
+    }
+
+    /// Trading day statistics without the price change fields (type=MINI).
+    pub fn get_trading_day_mini_price_stats<I, S>(&self, symbols: I) -> Result<Vec<MiniPriceStats>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("type".into(), "MINI".into());
+        let request = build_symbols_request(symbols, parameters);
+        self.client
+            .get(API::Spot(Spot::TickerTradingDay), Some(request))
+    }
+
+    /// Get the most recent trades (up to 'limit', default 500, max 1000).
+    pub fn get_trades<S1, S2>(&self, symbol: S1, limit: S2) -> Result<Vec<Trade>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<u16>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        if let Some(lt) = limit.into() {
+            parameters.insert("limit".into(), format!("{}", lt));
+        }
+        let request = build_request(parameters);
+        self.client.get(API::Spot(Spot::Trades), Some(request))
+    }
+
+    /// Get older trades, starting at trade id 'from_id' (most recent trades if omitted).
+    ///
+    /// This is a MARKET_DATA endpoint, so the client needs an API key.
+    pub fn get_historical_trades<S1, S2, S3>(
+        &self, symbol: S1, from_id: S2, limit: S3,
+    ) -> Result<Vec<Trade>>
+    where
+        S1: Into<String>,
+        S2: Into<Option<u64>>,
+        S3: Into<Option<u16>>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        if let Some(lt) = limit.into() {
+            parameters.insert("limit".into(), format!("{}", lt));
+        }
+        if let Some(fi) = from_id.into() {
+            parameters.insert("fromId".into(), format!("{}", fi));
+        }
+        let request = build_request(parameters);
+        self.client
+            .get_with_api_key(API::Spot(Spot::HistoricalTrades), Some(request))
+    }
+
+    /// Iterate over the trades of `symbol`, walking trade ids from `from_id`.
+    ///
+    /// `Forward` yields ascending ids starting at `from_id` (or the first trade
+    /// if omitted) up to the latest trade. `Backward` yields descending ids
+    /// starting at `from_id` (or the latest trade if omitted) down to trade 0.
+    /// Uses `get_historical_trades`, so the client needs an API key.
+    pub fn get_trades_history<S1, S2>(
+        &self, symbol: S1, from_id: S2, direction: TradeDirection,
+    ) -> TradesHistory<'_>
+    where
+        S1: Into<String>,
+        S2: Into<Option<u64>>,
+    {
+        TradesHistory {
+            market: self,
+            symbol: symbol.into(),
+            direction,
+            next_id: from_id.into(),
+            limit: 1000,
+            pause: Duration::from_millis(300),
+            last_request: None,
+            buffer: VecDeque::new(),
+            done: false,
+        }
+    }
+
     /// Get aggregated historical trades.
     ///
     /// If you provide start_time, you also need to provide end_time.
@@ -138,10 +329,45 @@ impl Market {
     }
 
     // Returns up to 'limit' klines for given symbol and interval ("1m", "5m", ...)
+    // The interval can also be given as a `KlineInterval`.
     // https://github.com/binance-exchange/binance-official-api-docs/blob/master/rest-api.md#klinecandlestick-data
     pub fn get_klines<S1, S2, S3, S4, S5>(
         &self, symbol: S1, interval: S2, limit: S3, start_time: S4, end_time: S5,
     ) -> Result<KlineSummaries>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        self.get_kline_summaries(Spot::Klines, symbol, interval, limit, start_time, end_time)
+    }
+
+    // Same as `get_klines`, with the klines modified for presentation of candlestick charts
+    pub fn get_ui_klines<S1, S2, S3, S4, S5>(
+        &self, symbol: S1, interval: S2, limit: S3, start_time: S4, end_time: S5,
+    ) -> Result<KlineSummaries>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<Option<u16>>,
+        S4: Into<Option<u64>>,
+        S5: Into<Option<u64>>,
+    {
+        self.get_kline_summaries(
+            Spot::UiKlines,
+            symbol,
+            interval,
+            limit,
+            start_time,
+            end_time,
+        )
+    }
+
+    fn get_kline_summaries<S1, S2, S3, S4, S5>(
+        &self, route: Spot, symbol: S1, interval: S2, limit: S3, start_time: S4, end_time: S5,
+    ) -> Result<KlineSummaries>
     where
         S1: Into<String>,
         S2: Into<String>,
@@ -166,7 +392,7 @@ impl Market {
         }
 
         let request = build_request(parameters);
-        let data: Vec<Vec<Value>> = self.client.get(API::Spot(Spot::Klines), Some(request))?;
+        let data: Vec<Vec<Value>> = self.client.get(API::Spot(route), Some(request))?;
 
         let klines = KlineSummaries::AllKlineSummaries(
             data.iter()
@@ -176,4 +402,431 @@ impl Market {
 
         Ok(klines)
     }
+
+    /// Iterate over all klines of `symbol` opened within `[start_time, end_time)`.
+    ///
+    /// The range is fetched page by page (1000 klines per request by default),
+    /// so it is not bound by the per-request limit of `get_klines`.
+    pub fn get_klines_history<S>(
+        &self, symbol: S, interval: KlineInterval, start_time: u64, end_time: u64,
+    ) -> KlineHistory<'_>
+    where
+        S: Into<String>,
+    {
+        let symbol = symbol.into();
+        KlineHistory::new(
+            move |start, end, limit| self.get_klines(symbol.as_str(), interval, limit, start, end),
+            interval,
+            start_time,
+            end_time,
+        )
+    }
+}
+
+fn build_symbols_request<I, S>(symbols: I, mut parameters: BTreeMap<String, String>) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    parameters.insert("symbols".into(), build_symbols_param(symbols));
+    build_request(parameters)
+}
+
+/// Kline/candlestick chart intervals.
+///
+/// `OneSecond` is only available on the spot market.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KlineInterval {
+    OneSecond,
+    OneMinute,
+    ThreeMinutes,
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+    OneHour,
+    TwoHours,
+    FourHours,
+    SixHours,
+    EightHours,
+    TwelveHours,
+    OneDay,
+    ThreeDays,
+    OneWeek,
+    OneMonth,
+}
+
+impl KlineInterval {
+    /// Length of the interval in milliseconds.
+    ///
+    /// Returns `None` for `OneMonth`, since months have no fixed length.
+    pub fn duration_ms(self) -> Option<u64> {
+        const SECOND: u64 = 1000;
+        const MINUTE: u64 = 60 * SECOND;
+        const HOUR: u64 = 60 * MINUTE;
+        const DAY: u64 = 24 * HOUR;
+
+        match self {
+            KlineInterval::OneSecond => Some(SECOND),
+            KlineInterval::OneMinute => Some(MINUTE),
+            KlineInterval::ThreeMinutes => Some(3 * MINUTE),
+            KlineInterval::FiveMinutes => Some(5 * MINUTE),
+            KlineInterval::FifteenMinutes => Some(15 * MINUTE),
+            KlineInterval::ThirtyMinutes => Some(30 * MINUTE),
+            KlineInterval::OneHour => Some(HOUR),
+            KlineInterval::TwoHours => Some(2 * HOUR),
+            KlineInterval::FourHours => Some(4 * HOUR),
+            KlineInterval::SixHours => Some(6 * HOUR),
+            KlineInterval::EightHours => Some(8 * HOUR),
+            KlineInterval::TwelveHours => Some(12 * HOUR),
+            KlineInterval::OneDay => Some(DAY),
+            KlineInterval::ThreeDays => Some(3 * DAY),
+            KlineInterval::OneWeek => Some(7 * DAY),
+            KlineInterval::OneMonth => None,
+        }
+    }
+}
+
+impl fmt::Display for KlineInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            KlineInterval::OneSecond => "1s",
+            KlineInterval::OneMinute => "1m",
+            KlineInterval::ThreeMinutes => "3m",
+            KlineInterval::FiveMinutes => "5m",
+            KlineInterval::FifteenMinutes => "15m",
+            KlineInterval::ThirtyMinutes => "30m",
+            KlineInterval::OneHour => "1h",
+            KlineInterval::TwoHours => "2h",
+            KlineInterval::FourHours => "4h",
+            KlineInterval::SixHours => "6h",
+            KlineInterval::EightHours => "8h",
+            KlineInterval::TwelveHours => "12h",
+            KlineInterval::OneDay => "1d",
+            KlineInterval::ThreeDays => "3d",
+            KlineInterval::OneWeek => "1w",
+            KlineInterval::OneMonth => "1M",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for KlineInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "1s" => Ok(Self::OneSecond),
+            "1m" => Ok(Self::OneMinute),
+            "3m" => Ok(Self::ThreeMinutes),
+            "5m" => Ok(Self::FiveMinutes),
+            "15m" => Ok(Self::FifteenMinutes),
+            "30m" => Ok(Self::ThirtyMinutes),
+            "1h" => Ok(Self::OneHour),
+            "2h" => Ok(Self::TwoHours),
+            "4h" => Ok(Self::FourHours),
+            "6h" => Ok(Self::SixHours),
+            "8h" => Ok(Self::EightHours),
+            "12h" => Ok(Self::TwelveHours),
+            "1d" => Ok(Self::OneDay),
+            "3d" => Ok(Self::ThreeDays),
+            "1w" => Ok(Self::OneWeek),
+            "1M" => Ok(Self::OneMonth),
+            other => Err(format!("Invalid KlineInterval: '{}'", other)),
+        }
+    }
+}
+
+impl From<KlineInterval> for String {
+    fn from(item: KlineInterval) -> Self {
+        item.to_string()
+    }
+}
+
+/// A range of klines that the exchange did not return, e.g. during maintenance.
+///
+/// `from` is the open time of the first missing kline and `to` the open time
+/// of the kline that followed the gap, or the end of the requested range
+/// (capped at the current time) when no kline followed it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KlineGap {
+    pub from: i64,
+    pub to: i64,
+}
+
+type KlinePageFetcher<'a> = Box<dyn FnMut(u64, u64, u16) -> Result<KlineSummaries> + 'a>;
+
+/// Paged download of a `[start_time, end_time)` kline range.
+///
+/// Created by `Market::get_klines_history` and the `*_history` methods of
+/// `futures::market::FuturesMarket`. Klines are yielded in ascending open
+/// time; klines repeated across page boundaries are skipped, and missing
+/// ranges are recorded in `gaps()`.
+pub struct KlineHistory<'a> {
+    fetch: KlinePageFetcher<'a>,
+    interval: KlineInterval,
+    start_time: u64,
+    next_start: u64,
+    end_time: u64,
+    limit: u16,
+    max_limit: u16,
+    pause: Duration,
+    last_request: Option<Instant>,
+    buffer: VecDeque<KlineSummary>,
+    last_open_time: Option<i64>,
+    gaps: Vec<KlineGap>,
+    error: Option<Error>,
+    done: bool,
+}
+
+impl<'a> KlineHistory<'a> {
+    pub fn new<F>(fetch: F, interval: KlineInterval, start_time: u64, end_time: u64) -> Self
+    where
+        F: FnMut(u64, u64, u16) -> Result<KlineSummaries> + 'a,
+    {
+        KlineHistory {
+            fetch: Box::new(fetch),
+            interval,
+            start_time,
+            next_start: start_time,
+            end_time,
+            limit: 1000,
+            max_limit: 1000,
+            // Keeps a full-sized futures page (weight 10) well below 2400 weight per minute
+            pause: Duration::from_millis(300),
+            last_request: None,
+            buffer: VecDeque::new(),
+            last_open_time: None,
+            gaps: Vec::new(),
+            error: None,
+            done: start_time >= end_time,
+        }
+    }
+
+    // Futures have no 1s klines: yield a single error instead of requesting pages
+    pub(crate) fn reject_one_second(mut self) -> Self {
+        if self.interval == KlineInterval::OneSecond {
+            self.error = Some("1s klines are only available on the spot market".into());
+            self.done = true;
+        }
+        self
+    }
+
+    // Futures pages hold up to 1500 klines
+    pub(crate) fn set_max_limit(mut self, max_limit: u16) -> Self {
+        self.max_limit = max_limit;
+        self
+    }
+
+    /// Number of klines requested per page, capped at 1000 on spot and 1500 on
+    /// futures. 0 keeps the default of 1000.
+    pub fn set_limit(mut self, limit: u16) -> Self {
+        if limit > 0 {
+            self.limit = limit.min(self.max_limit);
+        }
+        self
+    }
+
+    /// Minimum delay between two page requests.
+    pub fn set_pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    /// Gaps detected so far, including missing klines at the start or end of the range.
+    pub fn gaps(&self) -> &[KlineGap] {
+        &self.gaps
+    }
+
+    fn fetch_page(&mut self) -> Result<()> {
+        wait_for_pause(&mut self.last_request, self.pause);
+
+        let KlineSummaries::AllKlineSummaries(page) =
+            (self.fetch)(self.next_start, self.end_time - 1, self.limit)?;
+
+        let mut advanced = false;
+        for kline in page {
+            if kline.open_time < self.next_start as i64 || kline.open_time >= self.end_time as i64 {
+                continue;
+            }
+            if matches!(self.last_open_time, Some(last) if kline.open_time <= last) {
+                continue;
+            }
+            if let Some(expected) = self.expected_open_time(kline.open_time) {
+                self.record_gap(expected, kline.open_time);
+            }
+            self.last_open_time = Some(kline.open_time);
+            self.buffer.push_back(kline);
+            advanced = true;
+        }
+
+        // Pages may be shorter than the limit before the end of the range, the
+        // download only ends on a page without new klines
+        match self.last_open_time {
+            Some(last_open_time) if advanced => self.next_start = last_open_time as u64 + 1,
+            _ => self.finish(),
+        }
+        if self.next_start >= self.end_time {
+            self.finish();
+        }
+
+        Ok(())
+    }
+
+    // Open time the kline at `open_time` should have had, given the previous
+    // kline or, for the first one, the start of the range
+    fn expected_open_time(&self, open_time: i64) -> Option<i64> {
+        let duration = self.interval.duration_ms()? as i64;
+        Some(match self.last_open_time {
+            Some(last_open_time) => last_open_time + duration,
+            None => open_time - (open_time - self.start_time as i64) / duration * duration,
+        })
+    }
+
+    // Record the missing tail of the range, which cannot reach past now
+    fn finish(&mut self) {
+        if self.done {
+            return;
+        }
+        self.done = true;
+        if let Some(duration) = self.interval.duration_ms() {
+            let from = match self.last_open_time {
+                Some(last_open_time) => last_open_time + duration as i64,
+                None => self.start_time as i64,
+            };
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(i64::MAX, |now| now.as_millis() as i64);
+            self.record_gap(from, now.min(self.end_time as i64));
+        }
+    }
+
+    fn record_gap(&mut self, from: i64, to: i64) {
+        if from < to {
+            self.gaps.push(KlineGap { from, to });
+        }
+    }
+}
+
+impl<'a> Iterator for KlineHistory<'a> {
+    type Item = Result<KlineSummary>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        while self.buffer.is_empty() && !self.done {
+            if let Err(e) = self.fetch_page() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+}
+
+/// Direction in which `TradesHistory` walks trade ids.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TradeDirection {
+    Forward,
+    Backward,
+}
+
+/// Paged download of the complete trade history of a symbol.
+///
+/// Created by `Market::get_trades_history`.
+pub struct TradesHistory<'a> {
+    market: &'a Market,
+    symbol: String,
+    direction: TradeDirection,
+    next_id: Option<u64>,
+    limit: u16,
+    pause: Duration,
+    last_request: Option<Instant>,
+    buffer: VecDeque<Trade>,
+    done: bool,
+}
+
+impl<'a> TradesHistory<'a> {
+    /// Number of trades requested per page (max 1000), 0 keeps the default of 1000.
+    pub fn set_limit(mut self, limit: u16) -> Self {
+        if limit > 0 {
+            self.limit = limit;
+        }
+        self
+    }
+
+    /// Minimum delay between two page requests.
+    pub fn set_pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    fn fetch_page(&mut self) -> Result<()> {
+        wait_for_pause(&mut self.last_request, self.pause);
+
+        match self.direction {
+            TradeDirection::Forward => {
+                let from_id = self.next_id.unwrap_or(0);
+                let page =
+                    self.market
+                        .get_historical_trades(self.symbol.as_str(), from_id, self.limit)?;
+
+                let page: Vec<Trade> = page.into_iter().filter(|t| t.id >= from_id).collect();
+                match page.last() {
+                    Some(last) => self.next_id = Some(last.id + 1),
+                    None => self.done = true,
+                }
+                self.buffer.extend(page);
+            }
+            TradeDirection::Backward => {
+                let page = match self.next_id {
+                    Some(last_id) => {
+                        let from_id = last_id.saturating_sub(u64::from(self.limit) - 1);
+                        let limit = (last_id - from_id + 1) as u16;
+                        self.market
+                            .get_historical_trades(self.symbol.as_str(), from_id, limit)?
+                    }
+                    None => {
+                        self.market
+                            .get_historical_trades(self.symbol.as_str(), None, self.limit)?
+                    }
+                };
+
+                let last_id = self.next_id.unwrap_or(u64::MAX);
+                let page: Vec<Trade> = page.into_iter().filter(|t| t.id <= last_id).collect();
+                match page.first() {
+                    Some(first) if first.id > 0 => self.next_id = Some(first.id - 1),
+                    _ => self.done = true,
+                }
+                self.buffer.extend(page.into_iter().rev());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for TradesHistory<'a> {
+    type Item = Result<Trade>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffer.is_empty() && !self.done {
+            if let Err(e) = self.fetch_page() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+}
+
+// Sleep until at least `pause` has passed since the previous request
+fn wait_for_pause(last_request: &mut Option<Instant>, pause: Duration) {
+    if let Some(last_request) = last_request {
+        let elapsed = last_request.elapsed();
+        if elapsed < pause {
+            thread::sleep(pause - elapsed);
+        }
+    }
+    *last_request = Some(Instant::now());
 }
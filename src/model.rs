@@ -12,6 +12,19 @@ pub struct ServerTime {
     pub server_time: u64,
 }
 
+/// State of a WebSocket API session
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStatus {
+    /// The logged on API key, if any
+    pub api_key: Option<String>,
+    pub authorized_since: Option<u64>,
+    pub connected_since: u64,
+    pub return_rate_limits: bool,
+    pub server_time: u64,
+    pub user_data_stream: Option<bool>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ExchangeInformation {
@@ -28,6 +41,8 @@ pub struct RateLimit {
     pub interval: String,
     pub interval_num: u16,
     pub limit: u64,
+    /// Current usage, reported with WebSocket API responses
+    pub count: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -332,6 +347,57 @@ pub struct PriceStats {
     pub count: u64,
 }
 
+/// 24hr, rolling window or trading day statistics of a `type=MINI` request
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MiniPriceStats {
+    pub symbol: String,
+    #[serde(with = "string_or_float")]
+    pub open_price: f64,
+    #[serde(with = "string_or_float")]
+    pub high_price: f64,
+    #[serde(with = "string_or_float")]
+    pub low_price: f64,
+    #[serde(with = "string_or_float")]
+    pub last_price: f64,
+    #[serde(with = "string_or_float")]
+    pub volume: f64,
+    #[serde(with = "string_or_float")]
+    pub quote_volume: f64,
+    pub open_time: u64,
+    pub close_time: u64,
+    pub first_id: i64,
+    pub last_id: i64,
+    pub count: u64,
+}
+
+/// Rolling window or trading day price change statistics
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RollingWindowPriceStats {
+    pub symbol: String,
+    pub price_change: String,
+    pub price_change_percent: String,
+    pub weighted_avg_price: String,
+    #[serde(with = "string_or_float")]
+    pub open_price: f64,
+    #[serde(with = "string_or_float")]
+    pub high_price: f64,
+    #[serde(with = "string_or_float")]
+    pub low_price: f64,
+    #[serde(with = "string_or_float")]
+    pub last_price: f64,
+    #[serde(with = "string_or_float")]
+    pub volume: f64,
+    #[serde(with = "string_or_float")]
+    pub quote_volume: f64,
+    pub open_time: u64,
+    pub close_time: u64,
+    pub first_id: i64,
+    pub last_id: i64,
+    pub count: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AggTrade {
     #[serde(rename = "T")]
@@ -352,6 +418,24 @@ pub struct AggTrade {
     pub qty: f64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Trade {
+    pub id: u64,
+    #[serde(with = "string_or_float")]
+    pub price: f64,
+    #[serde(with = "string_or_float")]
+    pub qty: f64,
+    #[serde(with = "string_or_float")]
+    pub quote_qty: f64,
+    pub time: u64,
+    pub is_buyer_maker: bool,
+    pub is_best_match: bool,
+}
+
+/// Balances of the assets changed by an account event (`outboundAccountPosition`).
+///
+/// https://github.com/binance/binance-spot-api-docs/blob/master/user-data-stream.md#account-update
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountUpdateEvent {
@@ -361,36 +445,33 @@ pub struct AccountUpdateEvent {
     #[serde(rename = "E")]
     pub event_time: u64,
 
-    m: u64,
-    t: u64,
-    b: u64,
-    s: u64,
-
-    #[serde(rename = "T")]
-    t_ignore: bool,
-    #[serde(rename = "W")]
-    w_ignore: bool,
-    #[serde(rename = "D")]
-    d_ignore: bool,
+    #[serde(rename = "u")]
+    pub last_account_update_time: u64,
 
     #[serde(rename = "B")]
     pub balance: Vec<EventBalance>,
 }
 
+/// Deposit, withdrawal or transfer of an asset (`balanceUpdate`).
+///
+/// https://github.com/binance/binance-spot-api-docs/blob/master/user-data-stream.md#balance-update
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BalanceUpdateEvent {
-    #[serde(rename = "B")]
-    pub balance: Vec<EventBalance>,
-
     #[serde(rename = "e")]
     pub event_type: String,
 
     #[serde(rename = "E")]
     pub event_time: u64,
 
-    #[serde(rename = "u")]
-    pub last_account_update_time: u64,
+    #[serde(rename = "a")]
+    pub asset: String,
+
+    #[serde(rename = "d")]
+    pub balance_delta: String,
+
+    #[serde(rename = "T")]
+    pub clear_time: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -404,6 +485,12 @@ pub struct EventBalance {
     pub locked: String,
 }
 
+/// Order update (`executionReport`).
+///
+/// The fields which only apply to some orders, trades or self-trade
+/// preventions are `None` when not sent.
+///
+/// https://github.com/binance/binance-spot-api-docs/blob/master/user-data-stream.md#order-update
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderTradeEvent {
@@ -434,17 +521,22 @@ pub struct OrderTradeEvent {
     #[serde(rename = "p")]
     pub price: String,
 
-    #[serde(skip, rename = "P")]
-    pub p_ignore: String,
+    #[serde(rename = "P")]
+    pub stop_price: String,
+
+    #[serde(rename = "d")]
+    pub trailing_delta: Option<u64>,
 
-    #[serde(skip, rename = "F")]
-    pub f_ignore: String,
+    #[serde(rename = "F")]
+    pub iceberg_qty: String,
 
-    #[serde(skip)]
-    pub g: i32,
+    /// -1 unless the order is part of an order list
+    #[serde(rename = "g")]
+    pub order_list_id: i64,
 
-    #[serde(skip, rename = "C")]
-    pub c_ignore: Option<String>,
+    /// Client order id of the order being canceled, empty otherwise
+    #[serde(rename = "C")]
+    pub original_client_order_id: Option<String>,
 
     #[serde(rename = "x")]
     pub execution_type: String,
@@ -470,26 +562,194 @@ pub struct OrderTradeEvent {
     #[serde(rename = "n")]
     pub commission: String,
 
-    #[serde(skip, rename = "N")]
-    pub asset_commisioned: Option<String>,
+    #[serde(rename = "N")]
+    pub commission_asset: Option<String>,
 
     #[serde(rename = "T")]
     pub trade_order_time: u64,
 
+    /// -1 unless the execution is a trade
     #[serde(rename = "t")]
     pub trade_id: i64,
 
+    #[serde(rename = "v")]
+    pub prevented_match_id: Option<u64>,
+
     #[serde(skip, rename = "I")]
     pub i_ignore: u64,
 
-    #[serde(skip)]
-    pub w: bool,
+    /// Whether the order is on the book
+    #[serde(rename = "w")]
+    pub is_working: bool,
 
+    /// Whether the trade is the maker side
     #[serde(rename = "m")]
     pub is_buyer_maker: bool,
 
     #[serde(skip, rename = "M")]
     pub m_ignore: bool,
+
+    #[serde(rename = "O")]
+    pub order_creation_time: u64,
+
+    #[serde(rename = "Z")]
+    pub cumulative_quote_qty: String,
+
+    #[serde(rename = "Y")]
+    pub last_quote_qty: String,
+
+    #[serde(rename = "Q")]
+    pub quote_order_qty: String,
+
+    #[serde(rename = "W")]
+    pub working_time: Option<u64>,
+
+    #[serde(rename = "V")]
+    pub self_trade_prevention_mode: Option<String>,
+
+    #[serde(rename = "D")]
+    pub trailing_time: Option<u64>,
+
+    #[serde(rename = "j")]
+    pub strategy_id: Option<u64>,
+
+    #[serde(rename = "J")]
+    pub strategy_type: Option<u64>,
+
+    #[serde(rename = "A")]
+    pub prevented_qty: Option<String>,
+
+    #[serde(rename = "B")]
+    pub last_prevented_qty: Option<String>,
+
+    #[serde(rename = "u")]
+    pub trade_group_id: Option<i64>,
+
+    #[serde(rename = "U")]
+    pub counter_order_id: Option<i64>,
+
+    #[serde(rename = "Cs")]
+    pub counter_symbol: Option<String>,
+
+    #[serde(rename = "pl")]
+    pub prevented_execution_qty: Option<String>,
+
+    #[serde(rename = "pL")]
+    pub prevented_execution_price: Option<String>,
+
+    #[serde(rename = "pY")]
+    pub prevented_execution_quote_qty: Option<String>,
+
+    #[serde(rename = "b")]
+    pub match_type: Option<String>,
+
+    #[serde(rename = "a")]
+    pub allocation_id: Option<u64>,
+
+    #[serde(rename = "k")]
+    pub working_floor: Option<String>,
+
+    #[serde(rename = "uS")]
+    pub used_sor: Option<bool>,
+}
+
+/// Status of an order list, e.g. an OCO (`listStatus`).
+///
+/// https://github.com/binance/binance-spot-api-docs/blob/master/user-data-stream.md#order-update
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListStatusEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "g")]
+    pub order_list_id: i64,
+
+    #[serde(rename = "c")]
+    pub contingency_type: String,
+
+    #[serde(rename = "l")]
+    pub list_status_type: String,
+
+    #[serde(rename = "L")]
+    pub list_order_status: String,
+
+    #[serde(rename = "r")]
+    pub list_reject_reason: String,
+
+    #[serde(rename = "C")]
+    pub list_client_order_id: String,
+
+    #[serde(rename = "T")]
+    pub transaction_time: u64,
+
+    #[serde(rename = "O")]
+    pub orders: Vec<ListStatusOrder>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListStatusOrder {
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "i")]
+    pub order_id: u64,
+
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+}
+
+/// The listen key of the user data stream expired (`listenKeyExpired`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenKeyExpiredEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    pub listen_key: Option<String>,
+}
+
+/// Change of the amount of an asset locked by an external system, e.g. as
+/// collateral (`externalLockUpdate`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalLockUpdateEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "a")]
+    pub asset: String,
+
+    #[serde(rename = "d")]
+    pub delta: String,
+
+    #[serde(rename = "T")]
+    pub transaction_time: u64,
+}
+
+/// The user data stream was terminated, no further events follow
+/// (`eventStreamTerminated`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EventStreamTerminatedEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
 }
 
 /// The Aggregate Trade Streams push trade information that is aggregated for a single taker order.
@@ -678,6 +938,148 @@ pub struct LiquidationOrder {
     pub order_trade_time: u64,
 }
 
+// https://binance-docs.github.io/apidocs/futures/en/#composite-index-symbol-information-streams
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompositeIndexEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "p")]
+    pub price: String,
+
+    /// Asset the components are weighted by, `baseAsset` or `quoteAsset`
+    #[serde(rename = "C")]
+    pub composition_type: Option<String>,
+
+    #[serde(rename = "c")]
+    pub composition: Vec<CompositeIndexComponent>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompositeIndexComponent {
+    #[serde(rename = "b")]
+    pub base_asset: String,
+
+    #[serde(rename = "q")]
+    pub quote_asset: String,
+
+    #[serde(rename = "w")]
+    pub weight_in_quantity: String,
+
+    #[serde(rename = "W")]
+    pub weight_in_percentage: String,
+
+    #[serde(rename = "i")]
+    pub index_price: String,
+}
+
+// https://binance-docs.github.io/apidocs/futures/en/#contract-info-stream
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractInfoEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "ps")]
+    pub pair: String,
+
+    #[serde(rename = "ct")]
+    pub contract_type: String,
+
+    #[serde(rename = "dt")]
+    pub delivery_date: u64,
+
+    #[serde(rename = "ot")]
+    pub onboard_date: u64,
+
+    #[serde(rename = "cs")]
+    pub contract_status: String,
+
+    /// Only sent when the leverage brackets of the symbol change
+    #[serde(rename = "bks")]
+    pub brackets: Option<Vec<ContractBracket>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractBracket {
+    #[serde(rename = "bs")]
+    pub bracket: u32,
+
+    #[serde(rename = "bnf")]
+    pub notional_floor: f64,
+
+    #[serde(rename = "bnc")]
+    pub notional_cap: f64,
+
+    #[serde(rename = "mmr")]
+    pub maintenance_margin_ratio: f64,
+
+    #[serde(rename = "cf")]
+    pub cum: f64,
+
+    #[serde(rename = "mi")]
+    pub min_leverage: u32,
+
+    #[serde(rename = "ma")]
+    pub max_leverage: u32,
+}
+
+// https://binance-docs.github.io/apidocs/futures/en/#multi-assets-mode-asset-index
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetIndexEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "i")]
+    pub index_price: String,
+
+    #[serde(rename = "b")]
+    pub bid_buffer: String,
+
+    #[serde(rename = "a")]
+    pub ask_buffer: String,
+
+    #[serde(rename = "B")]
+    pub bid_rate: String,
+
+    #[serde(rename = "A")]
+    pub ask_rate: String,
+
+    #[serde(rename = "q")]
+    pub auto_exchange_bid_buffer: String,
+
+    #[serde(rename = "g")]
+    pub auto_exchange_ask_buffer: String,
+
+    #[serde(rename = "Q")]
+    pub auto_exchange_bid_rate: String,
+
+    #[serde(rename = "G")]
+    pub auto_exchange_ask_rate: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BookTickerEvent {
@@ -804,6 +1206,84 @@ pub struct MiniTickerEvent {
     pub quote_volume: String,
 }
 
+/// Rolling window statistics of the `1hTicker`, `4hTicker` and `1dTicker` streams.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RollingWindowTickerEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "p")]
+    pub price_change: String,
+
+    #[serde(rename = "P")]
+    pub price_change_percent: String,
+
+    #[serde(rename = "o")]
+    pub open: String,
+
+    #[serde(rename = "h")]
+    pub high: String,
+
+    #[serde(rename = "l")]
+    pub low: String,
+
+    #[serde(rename = "c")]
+    pub close: String,
+
+    #[serde(rename = "w")]
+    pub average_price: String,
+
+    #[serde(rename = "v")]
+    pub volume: String,
+
+    #[serde(rename = "q")]
+    pub quote_volume: String,
+
+    #[serde(rename = "O")]
+    pub open_time: u64,
+
+    #[serde(rename = "C")]
+    pub close_time: u64,
+
+    #[serde(rename = "F")]
+    pub first_trade_id: i64,
+
+    #[serde(rename = "L")]
+    pub last_trade_id: i64,
+
+    #[serde(rename = "n")]
+    pub num_trades: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AvgPriceEvent {
+    #[serde(rename = "e")]
+    pub event_type: String,
+
+    #[serde(rename = "E")]
+    pub event_time: u64,
+
+    #[serde(rename = "s")]
+    pub symbol: String,
+
+    #[serde(rename = "i")]
+    pub interval: String,
+
+    #[serde(rename = "w")]
+    pub average_price: String,
+
+    #[serde(rename = "T")]
+    pub last_trade_time: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct KlineEvent {
@@ -1111,6 +1591,62 @@ pub struct DepthOrderBookEvent {
     pub asks: Vec<PriceLevel>,
 }
 
+/// Emitted by a websocket with a reconnect policy once the connection has been
+/// re-established and the original subscriptions replayed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconnectedEvent {
+    /// Number of connection attempts it took to reconnect.
+    pub attempts: u32,
+
+    /// Why the previous connection was abandoned.
+    pub reason: String,
+}
+
+/// Response to a live request (`SUBSCRIBE`, `LIST_SUBSCRIPTIONS`, ...) sent on a
+/// websocket connection.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LiveResponse {
+    /// Id of the request, missing for malformed requests.
+    pub id: Option<u64>,
+
+    pub result: Value,
+
+    pub error: Option<BinanceContentError>,
+}
+
+impl LiveResponse {
+    /// Parse a websocket message answering a live request. Binance answers
+    /// `{"result": .., "id": ..}`, `{"error": {"code": .., "msg": ..}, "id": ..}`
+    /// or `{"code": .., "msg": .., "id": ..}`.
+    pub fn from_value(value: &Value) -> Option<LiveResponse> {
+        let error_value = value.get("error").unwrap_or(value);
+        let error = match (error_value.get("code"), error_value.get("msg")) {
+            (Some(code), Some(msg)) => Some(BinanceContentError {
+                code: from_value(code.clone()).ok()?,
+                msg: msg.as_str()?.to_string(),
+            }),
+            _ => None,
+        };
+        if value.get("result").is_none() && error.is_none() {
+            return None;
+        }
+
+        Some(LiveResponse {
+            id: value.get("id").and_then(Value::as_u64),
+            result: value.get("result").cloned().unwrap_or(Value::Null),
+            error,
+        })
+    }
+
+    pub fn into_result(self) -> Result<Value> {
+        match self.error {
+            Some(error) => bail!(ErrorKind::BinanceError(error)),
+            None => Ok(self.result),
+        }
+    }
+}
+
 /// Response to the Savings API get all coins request
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1232,7 +1768,7 @@ pub(crate) mod string_or_float {
 pub(crate) mod string_or_float_opt {
     use std::fmt;
 
-    use serde::{Serializer, Deserialize, Deserializer};
+    use serde::{Serializer, Deserializer};
 
     pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -1249,13 +1785,6 @@ pub(crate) mod string_or_float_opt {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(untagged)]
-        enum StringOrFloat {
-            String(String),
-            Float(f64),
-        }
-
         Ok(Some(crate::model::string_or_float::deserialize(
             deserializer,
         )?))
@@ -0,0 +1,335 @@
+use crate::errors::*;
+use crate::market::Market;
+use crate::model::{DepthOrderBookEvent, OrderBook, PriceLevel};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+// Delay before fetching again a snapshot older than the buffered events,
+// doubled on every stale snapshot
+const SNAPSHOT_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_SNAPSHOT_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Price ladders of a locally maintained order book.
+///
+/// Prices are keyed by their bit pattern, which orders the same way as the
+/// (always positive) prices themselves.
+#[derive(Debug, Clone, Default)]
+pub struct LocalOrderBook {
+    last_update_id: u64,
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+}
+
+impl LocalOrderBook {
+    /// Replace the whole book with the given levels.
+    pub fn reset(&mut self, last_update_id: u64, bids: &[PriceLevel], asks: &[PriceLevel]) {
+        self.bids.clear();
+        self.asks.clear();
+        self.apply(last_update_id, bids, asks);
+    }
+
+    /// Apply absolute quantities; a quantity of 0 removes the price level.
+    pub fn apply(&mut self, last_update_id: u64, bids: &[PriceLevel], asks: &[PriceLevel]) {
+        update_side(&mut self.bids, bids);
+        update_side(&mut self.asks, asks);
+        self.last_update_id = last_update_id;
+    }
+
+    /// Update id of the last snapshot or event applied to the book.
+    pub fn last_update_id(&self) -> u64 {
+        self.last_update_id
+    }
+
+    /// All bids, best (highest) price first.
+    pub fn bids(&self) -> Vec<PriceLevel> {
+        self.top_bids(self.bids.len())
+    }
+
+    /// All asks, best (lowest) price first.
+    pub fn asks(&self) -> Vec<PriceLevel> {
+        self.top_asks(self.asks.len())
+    }
+
+    /// The `depth` best bids.
+    pub fn top_bids(&self, depth: usize) -> Vec<PriceLevel> {
+        self.bids.iter().rev().take(depth).map(to_level).collect()
+    }
+
+    /// The `depth` best asks.
+    pub fn top_asks(&self, depth: usize) -> Vec<PriceLevel> {
+        self.asks.iter().take(depth).map(to_level).collect()
+    }
+
+    pub fn best_bid(&self) -> Option<PriceLevel> {
+        self.bids.iter().next_back().map(to_level)
+    }
+
+    pub fn best_ask(&self) -> Option<PriceLevel> {
+        self.asks.iter().next().map(to_level)
+    }
+
+    /// Difference between the best ask and the best bid.
+    pub fn spread(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some(ask.price - bid.price),
+            _ => None,
+        }
+    }
+
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some((ask.price + bid.price) / 2.0),
+            _ => None,
+        }
+    }
+
+    /// Total bid quantity at prices greater than or equal to `price`.
+    pub fn bid_qty_above(&self, price: f64) -> f64 {
+        self.bids.range(price.to_bits()..).map(|(_, qty)| qty).sum()
+    }
+
+    /// Total ask quantity at prices less than or equal to `price`.
+    pub fn ask_qty_below(&self, price: f64) -> f64 {
+        self.asks
+            .range(..=price.to_bits())
+            .map(|(_, qty)| qty)
+            .sum()
+    }
+}
+
+fn update_side(side: &mut BTreeMap<u64, f64>, levels: &[PriceLevel]) {
+    for level in levels {
+        if level.qty == 0.0 {
+            side.remove(&level.price.to_bits());
+        } else {
+            side.insert(level.price.to_bits(), level.qty);
+        }
+    }
+}
+
+fn to_level((price, qty): (&u64, &f64)) -> PriceLevel {
+    PriceLevel::new(f64::from_bits(*price), *qty)
+}
+
+/// REST depth snapshots an `OrderBookManager` is synchronized from.
+///
+/// Implemented by `Market`, `futures::market::FuturesMarket` and
+/// `futures::coin::market::CoinFuturesMarket`.
+pub trait DepthSnapshotSource {
+    /// How the diff events of this market follow each other.
+    type Continuity: UpdateContinuity;
+
+    fn depth_snapshot(&self, symbol: &str, depth: u64) -> Result<OrderBook>;
+}
+
+/// Rule telling whether a diff event continues the local book.
+///
+/// `bridged` tells whether an event has already been applied on top of the
+/// current snapshot.
+pub trait UpdateContinuity {
+    /// Whether there are missing updates between a snapshot and the first buffered event.
+    fn is_stale(snapshot_update_id: u64, first: &DepthOrderBookEvent) -> bool;
+
+    /// Whether the event is already contained in the book.
+    fn is_outdated(event: &DepthOrderBookEvent, last_update_id: u64, bridged: bool) -> bool;
+
+    /// Whether the event can be applied on top of the book.
+    fn continues(event: &DepthOrderBookEvent, last_update_id: u64, bridged: bool) -> bool;
+}
+
+/// Spot rule: the first event after the snapshot must satisfy
+/// `U <= lastUpdateId + 1 <= u`, every following event must start right
+/// after the previous one.
+#[derive(Debug, Copy, Clone)]
+pub struct SpotContinuity;
+
+impl UpdateContinuity for SpotContinuity {
+    fn is_stale(snapshot_update_id: u64, first: &DepthOrderBookEvent) -> bool {
+        snapshot_update_id + 1 < first.first_update_id
+    }
+
+    fn is_outdated(event: &DepthOrderBookEvent, last_update_id: u64, _bridged: bool) -> bool {
+        event.final_update_id <= last_update_id
+    }
+
+    fn continues(event: &DepthOrderBookEvent, last_update_id: u64, bridged: bool) -> bool {
+        let expected = last_update_id + 1;
+        if bridged {
+            event.first_update_id == expected
+        } else {
+            event.first_update_id <= expected && expected <= event.final_update_id
+        }
+    }
+}
+
+impl DepthSnapshotSource for Market {
+    type Continuity = SpotContinuity;
+
+    fn depth_snapshot(&self, symbol: &str, depth: u64) -> Result<OrderBook> {
+        self.get_custom_depth(symbol, depth)
+    }
+}
+
+/// Order book kept in sync with the `<symbol>@depth` diff stream.
+///
+/// Feed every `DepthOrderBookEvent` of the symbol to `update`. Events are
+/// buffered until a REST snapshot has been fetched from `S` and bridged,
+/// following the continuity rule of its market, see
+/// https://binance-docs.github.io/apidocs/spot/en/#how-to-manage-a-local-order-book-correctly
+/// A snapshot older than the buffered events is fetched again after a
+/// growing delay, while events keep being buffered. If an event does not
+/// continue the previous one, the book is dropped and synchronized again
+/// from a new snapshot.
+pub struct OrderBookManager<S = Market> {
+    source: S,
+    symbol: String,
+    snapshot_depth: u64,
+    synced: bool,
+    // Whether an event has been applied on top of the current snapshot
+    bridged: bool,
+    buffer: Vec<DepthOrderBookEvent>,
+    book: LocalOrderBook,
+    resyncs: u64,
+    // When the next snapshot may be fetched, `None` if it is due now
+    next_snapshot: Option<Instant>,
+    retry_delay: Duration,
+}
+
+impl<S: DepthSnapshotSource> OrderBookManager<S> {
+    pub fn new<T>(source: S, symbol: T) -> Self
+    where
+        T: Into<String>,
+    {
+        OrderBookManager {
+            source,
+            symbol: symbol.into().to_uppercase(),
+            snapshot_depth: 1000,
+            synced: false,
+            bridged: false,
+            buffer: Vec::new(),
+            book: LocalOrderBook::default(),
+            resyncs: 0,
+            next_snapshot: None,
+            retry_delay: SNAPSHOT_RETRY_DELAY,
+        }
+    }
+
+    /// Depth of the REST snapshot (up to 5000 on spot, 1000 on futures).
+    pub fn set_snapshot_depth(mut self, snapshot_depth: u64) -> Self {
+        self.snapshot_depth = snapshot_depth;
+        self
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    /// Number of times the book was dropped because of a sequence gap.
+    pub fn resyncs(&self) -> u64 {
+        self.resyncs
+    }
+
+    /// The local book, or `None` while it is being synchronized.
+    pub fn book(&self) -> Option<&LocalOrderBook> {
+        if self.synced {
+            Some(&self.book)
+        } else {
+            None
+        }
+    }
+
+    /// Process a diff event of the symbol; events of other symbols are ignored.
+    pub fn update(&mut self, event: &DepthOrderBookEvent) -> Result<()> {
+        if event.symbol != self.symbol {
+            return Ok(());
+        }
+
+        if self.synced {
+            let last_update_id = self.book.last_update_id();
+            if S::Continuity::is_outdated(event, last_update_id, self.bridged) {
+                return Ok(());
+            }
+            if S::Continuity::continues(event, last_update_id, self.bridged) {
+                self.book
+                    .apply(event.final_update_id, &event.bids, &event.asks);
+                self.bridged = true;
+                return Ok(());
+            }
+            self.resync(event);
+        }
+
+        self.buffer.push(event.clone());
+        if matches!(self.next_snapshot, Some(at) if Instant::now() < at) {
+            // Still waiting to retry after a stale snapshot
+            return Ok(());
+        }
+        self.next_snapshot = Some(Instant::now() + self.retry_delay);
+        let snapshot = self
+            .source
+            .depth_snapshot(self.symbol.as_str(), self.snapshot_depth)?;
+        self.sync(snapshot)
+    }
+
+    /// Bridge the buffered events with a snapshot.
+    ///
+    /// Called by `update`; can also be used to provide a snapshot obtained elsewhere.
+    pub fn sync(&mut self, snapshot: OrderBook) -> Result<()> {
+        // The snapshot must not be older than the first buffered event
+        match self.buffer.first() {
+            Some(first) if !S::Continuity::is_stale(snapshot.last_update_id, first) => (),
+            Some(_) => {
+                log::debug!(
+                    "{} depth snapshot {} is stale, retrying in {:?}",
+                    self.symbol,
+                    snapshot.last_update_id,
+                    self.retry_delay
+                );
+                self.retry_delay = (self.retry_delay * 2).min(MAX_SNAPSHOT_RETRY_DELAY);
+                return Ok(());
+            }
+            None => return Ok(()),
+        }
+
+        self.buffer
+            .retain(|event| !S::Continuity::is_outdated(event, snapshot.last_update_id, false));
+        self.book
+            .reset(snapshot.last_update_id, &snapshot.bids, &snapshot.asks);
+        self.bridged = false;
+        self.synced = true;
+        self.retry_delay = SNAPSHOT_RETRY_DELAY;
+
+        let mut buffer = std::mem::take(&mut self.buffer).into_iter();
+        while let Some(event) = buffer.next() {
+            if !S::Continuity::continues(&event, self.book.last_update_id(), self.bridged) {
+                // Keep the events from the gap on for the next snapshot
+                self.resync(&event);
+                self.buffer.push(event);
+                self.buffer.extend(buffer);
+                break;
+            }
+            self.book
+                .apply(event.final_update_id, &event.bids, &event.asks);
+            self.bridged = true;
+        }
+
+        Ok(())
+    }
+
+    // Drop the book after a gap, the next event fetches a new snapshot right away
+    fn resync(&mut self, event: &DepthOrderBookEvent) {
+        log::warn!(
+            "{} depth gap after update {}, got {} to {}, resynchronizing",
+            self.symbol,
+            self.book.last_update_id(),
+            event.first_update_id,
+            event.final_update_id
+        );
+        self.synced = false;
+        self.resyncs += 1;
+        self.next_snapshot = None;
+    }
+}
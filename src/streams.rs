@@ -0,0 +1,405 @@
+use crate::futures::account::ContractType;
+use crate::market::KlineInterval;
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of price levels of a partial book depth stream.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DepthLevels {
+    Five,
+    Ten,
+    Twenty,
+}
+
+impl fmt::Display for DepthLevels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DepthLevels::Five => "5",
+            DepthLevels::Ten => "10",
+            DepthLevels::Twenty => "20",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for DepthLevels {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "5" => Ok(Self::Five),
+            "10" => Ok(Self::Ten),
+            "20" => Ok(Self::Twenty),
+            other => Err(format!("Invalid DepthLevels: '{}'", other)),
+        }
+    }
+}
+
+/// Update speed of the depth streams, 1000ms on spot and 250ms on futures
+/// by default.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DepthSpeed {
+    Ms100,
+    /// Futures only
+    Ms500,
+}
+
+impl fmt::Display for DepthSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DepthSpeed::Ms100 => "100ms",
+            DepthSpeed::Ms500 => "500ms",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for DepthSpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "100ms" => Ok(Self::Ms100),
+            "500ms" => Ok(Self::Ms500),
+            other => Err(format!("Invalid DepthSpeed: '{}'", other)),
+        }
+    }
+}
+
+/// Update speed of the mark price and index price streams, 3s by default.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PriceSpeed {
+    Sec1,
+}
+
+impl fmt::Display for PriceSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            PriceSpeed::Sec1 => "1s",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for PriceSpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "1s" => Ok(Self::Sec1),
+            other => Err(format!("Invalid PriceSpeed: '{}'", other)),
+        }
+    }
+}
+
+/// Window of the rolling window statistics streams.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RollingWindow {
+    OneHour,
+    FourHours,
+    OneDay,
+}
+
+impl fmt::Display for RollingWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            RollingWindow::OneHour => "1h",
+            RollingWindow::FourHours => "4h",
+            RollingWindow::OneDay => "1d",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for RollingWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "1h" => Ok(Self::OneHour),
+            "4h" => Ok(Self::FourHours),
+            "1d" => Ok(Self::OneDay),
+            other => Err(format!("Invalid RollingWindow: '{}'", other)),
+        }
+    }
+}
+
+/// Name of a spot or futures market stream, to be used with `connect`,
+/// `connect_multiple_streams` and `subscribe` instead of a hand written string.
+///
+/// Symbols and pairs are lowercased.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stream {
+    Trade(String),
+    AggTrade(String),
+    Kline(String, KlineInterval),
+    /// Futures only
+    ContinuousKline(String, ContractType, KlineInterval),
+    /// Futures only
+    IndexPriceKline(String, KlineInterval),
+    /// Futures only
+    MarkPriceKline(String, KlineInterval),
+    /// Spot only
+    AvgPrice(String),
+    /// Futures only
+    MarkPrice(String, Option<PriceSpeed>),
+    /// Futures only
+    AllMarkPrices(Option<PriceSpeed>),
+    /// COIN-M futures only
+    IndexPrice(String, Option<PriceSpeed>),
+    /// Diff depth stream when `levels` is `None`, partial book depth otherwise
+    Depth {
+        symbol: String,
+        levels: Option<DepthLevels>,
+        speed: Option<DepthSpeed>,
+    },
+    BookTicker(String),
+    AllBookTickers,
+    MiniTicker(String),
+    AllMiniTickers,
+    Ticker(String),
+    AllTickers,
+    /// Spot only
+    RollingWindowTicker(String, RollingWindow),
+    /// Spot only
+    AllRollingWindowTickers(RollingWindow),
+    /// Futures only
+    ForceOrder(String),
+    /// Futures only
+    AllForceOrders,
+    /// Futures only
+    CompositeIndex(String),
+    /// Futures only, symbol listings, delistings and leverage bracket changes
+    ContractInfo,
+    /// Futures only, index of an asset in multi-assets mode, e.g. `ADAUSD`
+    AssetIndex(String),
+    /// Futures only
+    AllAssetIndexes,
+    /// User data stream of a listen key
+    UserData(String),
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stream::Trade(symbol) => write!(f, "{}@trade", symbol.to_lowercase()),
+            Stream::AggTrade(symbol) => write!(f, "{}@aggTrade", symbol.to_lowercase()),
+            Stream::Kline(symbol, interval) => {
+                write!(f, "{}@kline_{}", symbol.to_lowercase(), interval)
+            }
+            Stream::ContinuousKline(pair, contract_type, interval) => write!(
+                f,
+                "{}_{}@continuousKline_{}",
+                pair.to_lowercase(),
+                contract_type.to_string().to_lowercase(),
+                interval
+            ),
+            Stream::IndexPriceKline(pair, interval) => {
+                write!(f, "{}@indexPriceKline_{}", pair.to_lowercase(), interval)
+            }
+            Stream::MarkPriceKline(symbol, interval) => {
+                write!(f, "{}@markPriceKline_{}", symbol.to_lowercase(), interval)
+            }
+            Stream::AvgPrice(symbol) => write!(f, "{}@avgPrice", symbol.to_lowercase()),
+            Stream::MarkPrice(symbol, speed) => {
+                write!(f, "{}@markPrice", symbol.to_lowercase())?;
+                write_speed(f, speed)
+            }
+            Stream::AllMarkPrices(speed) => {
+                write!(f, "!markPrice@arr")?;
+                write_speed(f, speed)
+            }
+            Stream::IndexPrice(pair, speed) => {
+                write!(f, "{}@indexPrice", pair.to_lowercase())?;
+                write_speed(f, speed)
+            }
+            Stream::Depth {
+                symbol,
+                levels,
+                speed,
+            } => {
+                write!(f, "{}@depth", symbol.to_lowercase())?;
+                if let Some(levels) = levels {
+                    write!(f, "{}", levels)?;
+                }
+                write_speed(f, speed)
+            }
+            Stream::BookTicker(symbol) => write!(f, "{}@bookTicker", symbol.to_lowercase()),
+            Stream::AllBookTickers => write!(f, "!bookTicker"),
+            Stream::MiniTicker(symbol) => write!(f, "{}@miniTicker", symbol.to_lowercase()),
+            Stream::AllMiniTickers => write!(f, "!miniTicker@arr"),
+            Stream::Ticker(symbol) => write!(f, "{}@ticker", symbol.to_lowercase()),
+            Stream::AllTickers => write!(f, "!ticker@arr"),
+            Stream::RollingWindowTicker(symbol, window) => {
+                write!(f, "{}@ticker_{}", symbol.to_lowercase(), window)
+            }
+            Stream::AllRollingWindowTickers(window) => write!(f, "!ticker_{}@arr", window),
+            Stream::ForceOrder(symbol) => write!(f, "{}@forceOrder", symbol.to_lowercase()),
+            Stream::AllForceOrders => write!(f, "!forceOrder@arr"),
+            Stream::CompositeIndex(symbol) => {
+                write!(f, "{}@compositeIndex", symbol.to_lowercase())
+            }
+            Stream::ContractInfo => write!(f, "!contractInfo"),
+            Stream::AssetIndex(symbol) => write!(f, "{}@assetIndex", symbol.to_lowercase()),
+            Stream::AllAssetIndexes => write!(f, "!assetIndex@arr"),
+            Stream::UserData(listen_key) => write!(f, "{}", listen_key),
+        }
+    }
+}
+
+fn write_speed<T: fmt::Display>(f: &mut fmt::Formatter<'_>, speed: &Option<T>) -> fmt::Result {
+    match speed {
+        Some(speed) => write!(f, "@{}", speed),
+        None => Ok(()),
+    }
+}
+
+/// Parse a stream name, e.g. the one of a combined stream message. Symbols
+/// and pairs are kept lowercase.
+impl FromStr for Stream {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("Invalid Stream: '{}'", s);
+        let mut parts = s.split('@');
+        let name = parts.next().unwrap_or_default();
+        let kind = parts.next();
+        let speed = parts.next();
+        if parts.next().is_some() || name.is_empty() {
+            return Err(invalid());
+        }
+
+        let stream = match (name, kind) {
+            ("!bookTicker", None) => Stream::AllBookTickers,
+            ("!miniTicker", Some("arr")) => Stream::AllMiniTickers,
+            ("!ticker", Some("arr")) => Stream::AllTickers,
+            ("!forceOrder", Some("arr")) => Stream::AllForceOrders,
+            ("!contractInfo", None) => Stream::ContractInfo,
+            ("!assetIndex", Some("arr")) => Stream::AllAssetIndexes,
+            ("!markPrice", Some("arr")) => return Ok(Stream::AllMarkPrices(parse_speed(speed)?)),
+            (name, Some("arr")) if name.starts_with("!ticker_") => {
+                Stream::AllRollingWindowTickers(name["!ticker_".len()..].parse()?)
+            }
+            (name, _) if name.starts_with('!') => return Err(invalid()),
+            (listen_key, None) => Stream::UserData(listen_key.into()),
+            (symbol, Some(kind)) => {
+                let symbol = symbol.to_string();
+                match kind {
+                    "trade" => Stream::Trade(symbol),
+                    "aggTrade" => Stream::AggTrade(symbol),
+                    "avgPrice" => Stream::AvgPrice(symbol),
+                    "bookTicker" => Stream::BookTicker(symbol),
+                    "miniTicker" => Stream::MiniTicker(symbol),
+                    "ticker" => Stream::Ticker(symbol),
+                    "forceOrder" => Stream::ForceOrder(symbol),
+                    "compositeIndex" => Stream::CompositeIndex(symbol),
+                    "assetIndex" => Stream::AssetIndex(symbol),
+                    "markPrice" => return Ok(Stream::MarkPrice(symbol, parse_speed(speed)?)),
+                    "indexPrice" => return Ok(Stream::IndexPrice(symbol, parse_speed(speed)?)),
+                    depth if depth.starts_with("depth") => {
+                        let levels = match &depth["depth".len()..] {
+                            "" => None,
+                            levels => Some(levels.parse()?),
+                        };
+                        return Ok(Stream::Depth {
+                            symbol,
+                            levels,
+                            speed: parse_speed(speed)?,
+                        });
+                    }
+                    kind => match kind.split_once('_') {
+                        Some(("kline", interval)) => Stream::Kline(symbol, interval.parse()?),
+                        Some(("indexPriceKline", interval)) => {
+                            Stream::IndexPriceKline(symbol, interval.parse()?)
+                        }
+                        Some(("markPriceKline", interval)) => {
+                            Stream::MarkPriceKline(symbol, interval.parse()?)
+                        }
+                        Some(("ticker", window)) => {
+                            Stream::RollingWindowTicker(symbol, window.parse()?)
+                        }
+                        Some(("continuousKline", interval)) => {
+                            let (pair, contract_type) =
+                                symbol.split_once('_').ok_or_else(invalid)?;
+                            Stream::ContinuousKline(
+                                pair.into(),
+                                contract_type.to_uppercase().parse()?,
+                                interval.parse()?,
+                            )
+                        }
+                        _ => return Err(invalid()),
+                    },
+                }
+            }
+        };
+
+        // Only the streams returned above have an update speed
+        match speed {
+            Some(_) => Err(invalid()),
+            None => Ok(stream),
+        }
+    }
+}
+
+fn parse_speed<T: FromStr<Err = String>>(
+    speed: Option<&str>,
+) -> std::result::Result<Option<T>, String> {
+    speed.map(str::parse).transpose()
+}
+
+/// An event together with the name of the stream it was received on, and when.
+#[derive(Debug, Clone)]
+pub struct StreamMessage<E> {
+    /// Stream name of a combined stream message, `None` on a raw stream
+    pub stream: Option<String>,
+    pub event: E,
+    /// Event time (`E`, or `T` for events without one) in milliseconds since
+    /// the Unix epoch
+    pub event_time: Option<u64>,
+    /// Local receive time in microseconds since the Unix epoch, `None` for
+    /// events which were not received, e.g. `Reconnected`
+    pub received_at: Option<u64>,
+}
+
+impl<E> From<E> for StreamMessage<E> {
+    fn from(event: E) -> Self {
+        StreamMessage {
+            stream: None,
+            event,
+            event_time: None,
+            received_at: None,
+        }
+    }
+}
+
+impl<E> StreamMessage<E> {
+    /// The stream the event was received on, `None` when unknown.
+    pub fn descriptor(&self) -> Option<Stream> {
+        self.stream.as_ref()?.parse().ok()
+    }
+
+    /// Time from the event time to the local receive time in microseconds,
+    /// see `binance::latency`.
+    pub fn latency(&self) -> Option<i64> {
+        Some(self.received_at? as i64 - self.event_time? as i64 * 1000)
+    }
+}
+
+impl From<Stream> for String {
+    fn from(item: Stream) -> Self {
+        item.to_string()
+    }
+}
+
+impl From<&Stream> for String {
+    fn from(item: &Stream) -> Self {
+        item.to_string()
+    }
+}
+
+pub(crate) fn stream_names<S>(streams: &[S]) -> Vec<String>
+where
+    S: Clone + Into<String>,
+{
+    streams.iter().cloned().map(Into::into).collect()
+}
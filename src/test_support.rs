@@ -0,0 +1,365 @@
+//! Local stand-in for the Binance websocket servers, to exercise the websocket
+//! clients end to end in tests.
+//!
+//! A `TestServer` plays a script of `ServerAction`s on each connection, in the
+//! order the connections are accepted. It serves raw streams on `/ws/<stream>`
+//! and combined streams on `/stream?streams=`, acknowledges live requests such as
+//! `SUBSCRIBE`, and records what the clients sent. `TestServer::config` points
+//! the clients at it.
+//!
+//! ```no_run
+//! use binance::test_support::*;
+//! use binance::websockets::*;
+//! use std::sync::atomic::AtomicBool;
+//!
+//! let server = TestServer::start(vec![vec![
+//!     ServerAction::Send(r#"{"e":"trade","E":1,"s":"BNBBTC","t":1,"p":"0.001","q":"100","b":1,"a":2,"T":1,"m":true,"M":true}"#.into()),
+//!     ServerAction::Close,
+//! ]])
+//! .unwrap();
+//!
+//! let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+//!     println!("{:?}", event);
+//!     Ok(())
+//! });
+//! web_socket.connect_with_config("bnbbtc@trade", &server.config()).unwrap();
+//! let _ = web_socket.event_loop(&AtomicBool::new(true));
+//! ```
+
+use crate::capture::{CaptureReader, ReplaySpeed};
+use crate::config::Config;
+use crate::errors::*;
+use crate::websockets::is_read_timeout;
+use serde_json::{json, Value};
+
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::handshake::server::{Request, Response};
+use tungstenite::protocol::WebSocket;
+use tungstenite::Message;
+
+// How long the server waits for client messages before going on with its script
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A step of the script played on a connection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerAction {
+    /// Send a text frame as is.
+    Send(String),
+    /// Send the event `data` of `stream`: as is on a raw stream connection,
+    /// wrapped with the stream name on a combined stream connection. Skipped
+    /// when the connection is not subscribed to `stream`, like the stream
+    /// servers do.
+    Event {
+        stream: String,
+        data: String,
+    },
+    Ping,
+    /// Wait, while still answering live requests.
+    Sleep(Duration),
+    /// Close the connection with a closing handshake.
+    Close,
+    /// Drop the connection without a closing handshake.
+    Disconnect,
+}
+
+/// Script which sends the frames of a capture file, paced as recorded with
+/// `ReplaySpeed::Recorded`.
+pub fn script_from_capture<P>(path: P, speed: ReplaySpeed) -> Result<Vec<ServerAction>>
+where
+    P: AsRef<std::path::Path>,
+{
+    let mut script = Vec::new();
+    let mut previous: Option<u64> = None;
+    for frame in CaptureReader::open(path)? {
+        let frame = frame?;
+        if let (ReplaySpeed::Recorded, Some(previous)) = (speed, previous) {
+            script.push(ServerAction::Sleep(Duration::from_micros(
+                frame.received_at.saturating_sub(previous),
+            )));
+        }
+        previous = Some(frame.received_at);
+        script.push(ServerAction::Send(frame.message));
+    }
+    Ok(script)
+}
+
+/// What the clients did on the server.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerLog {
+    /// Requested path of each connection, e.g. `/ws/bnbbtc@trade`
+    pub connections: Vec<String>,
+    /// Live requests, in the order they were received
+    pub requests: Vec<Value>,
+    /// Answers to `ServerAction::Ping`
+    pub pongs: usize,
+    /// Pings sent by the clients
+    pub pings: usize,
+}
+
+/// Local websocket server playing scripted frames, see the module documentation.
+pub struct TestServer {
+    address: SocketAddr,
+    log: Arc<Mutex<ServerLog>>,
+    stopped: Arc<AtomicBool>,
+    acceptor: Option<thread::JoinHandle<Vec<thread::JoinHandle<()>>>>,
+}
+
+impl TestServer {
+    /// Serve the `n`th connection with the `n`th script. Further connections
+    /// are refused.
+    pub fn start(scripts: Vec<Vec<ServerAction>>) -> Result<TestServer> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+        let log = Arc::new(Mutex::new(ServerLog::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let acceptor = {
+            let log = log.clone();
+            let stopped = stopped.clone();
+            thread::Builder::new()
+                .name("binance-test-server".into())
+                .spawn(move || {
+                    let mut connections = Vec::new();
+                    for script in scripts {
+                        let stream = loop {
+                            match listener.accept() {
+                                Ok((stream, _)) => break Some(stream),
+                                Err(ref e)
+                                    if e.kind() == std::io::ErrorKind::WouldBlock
+                                        && !stopped.load(Ordering::Relaxed) =>
+                                {
+                                    thread::sleep(POLL_INTERVAL)
+                                }
+                                Err(_) => break None,
+                            }
+                        };
+                        let stream = match stream {
+                            Some(stream) => stream,
+                            None => break,
+                        };
+                        let log = log.clone();
+                        let stopped = stopped.clone();
+                        connections.push(thread::spawn(move || {
+                            if let Err(e) = serve_connection(stream, script, &log, &stopped) {
+                                log::debug!("Test server connection ended: {}", e);
+                            }
+                        }));
+                    }
+                    connections
+                })?
+        };
+
+        Ok(TestServer {
+            address,
+            log,
+            stopped,
+            acceptor: Some(acceptor),
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Configuration pointing the spot and futures websockets at the server.
+    pub fn config(&self) -> Config {
+        let endpoint = format!("ws://{}/ws", self.address);
+        Config::default()
+            .set_ws_endpoint(endpoint.clone())
+            .set_futures_ws_endpoint(endpoint.clone())
+            .set_coin_futures_ws_endpoint(endpoint.clone())
+            .set_options_ws_endpoint(endpoint)
+    }
+
+    /// What the clients did so far.
+    pub fn log(&self) -> ServerLog {
+        self.log.lock().unwrap().clone()
+    }
+
+    /// Wait until every script was played and its client went away.
+    pub fn join(mut self) -> ServerLog {
+        self.join_connections();
+        self.log()
+    }
+
+    fn join_connections(&mut self) {
+        if let Some(acceptor) = self.acceptor.take() {
+            for connection in acceptor.join().unwrap_or_default() {
+                let _ = connection.join();
+            }
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        self.join_connections();
+    }
+}
+
+struct Connection<'a> {
+    socket: WebSocket<TcpStream>,
+    streams: Vec<String>,
+    combined: bool,
+    log: &'a Mutex<ServerLog>,
+}
+
+fn serve_connection(
+    stream: TcpStream, script: Vec<ServerAction>, log: &Mutex<ServerLog>, stopped: &AtomicBool,
+) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    let mut path = String::new();
+    let socket = tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+        path = request.uri().to_string();
+        Ok(response)
+    })
+    .map_err(|e| Error::from(format!("Handshake failed: {}", e)))?;
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    log.lock().unwrap().connections.push(path.clone());
+
+    let (streams, combined) = streams_of(&path);
+    let mut connection = Connection {
+        socket,
+        streams,
+        combined,
+        log,
+    };
+
+    for action in script {
+        match action {
+            ServerAction::Send(frame) => connection.send(Message::Text(frame))?,
+            ServerAction::Event { stream, data } => {
+                if !connection.streams.contains(&stream) {
+                    connection.poll()?;
+                    continue;
+                }
+                let frame = if connection.combined {
+                    format!(r#"{{"stream":{},"data":{}}}"#, json!(stream), data)
+                } else {
+                    data
+                };
+                connection.send(Message::Text(frame))?
+            }
+            ServerAction::Ping => connection.send(Message::Ping(Vec::new()))?,
+            ServerAction::Sleep(duration) => {
+                let deadline = Instant::now() + duration;
+                while Instant::now() < deadline {
+                    connection.poll()?;
+                }
+            }
+            ServerAction::Close => {
+                connection.socket.close(None)?;
+                // Complete the closing handshake
+                while !stopped.load(Ordering::Relaxed) && connection.poll().is_ok() {}
+                return Ok(());
+            }
+            ServerAction::Disconnect => {
+                connection.socket.get_ref().shutdown(Shutdown::Both)?;
+                return Ok(());
+            }
+        }
+        connection.poll()?;
+    }
+
+    // Keep answering live requests until the client goes away
+    while !stopped.load(Ordering::Relaxed) {
+        connection.poll()?;
+    }
+    let _ = connection.socket.close(None);
+    Ok(())
+}
+
+impl<'a> Connection<'a> {
+    fn send(&mut self, message: Message) -> Result<()> {
+        self.socket.write_message(message)?;
+        Ok(())
+    }
+
+    // Handle the messages received so far, waiting at most one poll interval
+    fn poll(&mut self) -> Result<()> {
+        loop {
+            let message = match self.socket.read_message() {
+                Ok(message) => message,
+                Err(ref e) if is_read_timeout(e) => return Ok(()),
+                Err(e) => return Err(e.into()),
+            };
+            match message {
+                Message::Text(msg) => {
+                    let request: Value = serde_json::from_str(&msg)?;
+                    let response = self.respond(&request);
+                    self.log.lock().unwrap().requests.push(request);
+                    self.send(Message::Text(response.to_string()))?;
+                }
+                Message::Pong(_) => self.log.lock().unwrap().pongs += 1,
+                Message::Ping(_) => self.log.lock().unwrap().pings += 1,
+                Message::Binary(_) | Message::Close(_) => (),
+            }
+        }
+    }
+
+    // Response to a live request, like the stream servers do
+    fn respond(&mut self, request: &Value) -> Value {
+        let id = request["id"].clone();
+        let params = match request["params"] {
+            Value::Array(ref params) => params.clone(),
+            _ => Vec::new(),
+        };
+        let result = match request["method"].as_str() {
+            Some("SUBSCRIBE") => {
+                for stream in params.iter().filter_map(Value::as_str) {
+                    if !self.streams.iter().any(|s| s == stream) {
+                        self.streams.push(stream.to_string());
+                    }
+                }
+                Value::Null
+            }
+            Some("UNSUBSCRIBE") => {
+                self.streams
+                    .retain(|stream| !params.iter().any(|p| p.as_str() == Some(stream)));
+                Value::Null
+            }
+            Some("LIST_SUBSCRIPTIONS") => json!(self.streams),
+            Some("SET_PROPERTY") => {
+                if let (Some("combined"), Some(combined)) = (
+                    params.first().and_then(Value::as_str),
+                    params.get(1).and_then(Value::as_bool),
+                ) {
+                    self.combined = combined;
+                }
+                Value::Null
+            }
+            Some("GET_PROPERTY") => json!(self.combined),
+            _ => {
+                return json!({
+                    "error": {"code": 2, "msg": "Invalid request: unknown method"},
+                    "id": id
+                })
+            }
+        };
+        json!({ "result": result, "id": id })
+    }
+}
+
+// Streams of a connection path, and whether they are combined
+fn streams_of(path: &str) -> (Vec<String>, bool) {
+    let names = |streams: &str| -> Vec<String> {
+        streams
+            .split('/')
+            .filter(|stream| !stream.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    match path.find("streams=") {
+        Some(start) => {
+            let streams = &path[start + "streams=".len()..];
+            (names(streams.split('&').next().unwrap_or("")), true)
+        }
+        None => (names(path.trim_start_matches("/ws")), false),
+    }
+}
@@ -1,8 +1,20 @@
 use crate::model::*;
 use crate::client::*;
+use crate::config::*;
 use crate::errors::*;
 use crate::api::API;
-use crate::api::Spot;
+use crate::api::{CoinFutures, Futures, Sapi, Spot};
+use crate::futures::websockets::{FuturesMarket, FuturesWebsocketAPI, FuturesWebsocketEvent};
+use crate::streams::StreamMessage;
+use crate::websockets::{
+    HeartbeatPolicy, ReconnectPolicy, ShutdownHandle, StreamConnection, StreamEvent, WebsocketAPI,
+    WebsocketEvent,
+};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Clone)]
 pub struct UserStream {
@@ -26,3 +38,364 @@ impl UserStream {
             .delete(API::Spot(Spot::UserDataStream), listen_key)
     }
 }
+
+/// Account whose user data stream is managed by a `UserDataStreamManager`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UserDataStreamMarket {
+    Spot,
+    /// Cross margin account
+    Margin,
+    UsdMFutures,
+    CoinMFutures,
+}
+
+impl UserDataStreamMarket {
+    fn api(self) -> API {
+        match self {
+            UserDataStreamMarket::Spot => API::Spot(Spot::UserDataStream),
+            UserDataStreamMarket::Margin => API::Savings(Sapi::UserDataStream),
+            UserDataStreamMarket::UsdMFutures => API::Futures(Futures::UserDataStream),
+            UserDataStreamMarket::CoinMFutures => API::CoinFutures(CoinFutures::UserDataStream),
+        }
+    }
+
+    fn rest_api_endpoint(self, config: &Config) -> String {
+        match self {
+            UserDataStreamMarket::Spot | UserDataStreamMarket::Margin => {
+                config.rest_api_endpoint.clone()
+            }
+            UserDataStreamMarket::UsdMFutures => config.futures_rest_api_endpoint.clone(),
+            UserDataStreamMarket::CoinMFutures => config.coin_futures_rest_api_endpoint.clone(),
+        }
+    }
+
+    fn futures_market(self) -> Option<FuturesMarket> {
+        match self {
+            UserDataStreamMarket::Spot | UserDataStreamMarket::Margin => None,
+            UserDataStreamMarket::UsdMFutures => Some(FuturesMarket::USDM),
+            UserDataStreamMarket::CoinMFutures => Some(FuturesMarket::COINM),
+        }
+    }
+
+    fn ws_url(self, listen_key: &str, config: Option<&Config>) -> String {
+        match (self.futures_market(), config) {
+            (Some(market), Some(config)) => {
+                let endpoint = market.ws_endpoint(config).into();
+                FuturesWebsocketAPI::Custom(endpoint).params(market, listen_key)
+            }
+            (Some(market), None) => FuturesWebsocketAPI::Default.params(market, listen_key),
+            (None, Some(config)) => {
+                WebsocketAPI::Custom(config.ws_endpoint.clone()).params(listen_key)
+            }
+            (None, None) => WebsocketAPI::Default.params(listen_key),
+        }
+    }
+}
+
+/// Event received by the handler of a `UserDataStreamManager`.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum UserDataEvent {
+    /// Spot and margin events
+    Spot(WebsocketEvent),
+    /// USD-M and COIN-M futures events
+    Futures(FuturesWebsocketEvent),
+}
+
+impl From<WebsocketEvent> for UserDataEvent {
+    fn from(event: WebsocketEvent) -> UserDataEvent {
+        UserDataEvent::Spot(event)
+    }
+}
+
+impl From<FuturesWebsocketEvent> for UserDataEvent {
+    fn from(event: FuturesWebsocketEvent) -> UserDataEvent {
+        UserDataEvent::Futures(event)
+    }
+}
+
+// Event of a user data stream, which tells when its listen key expired
+trait UserDataStreamEvent: StreamEvent + Into<UserDataEvent> {
+    fn is_listen_key_expired(&self) -> bool;
+}
+
+impl UserDataStreamEvent for WebsocketEvent {
+    fn is_listen_key_expired(&self) -> bool {
+        matches!(self, WebsocketEvent::ListenKeyExpired(_))
+    }
+}
+
+impl UserDataStreamEvent for FuturesWebsocketEvent {
+    fn is_listen_key_expired(&self) -> bool {
+        matches!(self, FuturesWebsocketEvent::ListenKeyExpired(_))
+    }
+}
+
+// Why a connection of the manager ended
+enum ConnectionEnd {
+    Stopped,
+    ListenKeyExpired,
+    Renewal,
+}
+
+/// Runs a user data stream without the listen key chores.
+///
+/// `event_loop` obtains a listen key, connects the websocket and keeps the key
+/// alive from a background thread every `keep_alive_interval`. When the key
+/// expires, or once it is `renew_interval` old (Binance closes connections after
+/// 24 hours), a new key is obtained and the websocket reconnected. A renewed
+/// key is only closed once the websocket is connected with its successor, and
+/// the key in use is closed when the manager is dropped.
+pub struct UserDataStreamManager<'a> {
+    market: UserDataStreamMarket,
+    client: Client,
+    config: Option<Config>,
+    handler: Box<dyn FnMut(UserDataEvent) -> Result<()> + Send + 'a>,
+    keep_alive_interval: Duration,
+    renew_interval: Duration,
+    reconnect_policy: Option<ReconnectPolicy>,
+    listen_key: Option<String>,
+    listen_key_created_at: Instant,
+    // Key replaced by a renewal, closed once the new one is connected
+    renewed_listen_key: Option<String>,
+}
+
+impl<'a> UserDataStreamManager<'a> {
+    pub fn new<Callback>(
+        market: UserDataStreamMarket, api_key: Option<String>, handler: Callback,
+    ) -> UserDataStreamManager<'a>
+    where
+        Callback: FnMut(UserDataEvent) -> Result<()> + Send + 'a,
+    {
+        let mut manager = Self::new_with_config(market, api_key, &Config::default(), handler);
+        manager.config = None;
+        manager
+    }
+
+    pub fn new_with_config<Callback>(
+        market: UserDataStreamMarket, api_key: Option<String>, config: &Config, handler: Callback,
+    ) -> UserDataStreamManager<'a>
+    where
+        Callback: FnMut(UserDataEvent) -> Result<()> + Send + 'a,
+    {
+        UserDataStreamManager {
+            market,
+            client: Client::new(api_key, None, market.rest_api_endpoint(config)),
+            config: Some(config.clone()),
+            handler: Box::new(handler),
+            keep_alive_interval: Duration::from_secs(30 * 60),
+            renew_interval: Duration::from_secs(23 * 60 * 60),
+            reconnect_policy: None,
+            listen_key: None,
+            listen_key_created_at: Instant::now(),
+            renewed_listen_key: None,
+        }
+    }
+
+    pub fn set_keep_alive_interval(mut self, keep_alive_interval: Duration) -> Self {
+        self.keep_alive_interval = keep_alive_interval;
+        self
+    }
+
+    pub fn set_renew_interval(mut self, renew_interval: Duration) -> Self {
+        self.renew_interval = renew_interval;
+        self
+    }
+
+    /// Reconnect with the same listen key when the connection drops, instead of
+    /// failing `event_loop`. The age limit of the policy is not used, the
+    /// connection is renewed with the key.
+    pub fn set_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Some(reconnect_policy.set_max_connection_age(Duration::MAX));
+        self
+    }
+
+    /// The listen key in use, if any.
+    pub fn listen_key(&self) -> Option<&str> {
+        self.listen_key.as_deref()
+    }
+
+    /// Handle user data events until `running` is false. The listen key is kept
+    /// when it returns, so that calling it again resumes the same stream.
+    pub fn event_loop(&mut self, running: &AtomicBool) -> Result<()> {
+        while running.load(Ordering::Relaxed) {
+            let listen_key = match self.listen_key {
+                Some(ref listen_key) => listen_key.clone(),
+                None => {
+                    let listen_key = self
+                        .client
+                        .post::<UserDataStream>(self.market.api())?
+                        .listen_key;
+                    self.listen_key = Some(listen_key.clone());
+                    self.listen_key_created_at = Instant::now();
+                    listen_key
+                }
+            };
+
+            match self.run_connection(&listen_key, running)? {
+                ConnectionEnd::Stopped => break,
+                ConnectionEnd::ListenKeyExpired => self.listen_key = None,
+                ConnectionEnd::Renewal => self.renewed_listen_key = self.listen_key.take(),
+            }
+        }
+        Ok(())
+    }
+
+    fn run_connection(&mut self, listen_key: &str, running: &AtomicBool) -> Result<ConnectionEnd> {
+        let shutdown = ShutdownHandle::default();
+        let keep_alive = KeepAlive::spawn(
+            self.client.clone(),
+            self.market,
+            listen_key.to_string(),
+            self.keep_alive_interval,
+            self.listen_key_created_at + self.renew_interval,
+            shutdown.clone(),
+        );
+        let expired = AtomicBool::new(false);
+        let url = self.market.ws_url(listen_key, self.config.as_ref());
+        match self.market.futures_market() {
+            None => self.run_web_socket::<WebsocketEvent>(url, running, &shutdown, &expired)?,
+            Some(_) => {
+                self.run_web_socket::<FuturesWebsocketEvent>(url, running, &shutdown, &expired)?
+            }
+        }
+
+        Ok(if expired.load(Ordering::Relaxed) {
+            ConnectionEnd::ListenKeyExpired
+        } else if keep_alive.stop() {
+            ConnectionEnd::Renewal
+        } else {
+            ConnectionEnd::Stopped
+        })
+    }
+
+    fn run_web_socket<E: UserDataStreamEvent>(
+        &mut self, url: String, running: &AtomicBool, shutdown: &ShutdownHandle,
+        expired: &AtomicBool,
+    ) -> Result<()> {
+        let handler = &mut self.handler;
+        // User data streams may be quiet for hours, only the server pings come in
+        let mut web_socket = StreamConnection::with_handler(|message: StreamMessage<E>| {
+            if message.event.is_listen_key_expired() {
+                expired.store(true, Ordering::Relaxed);
+                shutdown.shutdown();
+            }
+            handler(message.event.into())
+        })
+        .set_heartbeat_policy(HeartbeatPolicy::default().set_stale_timeout(None))
+        .set_shutdown_handle(shutdown.clone());
+        if let Some(ref reconnect_policy) = self.reconnect_policy {
+            web_socket = web_socket.set_reconnect_policy(reconnect_policy.clone());
+        }
+        web_socket.connect_wss(url)?;
+        if let Some(listen_key) = self.renewed_listen_key.take() {
+            // The exchange may hand out the same key again
+            if self.listen_key.as_ref() != Some(&listen_key) {
+                close_listen_key(&self.client, self.market, &listen_key);
+            }
+        }
+        web_socket.event_loop(running)?;
+        // A shutdown may have interrupted a reconnect, leaving nothing to close
+        if let Err(e) = web_socket.disconnect() {
+            log::debug!("Failed to close the user data stream: {}", e);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Drop for UserDataStreamManager<'a> {
+    fn drop(&mut self) {
+        let mut listen_keys: Vec<String> = self.listen_key.take().into_iter().collect();
+        if let Some(listen_key) = self.renewed_listen_key.take() {
+            if !listen_keys.contains(&listen_key) {
+                listen_keys.push(listen_key);
+            }
+        }
+        for listen_key in listen_keys {
+            close_listen_key(&self.client, self.market, &listen_key);
+        }
+    }
+}
+
+fn close_listen_key(client: &Client, market: UserDataStreamMarket, listen_key: &str) {
+    if let Err(e) = client.delete::<Success>(market.api(), listen_key) {
+        log::warn!("Failed to close listen key: {}", e);
+    }
+}
+
+// Extends the listen key from a background thread, and shuts the connection
+// down when the key is due for renewal.
+struct KeepAlive {
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+    renewal: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl KeepAlive {
+    fn spawn(
+        client: Client, market: UserDataStreamMarket, listen_key: String, interval: Duration,
+        renew_at: Instant, shutdown: ShutdownHandle,
+    ) -> KeepAlive {
+        let stopped = Arc::new((Mutex::new(false), Condvar::new()));
+        let renewal = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stopped = stopped.clone();
+            let renewal = renewal.clone();
+            thread::spawn(move || {
+                let mut next_keep_alive = Instant::now() + interval;
+                loop {
+                    let now = Instant::now();
+                    if now >= renew_at {
+                        renewal.store(true, Ordering::Relaxed);
+                        shutdown.shutdown();
+                        return;
+                    }
+                    if now >= next_keep_alive {
+                        if let Err(e) = client.put::<Success>(market.api(), &listen_key) {
+                            log::warn!("Failed to keep listen key alive: {}", e);
+                        }
+                        next_keep_alive = Instant::now() + interval;
+                        continue;
+                    }
+
+                    let (lock, condvar) = &*stopped;
+                    let guard = lock.lock().unwrap();
+                    if *guard {
+                        return;
+                    }
+                    let timeout = std::cmp::min(next_keep_alive, renew_at) - now;
+                    let (guard, _) = condvar.wait_timeout(guard, timeout).unwrap();
+                    if *guard {
+                        return;
+                    }
+                }
+            })
+        };
+        KeepAlive {
+            stopped,
+            renewal,
+            thread: Some(thread),
+        }
+    }
+
+    // Stop the thread, returns whether the listen key is due for renewal
+    fn stop(mut self) -> bool {
+        self.join();
+        self.renewal.load(Ordering::Relaxed)
+    }
+
+    fn join(&mut self) {
+        let (lock, condvar) = &*self.stopped;
+        *lock.lock().unwrap() = true;
+        condvar.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
@@ -13,6 +13,17 @@ pub fn build_request(parameters: BTreeMap<String, String>) -> String {
     request
 }
 
+/// Build the value of a `symbols` parameter, i.e. a url-encoded JSON array of symbols.
+pub fn build_symbols_param<I, S>(symbols: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let symbols: Vec<String> = symbols.into_iter().map(|s| s.into()).collect();
+    let json = serde_json::to_string(&symbols).unwrap_or_default();
+    url::form_urlencoded::byte_serialize(json.as_bytes()).collect()
+}
+
 pub fn build_signed_request(
     parameters: BTreeMap<String, String>, recv_window: u64,
 ) -> Result<String> {
@@ -40,6 +51,14 @@ pub fn to_f64(v: &Value) -> f64 {
     v.as_str().unwrap().parse().unwrap()
 }
 
+/// Current time in microseconds since the Unix epoch.
+pub(crate) fn micros_since_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_micros() as u64)
+        .unwrap_or_default()
+}
+
 fn get_timestamp(start: SystemTime) -> Result<u64> {
     let since_epoch = start.duration_since(UNIX_EPOCH)?;
     Ok(since_epoch.as_secs() * 1000 + u64::from(since_epoch.subsec_nanos()) / 1_000_000)
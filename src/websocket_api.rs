@@ -0,0 +1,419 @@
+use crate::account::{build_order, OrderRequest, OrderSide, OrderType, TimeInForce};
+use crate::client::*;
+use crate::errors::*;
+use crate::model::*;
+use crate::util::*;
+use crate::websockets::{is_read_timeout, set_read_timeout, WebSocketConnection};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tungstenite::{connect, Message};
+use url::Url;
+
+// Parameters sent as numbers, all others are sent as strings
+const NUMBER_PARAMETERS: [&str; 2] = ["recvWindow", "timestamp"];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WebsocketApiResponse {
+    id: Option<Value>,
+    status: u16,
+    result: Option<Box<RawValue>>,
+    error: Option<BinanceContentError>,
+    rate_limits: Option<Vec<RateLimit>>,
+}
+
+impl WebsocketApiResponse {
+    fn into_result<T: DeserializeOwned>(self) -> Result<T> {
+        match (self.error, self.result) {
+            (Some(error), _) => Err(ErrorKind::BinanceError(error).into()),
+            (None, Some(result)) => Ok(serde_json::from_str(result.get())?),
+            (None, None) => bail!(format!("Received response: {} without result", self.status)),
+        }
+    }
+}
+
+/// Client of the WebSocket API: requests and responses over one persistent
+/// connection, as an alternative to the REST API for trading.
+///
+/// Requests are sent one at a time, each waits for the response carrying its id
+/// for at most the request timeout, or the timeout given to the request.
+/// Responses to requests which timed out are discarded when they arrive.
+pub struct WebsocketApi {
+    pub socket: Option<WebSocketConnection>,
+    pub(crate) client: Client,
+    pub(crate) endpoint: String,
+    pub(crate) recv_window: u64,
+    pub(crate) request_timeout: Duration,
+    pub(crate) next_request_id: u64,
+    pub(crate) rate_limits: Vec<RateLimit>,
+    pub(crate) logged_on: bool,
+}
+
+impl WebsocketApi {
+    /// Default time to wait for a response (10 seconds unless set).
+    pub fn set_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn connect(&mut self) -> Result<()> {
+        match connect(Url::parse(&self.endpoint)?) {
+            Ok(answer) => {
+                self.socket = Some(answer);
+                self.logged_on = false;
+                Ok(())
+            }
+            Err(e) => bail!(format!("Error during handshake {}", e)),
+        }
+    }
+
+    pub fn disconnect(&mut self) -> Result<()> {
+        if let Some(ref mut socket) = self.socket {
+            socket.0.close(None)?;
+            return Ok(());
+        }
+        bail!("Not able to close the connection");
+    }
+
+    /// Rate limits usage reported with the last response.
+    pub fn rate_limits(&self) -> &[RateLimit] {
+        &self.rate_limits
+    }
+
+    /// Send a request which needs no authentication.
+    pub fn request<T: DeserializeOwned>(
+        &mut self, method: &str, parameters: BTreeMap<String, String>,
+    ) -> Result<T> {
+        self.request_with_timeout(method, parameters, None)
+    }
+
+    /// Like `request`, waiting at most `timeout` for the response, or the
+    /// request timeout when `None`.
+    pub fn request_with_timeout<T, D>(
+        &mut self, method: &str, parameters: BTreeMap<String, String>, timeout: D,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+        D: Into<Option<Duration>>,
+    {
+        let params = parameters
+            .into_iter()
+            .map(|(key, value)| (key, Value::String(value)))
+            .collect();
+        self.send(method, params, timeout.into())
+    }
+
+    /// Send a request signed with the secret key, or authenticated by the
+    /// session once logged on.
+    pub fn signed_request<T: DeserializeOwned>(
+        &mut self, method: &str, parameters: BTreeMap<String, String>,
+    ) -> Result<T> {
+        self.signed_request_with_timeout(method, parameters, None)
+    }
+
+    /// Like `signed_request`, waiting at most `timeout` for the response, or
+    /// the request timeout when `None`.
+    pub fn signed_request_with_timeout<T, D>(
+        &mut self, method: &str, mut parameters: BTreeMap<String, String>, timeout: D,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+        D: Into<Option<Duration>>,
+    {
+        if self.logged_on {
+            let request = build_signed_request(parameters, self.recv_window)?;
+            return self.send(method, signed_params(&request, None), timeout.into());
+        }
+        parameters.insert("apiKey".into(), self.client.api_key().into());
+        let request = build_signed_request(parameters, self.recv_window)?;
+        let signature = self.client.signature(&request);
+        self.send(
+            method,
+            signed_params(&request, Some(signature)),
+            timeout.into(),
+        )
+    }
+
+    pub fn ping(&mut self) -> Result<()> {
+        self.request::<Empty>("ping", BTreeMap::new()).map(|_| ())
+    }
+
+    pub fn get_server_time(&mut self) -> Result<ServerTime> {
+        self.request("time", BTreeMap::new())
+    }
+
+    /// Authenticate the session, so that further requests need no signature.
+    ///
+    /// Binance only accepts Ed25519 API keys for this, `sign` returns the
+    /// base64 encoded Ed25519 signature of the payload it is given.
+    pub fn session_logon<F>(&mut self, sign: F) -> Result<SessionStatus>
+    where
+        F: FnOnce(&str) -> String,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("apiKey".into(), self.client.api_key().into());
+        let request = build_signed_request(parameters, self.recv_window)?;
+        let signature = sign(&request);
+        let status = self.send(
+            "session.logon",
+            signed_params(&request, Some(signature)),
+            None,
+        )?;
+        self.logged_on = true;
+        Ok(status)
+    }
+
+    pub fn session_status(&mut self) -> Result<SessionStatus> {
+        self.request("session.status", BTreeMap::new())
+    }
+
+    pub fn session_logout(&mut self) -> Result<SessionStatus> {
+        let status = self.request("session.logout", BTreeMap::new())?;
+        self.logged_on = false;
+        Ok(status)
+    }
+
+    // Account Information
+    pub fn get_account(&mut self) -> Result<AccountInformation> {
+        self.signed_request("account.status", BTreeMap::new())
+    }
+
+    // Current open orders for ONE symbol
+    pub fn get_open_orders<S>(&mut self, symbol: S) -> Result<Vec<Order>>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        self.signed_request("openOrders.status", parameters)
+    }
+
+    // Check an order's status
+    pub fn order_status<S>(&mut self, symbol: S, order_id: u64) -> Result<Order>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("orderId".into(), order_id.to_string());
+        self.signed_request("order.status", parameters)
+    }
+
+    // Place a LIMIT order - BUY
+    pub fn limit_buy<S, F>(&mut self, symbol: S, qty: F, price: f64) -> Result<Transaction>
+    where
+        S: Into<String>,
+        F: Into<f64>,
+    {
+        self.custom_order(
+            symbol,
+            qty,
+            price,
+            None,
+            OrderSide::Buy,
+            OrderType::Limit,
+            TimeInForce::GTC,
+            None,
+        )
+    }
+
+    // Place a LIMIT order - SELL
+    pub fn limit_sell<S, F>(&mut self, symbol: S, qty: F, price: f64) -> Result<Transaction>
+    where
+        S: Into<String>,
+        F: Into<f64>,
+    {
+        self.custom_order(
+            symbol,
+            qty,
+            price,
+            None,
+            OrderSide::Sell,
+            OrderType::Limit,
+            TimeInForce::GTC,
+            None,
+        )
+    }
+
+    // Place a MARKET order - BUY
+    pub fn market_buy<S, F>(&mut self, symbol: S, qty: F) -> Result<Transaction>
+    where
+        S: Into<String>,
+        F: Into<f64>,
+    {
+        self.custom_order(
+            symbol,
+            qty,
+            0.0,
+            None,
+            OrderSide::Buy,
+            OrderType::Market,
+            TimeInForce::GTC,
+            None,
+        )
+    }
+
+    // Place a MARKET order - SELL
+    pub fn market_sell<S, F>(&mut self, symbol: S, qty: F) -> Result<Transaction>
+    where
+        S: Into<String>,
+        F: Into<f64>,
+    {
+        self.custom_order(
+            symbol,
+            qty,
+            0.0,
+            None,
+            OrderSide::Sell,
+            OrderType::Market,
+            TimeInForce::GTC,
+            None,
+        )
+    }
+
+    /// Place a custom order
+    #[allow(clippy::too_many_arguments)]
+    pub fn custom_order<S, F>(
+        &mut self, symbol: S, qty: F, price: f64, stop_price: Option<f64>, order_side: OrderSide,
+        order_type: OrderType, time_in_force: TimeInForce, new_client_order_id: Option<String>,
+    ) -> Result<Transaction>
+    where
+        S: Into<String>,
+        F: Into<f64>,
+    {
+        let order = build_order(OrderRequest {
+            symbol: symbol.into(),
+            qty: qty.into(),
+            price,
+            stop_price,
+            order_side,
+            order_type,
+            time_in_force,
+            new_client_order_id,
+        });
+        self.signed_request("order.place", order)
+    }
+
+    /// Place a test custom order
+    ///
+    /// This order is sandboxed: it is validated, but not sent to the matching engine.
+    #[allow(clippy::too_many_arguments)]
+    pub fn test_custom_order<S, F>(
+        &mut self, symbol: S, qty: F, price: f64, stop_price: Option<f64>, order_side: OrderSide,
+        order_type: OrderType, time_in_force: TimeInForce, new_client_order_id: Option<String>,
+    ) -> Result<()>
+    where
+        S: Into<String>,
+        F: Into<f64>,
+    {
+        let order = build_order(OrderRequest {
+            symbol: symbol.into(),
+            qty: qty.into(),
+            price,
+            stop_price,
+            order_side,
+            order_type,
+            time_in_force,
+            new_client_order_id,
+        });
+        self.signed_request::<Empty>("order.test", order)
+            .map(|_| ())
+    }
+
+    pub fn cancel_order<S>(&mut self, symbol: S, order_id: u64) -> Result<OrderCanceled>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("orderId".into(), order_id.to_string());
+        self.signed_request("order.cancel", parameters)
+    }
+
+    pub fn cancel_order_with_client_id<S>(
+        &mut self, symbol: S, orig_client_order_id: String,
+    ) -> Result<OrderCanceled>
+    where
+        S: Into<String>,
+    {
+        let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+        parameters.insert("symbol".into(), symbol.into());
+        parameters.insert("origClientOrderId".into(), orig_client_order_id);
+        self.signed_request("order.cancel", parameters)
+    }
+
+    fn send<T: DeserializeOwned>(
+        &mut self, method: &str, params: Map<String, Value>, timeout: Option<Duration>,
+    ) -> Result<T> {
+        let timeout = timeout.unwrap_or(self.request_timeout);
+        let socket = match self.socket {
+            Some(ref mut socket) => socket,
+            None => bail!("Not connected to the WebSocket API"),
+        };
+        let id = self.next_request_id;
+        self.next_request_id += 1;
+
+        let mut request = serde_json::json!({ "id": id, "method": method });
+        if !params.is_empty() {
+            request["params"] = Value::Object(params);
+        }
+        socket.0.write_message(Message::Text(request.to_string()))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                bail!(ErrorKind::WebSocketApiTimeout(method.into(), timeout));
+            }
+            set_read_timeout(socket, Some(deadline - now))?;
+            let message = match socket.0.read_message() {
+                Ok(message) => message,
+                Err(ref e) if is_read_timeout(e) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            match message {
+                Message::Text(msg) => {
+                    let response: WebsocketApiResponse = serde_json::from_str(&msg)?;
+                    // Errors for malformed requests come back without an id
+                    let own_response = match response.id {
+                        Some(ref response_id) => response_id.as_u64() == Some(id),
+                        None => response.error.is_some(),
+                    };
+                    if !own_response {
+                        log::debug!("Discarding WebSocket API response {}", msg);
+                        continue;
+                    }
+                    if let Some(ref rate_limits) = response.rate_limits {
+                        self.rate_limits = rate_limits.clone();
+                    }
+                    return response.into_result();
+                }
+                Message::Ping(_) | Message::Pong(_) | Message::Binary(_) => (),
+                Message::Close(e) => bail!(ErrorKind::WebSocketDisconnected(e)),
+            }
+        }
+    }
+}
+
+// Parameters of a signed request, from the query string which was signed
+fn signed_params(request: &str, signature: Option<String>) -> Map<String, Value> {
+    let mut params: Map<String, Value> = request
+        .split('&')
+        .filter_map(|parameter| parameter.split_once('='))
+        .map(|(key, value)| {
+            let value = match value.parse::<u64>() {
+                Ok(number) if NUMBER_PARAMETERS.contains(&key) => Value::from(number),
+                _ => Value::from(value),
+            };
+            (key.to_string(), value)
+        })
+        .collect();
+    if let Some(signature) = signature {
+        params.insert("signature".into(), Value::from(signature));
+    }
+    params
+}
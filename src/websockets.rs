@@ -1,25 +1,40 @@
+use crate::capture::{replay, Capture, ReplaySpeed};
+use crate::latency::LatencyMonitor;
 use crate::errors::*;
 use crate::config::*;
 use crate::model::*;
+use crate::streams::{stream_names, StreamMessage};
+use crate::util::micros_since_epoch;
 use url::Url;
+use serde::de::{DeserializeOwned, Deserializer, IgnoredAny, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::borrow::Cow;
+use std::path::Path;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
 use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
 use tungstenite::{connect, Message};
 use tungstenite::protocol::WebSocket;
 use tungstenite::stream::MaybeTlsStream;
 use tungstenite::handshake::client::Response;
 
 #[allow(clippy::all)]
-enum WebsocketAPI {
+pub(crate) enum WebsocketAPI {
     Default,
     MultiStream,
     Custom(String),
+    CustomMultiStream(String),
 }
 
 impl WebsocketAPI {
-    fn params(self, subscription: &str) -> String {
+    pub(crate) fn params(self, subscription: &str) -> String {
         match self {
             WebsocketAPI::Default => format!("wss://stream.binance.com:9443/ws/{}", subscription),
             WebsocketAPI::MultiStream => format!(
@@ -27,6 +42,12 @@ impl WebsocketAPI {
                 subscription
             ),
             WebsocketAPI::Custom(url) => format!("{}/{}", url, subscription),
+            // The combined stream endpoint is next to the raw stream one, `/ws`
+            WebsocketAPI::CustomMultiStream(url) => format!(
+                "{}/stream?streams={}",
+                url.trim_end_matches('/').trim_end_matches("/ws"),
+                subscription
+            ),
         }
     }
 }
@@ -37,71 +58,850 @@ pub enum WebsocketEvent {
     AccountUpdate(AccountUpdateEvent),
     BalanceUpdate(BalanceUpdateEvent),
     OrderTrade(OrderTradeEvent),
+    ListStatus(ListStatusEvent),
+    ListenKeyExpired(ListenKeyExpiredEvent),
+    ExternalLockUpdate(ExternalLockUpdateEvent),
+    EventStreamTerminated(EventStreamTerminatedEvent),
     AggrTrades(AggrTradesEvent),
     Trade(TradeEvent),
     OrderBook(OrderBook),
     DayTicker(DayTickerEvent),
     DayTickerAll(Vec<DayTickerEvent>),
+    MiniTicker(MiniTickerEvent),
+    MiniTickerAll(Vec<MiniTickerEvent>),
+    RollingWindowTicker(RollingWindowTickerEvent),
+    RollingWindowTickerAll(Vec<RollingWindowTickerEvent>),
+    AvgPrice(AvgPriceEvent),
     Kline(KlineEvent),
     DepthOrderBook(DepthOrderBookEvent),
     BookTicker(BookTickerEvent),
+    Reconnected(ReconnectedEvent),
+    /// Response to a live request which was not waited for
+    LiveResponse(LiveResponse),
+    /// Message which could not be decoded, see `set_strict`
+    Unknown {
+        raw: String,
+        error: String,
+    },
 }
 
-pub struct WebSockets<'a> {
-    pub socket: Option<(WebSocket<MaybeTlsStream<TcpStream>>, Response)>,
-    handler: Box<dyn FnMut(WebsocketEvent) -> Result<()> + 'a>,
+/// How a supervised websocket re-establishes a lost connection.
+///
+/// Binance closes every connection after 24 hours, so the connection is also
+/// renewed once it is older than `max_connection_age`. The age is checked after
+/// each handled message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReconnectPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Give up after this many failed attempts in a row, `None` retries forever.
+    pub max_attempts: Option<u32>,
+    pub max_connection_age: Duration,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(untagged)]
-enum Events {
-    Vec(Vec<DayTickerEvent>),
-    BalanceUpdateEvent(BalanceUpdateEvent),
-    DayTickerEvent(DayTickerEvent),
-    BookTickerEvent(BookTickerEvent),
-    AccountUpdateEvent(AccountUpdateEvent),
-    OrderTradeEvent(OrderTradeEvent),
-    AggrTradesEvent(AggrTradesEvent),
-    TradeEvent(TradeEvent),
-    KlineEvent(KlineEvent),
-    OrderBook(OrderBook),
-    DepthOrderBookEvent(DepthOrderBookEvent),
+impl Default for ReconnectPolicy {
+    fn default() -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_attempts: None,
+            max_connection_age: Duration::from_secs(23 * 60 * 60),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn set_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn set_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn set_max_attempts<T: Into<Option<u32>>>(mut self, max_attempts: T) -> Self {
+        self.max_attempts = max_attempts.into();
+        self
+    }
+
+    pub fn set_max_connection_age(mut self, max_connection_age: Duration) -> Self {
+        self.max_connection_age = max_connection_age;
+        self
+    }
+
+    /// Connect to `wss` with exponential backoff until it succeeds, `running`
+    /// returns false (`Ok(None)`) or `max_attempts` is exhausted.
+    /// Returns the socket and the number of attempts.
+    pub(crate) fn connect<F>(
+        &self, wss: &str, running: F,
+    ) -> Result<Option<(WebSocketConnection, u32)>>
+    where
+        F: Fn() -> bool,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut attempts = 0;
+        while running() {
+            attempts += 1;
+            match connect(Url::parse(wss)?) {
+                Ok(answer) => return Ok(Some((answer, attempts))),
+                Err(e) => {
+                    if matches!(self.max_attempts, Some(max) if attempts >= max) {
+                        bail!(format!(
+                            "Not able to reconnect after {} attempts: {}",
+                            attempts, e
+                        ));
+                    }
+                    log::warn!("Reconnect attempt {} failed: {}", attempts, e);
+                    thread::sleep(backoff);
+                    backoff = std::cmp::min(backoff * 2, self.max_backoff);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Keeps a connection alive and detects when it silently stopped delivering.
+///
+/// Reads wait at most `read_timeout`, so that `event_loop` notices a shutdown,
+/// sends pings and checks the other timeouts even on a quiet stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeartbeatPolicy {
+    pub read_timeout: Duration,
+    /// Send a ping when nothing was sent for this long.
+    pub ping_interval: Option<Duration>,
+    /// Close the connection when nothing, pongs included, was received for
+    /// this long. It is reestablished with a reconnect policy, otherwise
+    /// `event_loop` fails with `WebSocketStale`.
+    pub stale_timeout: Option<Duration>,
+}
+
+impl Default for HeartbeatPolicy {
+    fn default() -> HeartbeatPolicy {
+        HeartbeatPolicy {
+            read_timeout: Duration::from_secs(1),
+            ping_interval: Some(Duration::from_secs(30)),
+            stale_timeout: Some(Duration::from_secs(90)),
+        }
+    }
+}
+
+impl HeartbeatPolicy {
+    pub fn set_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    pub fn set_ping_interval<T: Into<Option<Duration>>>(mut self, ping_interval: T) -> Self {
+        self.ping_interval = ping_interval.into();
+        self
+    }
+
+    pub fn set_stale_timeout<T: Into<Option<Duration>>>(mut self, stale_timeout: T) -> Self {
+        self.stale_timeout = stale_timeout.into();
+        self
+    }
+}
+
+/// Stops an `event_loop` from another thread.
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownHandle {
+    shutdown: Arc<AtomicBool>,
+}
+
+impl ShutdownHandle {
+    /// The loop returns after the current read, at most a read timeout later
+    /// when a heartbeat policy is set.
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::Relaxed)
+    }
+}
+
+pub(crate) type WebSocketConnection = (WebSocket<MaybeTlsStream<TcpStream>>, Response);
+
+pub(crate) fn set_read_timeout(
+    socket: &WebSocketConnection, read_timeout: Option<Duration>,
+) -> Result<()> {
+    #[allow(unreachable_patterns)]
+    match socket.0.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(read_timeout)?,
+        MaybeTlsStream::NativeTls(stream) => stream.get_ref().set_read_timeout(read_timeout)?,
+        _ => (),
+    }
+    Ok(())
+}
+
+pub(crate) fn read_timeout(socket: &WebSocketConnection) -> Result<Option<Duration>> {
+    #[allow(unreachable_patterns)]
+    let read_timeout = match socket.0.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.read_timeout()?,
+        MaybeTlsStream::NativeTls(stream) => stream.get_ref().read_timeout()?,
+        _ => None,
+    };
+    Ok(read_timeout)
+}
+
+pub(crate) fn is_read_timeout(error: &tungstenite::Error) -> bool {
+    match error {
+        tungstenite::Error::Io(e) => matches!(
+            e.kind(),
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        ),
+        _ => false,
+    }
+}
+
+// How long a live request waits for its response by default
+const LIVE_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Send a live request (`SUBSCRIBE`, `LIST_SUBSCRIPTIONS`, ...) and wait for its
+/// response. Stream messages received in the meantime are passed to `on_message`.
+///
+/// Fails with `WebSocketLiveRequestTimeout` when no response arrives in time.
+pub(crate) fn send_live_request<F>(
+    socket: &mut WebSocketConnection, id: u64, method: &str, params: Vec<serde_json::Value>,
+    timeout: Duration, on_message: F,
+) -> Result<serde_json::Value>
+where
+    F: FnMut(&str) -> Result<()>,
+{
+    let request = serde_json::json!({ "method": method, "params": params, "id": id });
+    socket.0.write_message(Message::Text(request.to_string()))?;
+
+    // The reads are bounded by the deadline, then set back for the event loop
+    let read_timeout = read_timeout(socket)?;
+    let response = wait_for_live_response(socket, id, method, timeout, on_message);
+    set_read_timeout(socket, read_timeout)?;
+    response
+}
+
+fn wait_for_live_response<F>(
+    socket: &mut WebSocketConnection, id: u64, method: &str, timeout: Duration, mut on_message: F,
+) -> Result<serde_json::Value>
+where
+    F: FnMut(&str) -> Result<()>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            bail!(ErrorKind::WebSocketLiveRequestTimeout(
+                method.into(),
+                id,
+                timeout
+            ));
+        }
+        set_read_timeout(socket, Some(deadline - now))?;
+        let message = match socket.0.read_message() {
+            Ok(message) => message,
+            Err(ref e) if is_read_timeout(e) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        match message {
+            Message::Text(msg) => {
+                let value: serde_json::Value = serde_json::from_str(&msg)?;
+                if let Some(response) = LiveResponse::from_value(&value) {
+                    // Errors for malformed requests come back without an id
+                    if response.id == Some(id)
+                        || (response.id.is_none() && response.error.is_some())
+                    {
+                        return response.into_result();
+                    }
+                }
+                on_message(&msg)?;
+            }
+            Message::Ping(_) | Message::Pong(_) | Message::Binary(_) => (),
+            Message::Close(e) => bail!(ErrorKind::WebSocketDisconnected(e)),
+        }
+    }
+}
+
+/// Streams added and removed on the open connection, replayed after a reconnect.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LiveSubscriptions {
+    pub(crate) subscribed: Vec<String>,
+    pub(crate) unsubscribed: Vec<String>,
+}
+
+impl LiveSubscriptions {
+    pub(crate) fn subscribe(&mut self, streams: &[String]) {
+        self.unsubscribed.retain(|stream| !streams.contains(stream));
+        for stream in streams {
+            if !self.subscribed.contains(stream) {
+                self.subscribed.push(stream.clone());
+            }
+        }
+    }
+
+    pub(crate) fn unsubscribe(&mut self, streams: &[String]) {
+        self.subscribed.retain(|stream| !streams.contains(stream));
+        for stream in streams {
+            if !self.unsubscribed.contains(stream) {
+                self.unsubscribed.push(stream.clone());
+            }
+        }
+    }
+}
+
+pub(crate) fn stream_params(streams: &[String]) -> Vec<serde_json::Value> {
+    streams
+        .iter()
+        .map(|stream| serde_json::Value::from(stream.as_str()))
+        .collect()
+}
+
+/// Event type of a websocket connection, decoded from its stream messages.
+///
+/// Implemented by `WebsocketEvent` and `futures::websockets::FuturesWebsocketEvent`.
+pub trait StreamEvent: Sized {
+    /// Decode a stream message along with the stream name of a combined
+    /// stream, the `Unknown` event if it cannot be decoded.
+    fn parse_message(msg: &str) -> StreamMessage<Self>;
+
+    /// Decode a stream message, the `Unknown` event if it cannot be decoded.
+    fn parse(msg: &str) -> Self {
+        Self::parse_message(msg).event
+    }
+
+    /// Event handled once a lost connection has been reestablished.
+    fn reconnected(event: ReconnectedEvent) -> Self;
+
+    /// Raw message and decoding error of an `Unknown` event.
+    fn as_unknown(&self) -> Option<(&str, &str)>;
+}
+
+impl StreamEvent for WebsocketEvent {
+    fn parse_message(msg: &str) -> StreamMessage<Self> {
+        parse_message(msg)
+    }
+
+    fn reconnected(event: ReconnectedEvent) -> Self {
+        WebsocketEvent::Reconnected(event)
+    }
+
+    fn as_unknown(&self) -> Option<(&str, &str)> {
+        match self {
+            WebsocketEvent::Unknown { raw, error } => Some((raw, error)),
+            _ => None,
+        }
+    }
+}
+
+/// Connection to market or user data streams, whose messages are decoded
+/// into `E` and passed to the handler `H`.
+///
+/// Used as `WebSockets` on spot and `futures::websockets::FuturesWebSockets`
+/// on futures, which only differ in their events and in how they connect.
+pub struct StreamConnection<E, H> {
+    pub socket: Option<WebSocketConnection>,
+    handler: H,
+    url: Option<String>,
+    connected_at: Option<Instant>,
+    reconnect_policy: Option<ReconnectPolicy>,
+    live_subscriptions: LiveSubscriptions,
+    next_request_id: u64,
+    live_request_timeout: Duration,
+    heartbeat_policy: Option<HeartbeatPolicy>,
+    last_received: Instant,
+    last_sent: Instant,
+    shutdown: ShutdownHandle,
+    strict: bool,
+    capture: Option<Capture>,
+    latency_monitor: Option<LatencyMonitor>,
+    event: PhantomData<fn() -> E>,
+}
+
+/// Connection to spot market or user data streams.
+pub type WebSockets<'a> = StreamConnection<
+    WebsocketEvent,
+    Box<dyn FnMut(StreamMessage<WebsocketEvent>) -> Result<()> + 'a>,
+>;
+
+/// Top level fields of a stream message, read without decoding the event.
+#[derive(Deserialize)]
+pub(crate) struct EventHeader<'a> {
+    #[serde(rename = "e", borrow, default)]
+    pub event_type: Option<Cow<'a, str>>,
+
+    #[serde(borrow, default)]
+    pub stream: Option<Cow<'a, str>>,
+
+    #[serde(borrow, default)]
+    pub data: Option<&'a RawValue>,
+
+    #[serde(rename = "lastUpdateId", default, deserialize_with = "present")]
+    pub last_update_id: bool,
+
+    #[serde(rename = "E", default)]
+    pub event_time: Option<u64>,
+
+    #[serde(rename = "T", default)]
+    pub transaction_time: Option<u64>,
+
+    #[serde(default, deserialize_with = "present")]
+    result: bool,
+
+    #[serde(default, deserialize_with = "present")]
+    error: bool,
+
+    #[serde(default, deserialize_with = "present")]
+    code: bool,
+}
+
+impl<'a> EventHeader<'a> {
+    /// Read the header of a message which is not an array.
+    pub(crate) fn parse(msg: &'a str) -> Result<EventHeader<'a>> {
+        Ok(serde_json::from_str(msg)?)
+    }
+
+    /// Whether the message answers a live request rather than being an event.
+    pub(crate) fn is_live_response(&self) -> bool {
+        self.event_type.is_none() && (self.result || self.error || self.code)
+    }
+
+    /// Event time, or transaction time for the events without one.
+    pub(crate) fn time(&self) -> Option<u64> {
+        self.event_time.or(self.transaction_time)
+    }
+}
+
+fn present<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    IgnoredAny::deserialize(deserializer).map(|_| true)
+}
+
+// Event type and time of the first element of an array message
+struct ArrayEventType(Option<(String, Option<u64>)>);
+
+impl<'de> Deserialize<'de> for ArrayEventType {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FirstElement;
+
+        impl<'de> Visitor<'de> for FirstElement {
+            type Value = ArrayEventType;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("an array of events")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> std::result::Result<ArrayEventType, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let first: Option<EventHeader<'de>> = seq.next_element()?;
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(ArrayEventType(first.and_then(|header| {
+                    let time = header.time();
+                    header
+                        .event_type
+                        .map(|event_type| (event_type.into_owned(), time))
+                })))
+            }
+        }
+
+        deserializer.deserialize_seq(FirstElement)
+    }
+}
+
+/// Event type and time of an array message (`!ticker@arr` and the like),
+/// `None` for any other message.
+pub(crate) fn array_event_type(msg: &str) -> Result<Option<(String, Option<u64>)>> {
+    if !msg.trim_start().starts_with('[') {
+        return Ok(None);
+    }
+    let event_type: ArrayEventType = serde_json::from_str(msg)?;
+    Ok(event_type.0)
+}
+
+pub(crate) fn decode<T: DeserializeOwned>(msg: &str) -> std::result::Result<T, String> {
+    serde_json::from_str(msg).map_err(|e| e.to_string())
+}
+
+pub(crate) fn unknown_event_type(event_type: &str) -> String {
+    format!("unknown event type {}", event_type)
+}
+
+/// Decode a stream message along with the stream name of a combined stream,
+/// `WebsocketEvent::Unknown` if it is not a known event.
+///
+/// The event is picked by its `e` field, or by the stream name for the events
+/// of a combined stream which have none, and decoded straight from the text.
+pub(crate) fn parse_message(msg: &str) -> StreamMessage<WebsocketEvent> {
+    let mut stream = None;
+    let mut event_time = None;
+    let event = parse_stream_event(msg, &mut stream, &mut event_time).unwrap_or_else(|error| {
+        WebsocketEvent::Unknown {
+            raw: msg.into(),
+            error,
+        }
+    });
+    StreamMessage {
+        stream,
+        event,
+        event_time,
+        received_at: None,
+    }
+}
+
+fn parse_stream_event(
+    msg: &str, stream: &mut Option<String>, event_time: &mut Option<u64>,
+) -> std::result::Result<WebsocketEvent, String> {
+    if let Some((event_type, time)) = array_event_type(msg).map_err(|e| e.to_string())? {
+        *event_time = time;
+        return match event_type.as_str() {
+            "24hrTicker" => decode(msg).map(WebsocketEvent::DayTickerAll),
+            "24hrMiniTicker" => decode(msg).map(WebsocketEvent::MiniTickerAll),
+            "1hTicker" | "4hTicker" | "1dTicker" => {
+                decode(msg).map(WebsocketEvent::RollingWindowTickerAll)
+            }
+            _ => Err(unknown_event_type(&event_type)),
+        };
+    }
+
+    let header = EventHeader::parse(msg).map_err(|e| e.to_string())?;
+    if let Some(data) = header.data {
+        *stream = header.stream.map(Cow::into_owned);
+        return parse_stream_event(data.get(), stream, event_time);
+    }
+    *event_time = header.time();
+
+    match header.event_type.as_deref() {
+        Some("24hrTicker") => decode(msg).map(WebsocketEvent::DayTicker),
+        Some("24hrMiniTicker") => decode(msg).map(WebsocketEvent::MiniTicker),
+        Some("1hTicker") | Some("4hTicker") | Some("1dTicker") => {
+            decode(msg).map(WebsocketEvent::RollingWindowTicker)
+        }
+        Some("avgPrice") => decode(msg).map(WebsocketEvent::AvgPrice),
+        Some("outboundAccountPosition") => decode(msg).map(WebsocketEvent::AccountUpdate),
+        Some("balanceUpdate") => decode(msg).map(WebsocketEvent::BalanceUpdate),
+        Some("executionReport") => decode(msg).map(WebsocketEvent::OrderTrade),
+        Some("listStatus") => decode(msg).map(WebsocketEvent::ListStatus),
+        Some("listenKeyExpired") => decode(msg).map(WebsocketEvent::ListenKeyExpired),
+        Some("externalLockUpdate") => decode(msg).map(WebsocketEvent::ExternalLockUpdate),
+        Some("eventStreamTerminated") => decode(msg).map(WebsocketEvent::EventStreamTerminated),
+        Some("aggTrade") => decode(msg).map(WebsocketEvent::AggrTrades),
+        Some("trade") => decode(msg).map(WebsocketEvent::Trade),
+        Some("kline") => decode(msg).map(WebsocketEvent::Kline),
+        Some("depthUpdate") => decode(msg).map(WebsocketEvent::DepthOrderBook),
+        Some(event_type) => Err(unknown_event_type(event_type)),
+        None if header.is_live_response() => {
+            parse_live_response(msg).map(WebsocketEvent::LiveResponse)
+        }
+        // Book tickers and partial book depths have no event type
+        None => match stream.as_deref() {
+            Some(stream) if stream.contains("@depth") => decode(msg).map(WebsocketEvent::OrderBook),
+            Some(stream) if stream.ends_with("bookTicker") => {
+                decode(msg).map(WebsocketEvent::BookTicker)
+            }
+            _ if header.last_update_id => decode(msg).map(WebsocketEvent::OrderBook),
+            _ => decode(msg).map(WebsocketEvent::BookTicker),
+        },
+    }
+}
+
+pub(crate) fn parse_live_response(msg: &str) -> std::result::Result<LiveResponse, String> {
+    let value: serde_json::Value = decode(msg)?;
+    LiveResponse::from_value(&value).ok_or_else(|| "invalid live response".to_string())
 }
 
 impl<'a> WebSockets<'a> {
-    pub fn new<Callback>(handler: Callback) -> WebSockets<'a>
+    pub fn new<Callback>(mut handler: Callback) -> WebSockets<'a>
     where
         Callback: FnMut(WebsocketEvent) -> Result<()> + 'a,
     {
-        WebSockets {
+        WebSockets::new_with_stream_names(move |message: StreamMessage<WebsocketEvent>| {
+            handler(message.event)
+        })
+    }
+
+    /// Like `new`, the handler also receives the stream name of combined
+    /// stream messages.
+    pub fn new_with_stream_names<Callback>(handler: Callback) -> WebSockets<'a>
+    where
+        Callback: FnMut(StreamMessage<WebsocketEvent>) -> Result<()> + 'a,
+    {
+        StreamConnection::with_handler(Box::new(handler))
+    }
+
+    /// Connect to a stream, given either as a `Stream` or by its name.
+    pub fn connect<S>(&mut self, subscription: S) -> Result<()>
+    where
+        S: Into<String>,
+    {
+        self.connect_wss(WebsocketAPI::Default.params(&subscription.into()))
+    }
+
+    pub fn connect_with_config<S>(&mut self, subscription: S, config: &Config) -> Result<()>
+    where
+        S: Into<String>,
+    {
+        self.connect_wss(
+            WebsocketAPI::Custom(config.ws_endpoint.clone()).params(&subscription.into()),
+        )
+    }
+
+    pub fn connect_multiple_streams<S>(&mut self, endpoints: &[S]) -> Result<()>
+    where
+        S: Clone + Into<String>,
+    {
+        self.connect_wss(WebsocketAPI::MultiStream.params(&stream_names(endpoints).join("/")))
+    }
+
+    pub fn connect_multiple_streams_with_config<S>(
+        &mut self, endpoints: &[S], config: &Config,
+    ) -> Result<()>
+    where
+        S: Clone + Into<String>,
+    {
+        self.connect_wss(
+            WebsocketAPI::CustomMultiStream(config.ws_endpoint.clone())
+                .params(&stream_names(endpoints).join("/")),
+        )
+    }
+}
+
+impl<E, H> StreamConnection<E, H>
+where
+    E: StreamEvent,
+    H: FnMut(StreamMessage<E>) -> Result<()>,
+{
+    pub(crate) fn with_handler(handler: H) -> Self {
+        StreamConnection {
             socket: None,
-            handler: Box::new(handler),
+            handler,
+            url: None,
+            connected_at: None,
+            reconnect_policy: None,
+            live_subscriptions: LiveSubscriptions::default(),
+            next_request_id: 1,
+            live_request_timeout: LIVE_REQUEST_TIMEOUT,
+            heartbeat_policy: None,
+            last_received: Instant::now(),
+            last_sent: Instant::now(),
+            shutdown: ShutdownHandle::default(),
+            strict: false,
+            capture: None,
+            latency_monitor: None,
+            event: PhantomData,
         }
     }
 
-    pub fn connect(&mut self, subscription: &str) -> Result<()> {
-        self.connect_wss(WebsocketAPI::Default.params(subscription))
+    /// Supervise the connection: instead of failing, `event_loop` reconnects to
+    /// the same streams and handles a `Reconnected` event.
+    pub fn set_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Some(reconnect_policy);
+        self
     }
 
-    pub fn connect_with_config(&mut self, subscription: &str, config: &Config) -> Result<()> {
-        self.connect_wss(WebsocketAPI::Custom(config.ws_endpoint.clone()).params(subscription))
+    /// Bound the reads, send pings and watch for a stale connection.
+    pub fn set_heartbeat_policy(mut self, heartbeat_policy: HeartbeatPolicy) -> Self {
+        self.heartbeat_policy = Some(heartbeat_policy);
+        self
     }
 
-    pub fn connect_multiple_streams(&mut self, endpoints: &[String]) -> Result<()> {
-        self.connect_wss(WebsocketAPI::MultiStream.params(&endpoints.join("/")))
+    /// Handle to stop `event_loop` from another thread.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
     }
 
-    fn connect_wss(&mut self, wss: String) -> Result<()> {
+    pub(crate) fn set_shutdown_handle(mut self, shutdown: ShutdownHandle) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Write every text frame received to `capture`, with its receive time.
+    pub fn set_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Record the latency of every event with an event time into `latency_monitor`.
+    pub fn set_latency_monitor(mut self, latency_monitor: LatencyMonitor) -> Self {
+        self.latency_monitor = Some(latency_monitor);
+        self
+    }
+
+    /// How long `subscribe` and the other live requests wait for their
+    /// response, 10 seconds by default.
+    pub fn set_live_request_timeout(mut self, live_request_timeout: Duration) -> Self {
+        self.live_request_timeout = live_request_timeout;
+        self
+    }
+
+    /// Fail with `WebSocketUnknownEvent` on a message which cannot be decoded,
+    /// instead of handling it as an `Unknown` event.
+    pub fn set_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub(crate) fn connect_wss(&mut self, wss: String) -> Result<()> {
         let url = Url::parse(&wss)?;
         match connect(url) {
             Ok(answer) => {
-                self.socket = Some(answer);
-                Ok(())
+                self.url = Some(wss);
+                self.live_subscriptions = LiveSubscriptions::default();
+                self.set_socket(answer)
             }
             Err(e) => bail!(format!("Error during handshake {}", e)),
         }
     }
 
+    fn reconnect(&mut self, running: &AtomicBool, reason: String) -> Result<()> {
+        let (policy, url) = match (&self.reconnect_policy, &self.url) {
+            (Some(policy), Some(url)) => (policy.clone(), url.clone()),
+            _ => bail!("Not able to reconnect without a reconnect policy and a connection"),
+        };
+        if let Some(mut socket) = self.socket.take() {
+            // The connection may already be gone
+            let _ = socket.0.close(None);
+        }
+
+        log::warn!("Reconnecting websocket {}: {}", url, reason);
+        let shutdown = self.shutdown.clone();
+        let keep_running = || running.load(Ordering::Relaxed) && !shutdown.is_shutdown();
+        if let Some((answer, attempts)) = policy.connect(&url, keep_running)? {
+            self.set_socket(answer)?;
+            self.replay_live_subscriptions()?;
+            let event = E::reconnected(ReconnectedEvent { attempts, reason });
+            (self.handler)(StreamMessage::from(event))?;
+        }
+        Ok(())
+    }
+
+    /// Subscribe to more streams on the open connection.
+    pub fn subscribe<S>(&mut self, streams: &[S]) -> Result<()>
+    where
+        S: Clone + Into<String>,
+    {
+        let streams = stream_names(streams);
+        self.send_live_request("SUBSCRIBE", stream_params(&streams))?;
+        self.live_subscriptions.subscribe(&streams);
+        Ok(())
+    }
+
+    /// Unsubscribe from streams on the open connection.
+    pub fn unsubscribe<S>(&mut self, streams: &[S]) -> Result<()>
+    where
+        S: Clone + Into<String>,
+    {
+        let streams = stream_names(streams);
+        self.send_live_request("UNSUBSCRIBE", stream_params(&streams))?;
+        self.live_subscriptions.unsubscribe(&streams);
+        Ok(())
+    }
+
+    /// Streams the connection is currently subscribed to.
+    pub fn list_subscriptions(&mut self) -> Result<Vec<String>> {
+        let result = self.send_live_request("LIST_SUBSCRIPTIONS", vec![])?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Set a property of the connection, e.g. `combined` to wrap the events
+    /// in `{"stream": .., "data": ..}`.
+    pub fn set_property(&mut self, property: &str, value: bool) -> Result<()> {
+        self.send_live_request("SET_PROPERTY", vec![property.into(), value.into()])?;
+        Ok(())
+    }
+
+    pub fn get_property(&mut self, property: &str) -> Result<serde_json::Value> {
+        self.send_live_request("GET_PROPERTY", vec![property.into()])
+    }
+
+    // Stream messages received while waiting for the response are handled as usual
+    fn send_live_request(
+        &mut self, method: &str, params: Vec<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let id = self.next_request_id;
+        self.next_request_id += 1;
+
+        let mut socket = match self.socket.take() {
+            Some(socket) => socket,
+            None => bail!("Not able to send a request without a connection"),
+        };
+        let timeout = self.live_request_timeout;
+        let response = send_live_request(&mut socket, id, method, params, timeout, |msg| {
+            self.receive_msg(msg)
+        });
+        self.socket = Some(socket);
+        response
+    }
+
+    fn replay_live_subscriptions(&mut self) -> Result<()> {
+        let live_subscriptions = self.live_subscriptions.clone();
+        if !live_subscriptions.subscribed.is_empty() {
+            self.subscribe(&live_subscriptions.subscribed)?;
+        }
+        if !live_subscriptions.unsubscribed.is_empty() {
+            self.unsubscribe(&live_subscriptions.unsubscribed)?;
+        }
+        Ok(())
+    }
+
+    fn set_socket(&mut self, socket: WebSocketConnection) -> Result<()> {
+        if let Some(ref policy) = self.heartbeat_policy {
+            set_read_timeout(&socket, Some(policy.read_timeout))?;
+        }
+        self.socket = Some(socket);
+        self.connected_at = Some(Instant::now());
+        self.last_received = Instant::now();
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    // Ping a quiet connection and drop a stale one
+    fn check_heartbeat(&mut self, running: &AtomicBool) -> Result<()> {
+        let policy = match self.heartbeat_policy {
+            Some(ref policy) => policy.clone(),
+            None => return Ok(()),
+        };
+
+        if let Some(stale_timeout) = policy.stale_timeout {
+            let elapsed = self.last_received.elapsed();
+            if elapsed >= stale_timeout {
+                if self.reconnect_policy.is_some() {
+                    return self.reconnect(running, format!("no data received for {:?}", elapsed));
+                }
+                if let Some(mut socket) = self.socket.take() {
+                    let _ = socket.0.close(None);
+                }
+                bail!(ErrorKind::WebSocketStale(elapsed));
+            }
+        }
+
+        if let (Some(ping_interval), Some(socket)) = (policy.ping_interval, self.socket.as_mut()) {
+            if self.last_sent.elapsed() >= ping_interval {
+                socket.0.write_message(Message::Ping(vec![]))?;
+                self.last_sent = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    fn keep_running(&self, running: &AtomicBool) -> bool {
+        running.load(Ordering::Relaxed) && !self.shutdown.is_shutdown()
+    }
+
+    fn connection_expired(&self) -> bool {
+        match (&self.reconnect_policy, self.connected_at) {
+            (Some(policy), Some(connected_at)) => {
+                connected_at.elapsed() >= policy.max_connection_age
+            }
+            _ => false,
+        }
+    }
+
     pub fn disconnect(&mut self) -> Result<()> {
         if let Some(ref mut socket) = self.socket {
             socket.0.close(None)?;
@@ -111,51 +911,1009 @@ impl<'a> WebSockets<'a> {
     }
 
     pub fn test_handle_msg(&mut self, msg: &str) -> Result<()> {
-        self.handle_msg(msg)
+        self.handle_msg(msg, micros_since_epoch())
     }
 
-    fn handle_msg(&mut self, msg: &str) -> Result<()> {
-        let value: serde_json::Value = serde_json::from_str(msg)?;
+    /// Handle the frames of a capture file like received ones, returns the
+    /// number of frames. Events carry the receive time of their frame.
+    pub fn replay<P: AsRef<Path>>(&mut self, path: P, speed: ReplaySpeed) -> Result<usize> {
+        replay(path, speed, |frame| {
+            self.handle_msg(&frame.message, frame.received_at)
+        })
+    }
 
-        if let Some(data) = value.get("data") {
-            self.handle_msg(&data.to_string())?;
-            return Ok(());
+    fn receive_msg(&mut self, msg: &str) -> Result<()> {
+        let received_at = micros_since_epoch();
+        if let Some(ref mut capture) = self.capture {
+            capture.record(received_at, msg)?;
+        }
+        self.handle_msg(msg, received_at)
+    }
+
+    fn handle_msg(&mut self, msg: &str, received_at: u64) -> Result<()> {
+        let mut message = E::parse_message(msg);
+        message.received_at = Some(received_at);
+        if let Some(ref latency_monitor) = self.latency_monitor {
+            latency_monitor.record_message(self.url.as_deref(), &message);
+        }
+        match message.event.as_unknown() {
+            Some((raw, error)) if self.strict => {
+                bail!(ErrorKind::WebSocketUnknownEvent(raw.into(), error.into()))
+            }
+            _ => (self.handler)(message),
+        }
+    }
+
+    pub fn event_loop(&mut self, running: &AtomicBool) -> Result<()> {
+        self.interruptible_event_loop(running, &AtomicBool::new(false))
+    }
+
+    /// Like `event_loop`, also returns once `interrupted` is set while connected.
+    /// A reconnect is only stopped by `running`.
+    pub(crate) fn interruptible_event_loop(
+        &mut self, running: &AtomicBool, interrupted: &AtomicBool,
+    ) -> Result<()> {
+        while self.keep_running(running) {
+            let socket = match self.socket {
+                Some(_) if interrupted.load(Ordering::SeqCst) => break,
+                Some(ref mut socket) => socket,
+                // A reconnect was interrupted, or the connection was closed
+                None => {
+                    self.reconnect(running, "not connected".into())?;
+                    continue;
+                }
+            };
+            let message = match socket.0.read_message() {
+                Ok(message) => message,
+                Err(ref e) if is_read_timeout(e) => {
+                    self.check_heartbeat(running)?;
+                    continue;
+                }
+                Err(e) if self.reconnect_policy.is_some() => {
+                    self.reconnect(running, format!("read error: {}", e))?;
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            self.last_received = Instant::now();
+            match message {
+                Message::Text(msg) => {
+                    if let Err(e) = self.receive_msg(&msg) {
+                        bail!(format!("Error on handling stream message: {}", e));
+                    }
+                    if self.connection_expired() {
+                        self.reconnect(running, "connection age limit reached".into())?;
+                    }
+                }
+                Message::Ping(_) | Message::Pong(_) | Message::Binary(_) => (),
+                Message::Close(e) => {
+                    if self.reconnect_policy.is_none() {
+                        bail!(ErrorKind::WebSocketDisconnected(e));
+                    }
+                    self.reconnect(running, format!("closed by server: {:?}", e))?;
+                }
+            }
+            self.check_heartbeat(running)?;
+        }
+        Ok(())
+    }
+}
+
+/// What a `WebsocketChannel` does with a new event when its buffer is full.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Stop reading from the socket until the consumer catches up.
+    Block,
+    /// Discard the oldest buffered event.
+    DropOldest,
+    /// Replace the latest buffered event of the same type and symbol, if any,
+    /// keeping its place in the buffer; otherwise discard the oldest event.
+    /// Only snapshots are replaced: tickers, book tickers and klines of the
+    /// same interval. Trades, diff depth and user data events are never
+    /// replaced, since each of them matters.
+    CoalescePerSymbol,
+}
+
+/// Consume websocket events from a bounded channel instead of a callback.
+///
+/// `spawn` connects and runs the event loop on a dedicated thread, so a slow
+/// consumer does not hold up the socket. Events are read from the returned
+/// `WebsocketEventReceiver`, along with the name of their stream.
+pub struct WebsocketChannel {
+    streams: Vec<String>,
+    config: Option<Config>,
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+    reconnect_policy: Option<ReconnectPolicy>,
+    heartbeat_policy: HeartbeatPolicy,
+}
+
+impl WebsocketChannel {
+    pub fn new<S>(streams: &[S]) -> WebsocketChannel
+    where
+        S: Clone + Into<String>,
+    {
+        WebsocketChannel {
+            streams: stream_names(streams),
+            config: None,
+            capacity: 1000,
+            overflow_policy: OverflowPolicy::Block,
+            reconnect_policy: None,
+            heartbeat_policy: HeartbeatPolicy::default(),
+        }
+    }
+
+    pub fn set_config(mut self, config: &Config) -> Self {
+        self.config = Some(config.clone());
+        self
+    }
+
+    /// Number of events buffered before the overflow policy applies (default 1000).
+    pub fn set_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = std::cmp::max(capacity, 1);
+        self
+    }
+
+    pub fn set_overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+
+    pub fn set_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Some(reconnect_policy);
+        self
+    }
+
+    /// Heartbeat of the connection, `HeartbeatPolicy::default()` unless set.
+    pub fn set_heartbeat_policy(mut self, heartbeat_policy: HeartbeatPolicy) -> Self {
+        self.heartbeat_policy = heartbeat_policy;
+        self
+    }
+
+    /// Connect and start reading on a new thread. Returns once connected.
+    pub fn spawn(self) -> Result<WebsocketEventReceiver> {
+        let (first, others) = match self.streams.split_first() {
+            Some((first, others)) => (first.clone(), others.to_vec()),
+            None => bail!("Not able to connect without a stream"),
+        };
+        let queue = Arc::new(EventQueue::new(self.capacity, self.overflow_policy));
+        let running = Arc::new(AtomicBool::new(true));
+        let (connected_tx, connected_rx) = mpsc::channel();
+
+        let reader_queue = queue.clone();
+        let reader_running = running.clone();
+        let reader = thread::Builder::new()
+            .name("binance-websocket".into())
+            .spawn(move || {
+                let producer = reader_queue.clone();
+                let mut web_socket =
+                    WebSockets::new_with_stream_names(move |message| producer.push(message))
+                        .set_heartbeat_policy(self.heartbeat_policy);
+                if let Some(reconnect_policy) = self.reconnect_policy {
+                    web_socket = web_socket.set_reconnect_policy(reconnect_policy);
+                }
+
+                // Further streams are subscribed on the connection, so that they
+                // work with a custom endpoint as well. The combined stream endpoint
+                // gives each event the name of its stream.
+                let connected = match &self.config {
+                    Some(config) => {
+                        web_socket.connect_multiple_streams_with_config(&[first], config)
+                    }
+                    None => web_socket.connect_multiple_streams(&[first]),
+                }
+                .and_then(|_| {
+                    if others.is_empty() {
+                        Ok(())
+                    } else {
+                        web_socket.subscribe(&others)
+                    }
+                });
+                let connect_error = connected.as_ref().err().map(|e| e.to_string());
+                let _ = connected_tx.send(connect_error);
+
+                let result = connected.and_then(|_| web_socket.event_loop(&reader_running));
+                reader_queue.close();
+                result
+            })?;
+
+        match connected_rx.recv() {
+            Ok(None) => Ok(WebsocketEventReceiver {
+                queue,
+                running,
+                reader: Some(reader),
+            }),
+            Ok(Some(e)) => bail!(e),
+            Err(_) => bail!("The websocket reader stopped before connecting"),
+        }
+    }
+}
+
+/// Receiving end of a `WebsocketChannel`, also usable as a blocking iterator
+/// which ends once the reader has stopped and the buffer is drained.
+pub struct WebsocketEventReceiver {
+    queue: Arc<EventQueue>,
+    running: Arc<AtomicBool>,
+    reader: Option<thread::JoinHandle<Result<()>>>,
+}
+
+impl WebsocketEventReceiver {
+    /// Wait for the next event, `None` once the reader has stopped.
+    pub fn recv(&self) -> Option<StreamMessage<WebsocketEvent>> {
+        self.queue.pop(None)
+    }
+
+    /// Wait at most `timeout` for the next event.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<StreamMessage<WebsocketEvent>> {
+        self.queue.pop(Some(timeout))
+    }
+
+    pub fn try_recv(&self) -> Option<StreamMessage<WebsocketEvent>> {
+        self.queue.pop(Some(Duration::from_secs(0)))
+    }
+
+    /// Number of events currently buffered.
+    pub fn len(&self) -> usize {
+        self.queue.state().messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of events discarded or replaced by the overflow policy.
+    pub fn dropped(&self) -> u64 {
+        self.queue.state().dropped
+    }
+
+    /// Ask the reader to stop; it exits within the read timeout of the heartbeat policy.
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Relaxed);
+        self.queue.close();
+    }
+
+    /// Stop the reader and wait for it, returning the error it stopped with.
+    pub fn join(mut self) -> Result<()> {
+        self.stop();
+        match self.reader.take().map(|reader| reader.join()) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => bail!("The websocket reader panicked"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Iterator for WebsocketEventReceiver {
+    type Item = StreamMessage<WebsocketEvent>;
+
+    fn next(&mut self) -> Option<StreamMessage<WebsocketEvent>> {
+        self.recv()
+    }
+}
+
+impl Drop for WebsocketEventReceiver {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// Binance limits a connection to 1024 streams
+const MAX_STREAMS_PER_CONNECTION: usize = 1024;
+// Streams per SUBSCRIBE / UNSUBSCRIBE request of a multiplexer connection
+const SUBSCRIPTION_BATCH_SIZE: usize = 200;
+// Binance accepts at most 5 incoming messages per second on a connection
+const CONTROL_MESSAGE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Spread a large set of streams over several connections, and consume their
+/// events from a single bounded channel.
+///
+/// Each connection carries at most `streams_per_connection` streams. They are
+/// subscribed on the connection in batches, paced to stay under the control
+/// message rate limit, rather than put into the URL. Streams can be added and
+/// removed while running through the returned `MultiplexedReceiver`, which
+/// keeps the connections balanced: new streams go to the least loaded
+/// connection, and removing streams merges connections which fit into the
+/// others.
+pub struct WebsocketMultiplexer {
+    streams: Vec<String>,
+    streams_per_connection: usize,
+    config: Option<Config>,
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+    reconnect_policy: Option<ReconnectPolicy>,
+    heartbeat_policy: HeartbeatPolicy,
+}
+
+impl WebsocketMultiplexer {
+    pub fn new<S>(streams: &[S]) -> WebsocketMultiplexer
+    where
+        S: Clone + Into<String>,
+    {
+        WebsocketMultiplexer {
+            streams: stream_names(streams),
+            streams_per_connection: MAX_STREAMS_PER_CONNECTION,
+            config: None,
+            capacity: 1000,
+            overflow_policy: OverflowPolicy::Block,
+            reconnect_policy: None,
+            heartbeat_policy: HeartbeatPolicy::default(),
         }
+    }
+
+    /// Most streams on one connection, between 1 and 1024 (default 1024).
+    pub fn set_streams_per_connection(mut self, streams_per_connection: usize) -> Self {
+        self.streams_per_connection = streams_per_connection.clamp(1, MAX_STREAMS_PER_CONNECTION);
+        self
+    }
+
+    pub fn set_config(mut self, config: &Config) -> Self {
+        self.config = Some(config.clone());
+        self
+    }
+
+    /// Number of events buffered before the overflow policy applies (default 1000).
+    pub fn set_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = std::cmp::max(capacity, 1);
+        self
+    }
+
+    pub fn set_overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+
+    /// Reconnect policy of each connection.
+    pub fn set_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Some(reconnect_policy);
+        self
+    }
+
+    /// Heartbeat of each connection, `HeartbeatPolicy::default()` unless set.
+    ///
+    /// Added and removed streams are picked up within its read timeout.
+    pub fn set_heartbeat_policy(mut self, heartbeat_policy: HeartbeatPolicy) -> Self {
+        self.heartbeat_policy = heartbeat_policy;
+        self
+    }
+
+    /// Open as many connections as the streams need, each read on its own
+    /// thread. Returns once all of them are connected and subscribed.
+    pub fn spawn(self) -> Result<MultiplexedReceiver> {
+        let receiver = MultiplexedReceiver {
+            context: ShardContext {
+                config: self.config,
+                reconnect_policy: self.reconnect_policy,
+                heartbeat_policy: self.heartbeat_policy,
+                queue: Arc::new(EventQueue::new(self.capacity, self.overflow_policy)),
+                running: Arc::new(AtomicBool::new(true)),
+            },
+            streams_per_connection: self.streams_per_connection,
+            shards: Mutex::new(Shards::default()),
+        };
+        receiver.add_streams(&self.streams)?;
+        Ok(receiver)
+    }
+}
+
+/// Receiving end of a `WebsocketMultiplexer`, with the events of all its
+/// connections. Also usable as a blocking iterator, which ends once the
+/// multiplexer was stopped or one of its connections failed.
+pub struct MultiplexedReceiver {
+    context: ShardContext,
+    streams_per_connection: usize,
+    shards: Mutex<Shards>,
+}
+
+impl MultiplexedReceiver {
+    /// Wait for the next event, `None` once stopped.
+    pub fn recv(&self) -> Option<StreamMessage<WebsocketEvent>> {
+        self.context.queue.pop(None)
+    }
 
-        if let Ok(events) = serde_json::from_value::<Events>(value) {
-            let action = match events {
-                Events::Vec(v) => WebsocketEvent::DayTickerAll(v),
-                Events::BookTickerEvent(v) => WebsocketEvent::BookTicker(v),
-                Events::BalanceUpdateEvent(v) => WebsocketEvent::BalanceUpdate(v),
-                Events::AccountUpdateEvent(v) => WebsocketEvent::AccountUpdate(v),
-                Events::OrderTradeEvent(v) => WebsocketEvent::OrderTrade(v),
-                Events::AggrTradesEvent(v) => WebsocketEvent::AggrTrades(v),
-                Events::TradeEvent(v) => WebsocketEvent::Trade(v),
-                Events::DayTickerEvent(v) => WebsocketEvent::DayTicker(v),
-                Events::KlineEvent(v) => WebsocketEvent::Kline(v),
-                Events::OrderBook(v) => WebsocketEvent::OrderBook(v),
-                Events::DepthOrderBookEvent(v) => WebsocketEvent::DepthOrderBook(v),
+    /// Wait at most `timeout` for the next event.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<StreamMessage<WebsocketEvent>> {
+        self.context.queue.pop(Some(timeout))
+    }
+
+    pub fn try_recv(&self) -> Option<StreamMessage<WebsocketEvent>> {
+        self.context.queue.pop(Some(Duration::from_secs(0)))
+    }
+
+    /// Number of events currently buffered.
+    pub fn len(&self) -> usize {
+        self.context.queue.state().messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of events discarded or replaced by the overflow policy.
+    pub fn dropped(&self) -> u64 {
+        self.context.queue.state().dropped
+    }
+
+    /// Streams of each open connection.
+    pub fn connections(&self) -> Vec<Vec<String>> {
+        self.shards()
+            .active
+            .iter()
+            .map(|shard| shard.streams.clone())
+            .collect()
+    }
+
+    /// Subscribe to more streams.
+    ///
+    /// Each new stream goes to the connection with the fewest streams which
+    /// still has room; new connections are opened for the streams left over.
+    /// Streams already subscribed are ignored.
+    pub fn add_streams<S>(&self, streams: &[S]) -> Result<()>
+    where
+        S: Clone + Into<String>,
+    {
+        let mut shards = self.shards();
+        let mut new_streams: Vec<String> = Vec::new();
+        for stream in stream_names(streams) {
+            if !shards.contains(&stream) && !new_streams.contains(&stream) {
+                new_streams.push(stream);
+            }
+        }
+
+        let unassigned = self.assign(&mut shards.active, new_streams, None);
+        for streams in spread(unassigned, self.streams_per_connection) {
+            let shard = self.context.spawn(streams)?;
+            shards.active.push(shard);
+        }
+        Ok(())
+    }
+
+    /// Unsubscribe from streams, then rebalance: connections left without a
+    /// stream are closed, and the streams of the least loaded connection are
+    /// moved to the others as long as they have room for all of them.
+    ///
+    /// A merged connection is only closed once the others have subscribed to
+    /// its streams, so no event is missed; events received meanwhile may come
+    /// twice.
+    pub fn remove_streams<S>(&self, streams: &[S]) -> Result<()>
+    where
+        S: Clone + Into<String>,
+    {
+        let removed_streams = stream_names(streams);
+        let mut shards = self.shards();
+        let mut active = Vec::new();
+        // Streams to unsubscribe from on each connection left
+        let mut removed: Vec<Vec<String>> = Vec::new();
+        for mut shard in std::mem::take(&mut shards.active) {
+            let (gone, kept) = shard
+                .streams
+                .into_iter()
+                .partition(|stream| removed_streams.contains(stream));
+            shard.streams = kept;
+            if shard.streams.is_empty() {
+                shard.control.retire();
+                shards.retired.push(shard);
+            } else {
+                removed.push(gone);
+                active.push(shard);
+            }
+        }
+
+        // Connections still taking over streams are not merged themselves
+        while active.len() > 1 {
+            let least_loaded = active
+                .iter()
+                .enumerate()
+                .filter(|(_, shard)| shard.control.takeovers.load(Ordering::SeqCst) == 0)
+                .min_by_key(|(_, shard)| shard.streams.len())
+                .map(|(index, shard)| (index, shard.streams.len()));
+            let (index, least_loaded) = match least_loaded {
+                Some(least_loaded) => least_loaded,
+                None => break,
             };
-            (self.handler)(action)?;
+            let room: usize = active
+                .iter()
+                .map(|shard| self.streams_per_connection - shard.streams.len())
+                .sum::<usize>()
+                - (self.streams_per_connection - least_loaded);
+            if least_loaded > room {
+                break;
+            }
+            let shard = active.remove(index);
+            removed.remove(index);
+            let handover = Arc::new(Handover {
+                from: shard.control.clone(),
+                pending: AtomicUsize::new(0),
+            });
+            self.assign(&mut active, shard.streams.clone(), Some(&handover));
+            shards.retired.push(shard);
         }
+
+        for (shard, removed) in active.iter().zip(removed) {
+            if !removed.is_empty() {
+                shard.control.send(ShardCommand::Unsubscribe(removed));
+            }
+        }
+        shards.active = active;
         Ok(())
     }
 
-    pub fn event_loop(&mut self, running: &AtomicBool) -> Result<()> {
-        while running.load(Ordering::Relaxed) {
-            if let Some(ref mut socket) = self.socket {
-                let message = socket.0.read_message()?;
-                match message {
-                    Message::Text(msg) => {
-                        if let Err(e) = self.handle_msg(&msg) {
-                            bail!(format!("Error on handling stream message: {}", e));
-                        }
+    // Give each stream to the connection with the fewest streams which still
+    // has room, returns the streams left over. The streams are taken over from
+    // the connection of `handover`, if any.
+    fn assign(
+        &self, active: &mut [Shard], streams: Vec<String>, handover: Option<&Arc<Handover>>,
+    ) -> Vec<String> {
+        let mut added: Vec<Vec<String>> = vec![Vec::new(); active.len()];
+        let mut unassigned = Vec::new();
+        for stream in streams {
+            let least_loaded = active
+                .iter()
+                .enumerate()
+                .filter(|(_, shard)| shard.streams.len() < self.streams_per_connection)
+                .min_by_key(|(_, shard)| shard.streams.len())
+                .map(|(index, _)| index);
+            match least_loaded {
+                Some(index) => {
+                    active[index].streams.push(stream.clone());
+                    added[index].push(stream);
+                }
+                None => unassigned.push(stream),
+            }
+        }
+
+        if let Some(handover) = handover {
+            let targets = added.iter().filter(|streams| !streams.is_empty()).count();
+            handover.pending.store(targets, Ordering::SeqCst);
+        }
+        for (shard, streams) in active.iter().zip(added) {
+            if streams.is_empty() {
+                continue;
+            }
+            let command = match handover {
+                Some(handover) => {
+                    shard.control.takeovers.fetch_add(1, Ordering::SeqCst);
+                    ShardCommand::TakeOver(streams, handover.clone())
+                }
+                None => ShardCommand::Subscribe(streams),
+            };
+            shard.control.send(command);
+        }
+        unassigned
+    }
+
+    /// Ask every connection to stop; they exit within the read timeout of the
+    /// heartbeat policy.
+    pub fn stop(&self) {
+        self.context.running.store(false, Ordering::SeqCst);
+        let shards = self.shards();
+        for shard in shards.active.iter().chain(&shards.retired) {
+            shard.control.retire();
+        }
+        self.context.queue.close();
+    }
+
+    /// Stop every connection and wait for them, returning the first error one
+    /// of them stopped with.
+    pub fn join(self) -> Result<()> {
+        self.stop();
+        let shards = std::mem::take(&mut *self.shards());
+        let readers = shards
+            .active
+            .into_iter()
+            .chain(shards.retired)
+            .map(|shard| shard.reader);
+        let mut result = Ok(());
+        for reader in readers {
+            let stopped = match reader.join() {
+                Ok(stopped) => stopped,
+                Err(_) => Err("A websocket reader panicked".into()),
+            };
+            if result.is_ok() {
+                result = stopped;
+            }
+        }
+        result
+    }
+
+    fn shards(&self) -> std::sync::MutexGuard<'_, Shards> {
+        self.shards.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Iterator for MultiplexedReceiver {
+    type Item = StreamMessage<WebsocketEvent>;
+
+    fn next(&mut self) -> Option<StreamMessage<WebsocketEvent>> {
+        self.recv()
+    }
+}
+
+impl Drop for MultiplexedReceiver {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// Split streams over as few connections as possible, evenly
+fn spread(streams: Vec<String>, streams_per_connection: usize) -> Vec<Vec<String>> {
+    let connections = streams.len().div_ceil(streams_per_connection);
+    let mut spread = vec![Vec::new(); connections];
+    for (index, stream) in streams.into_iter().enumerate() {
+        spread[index % connections].push(stream);
+    }
+    spread
+}
+
+#[derive(Default)]
+struct Shards {
+    active: Vec<Shard>,
+    // Connections closed, or being merged into others, joined along with the
+    // active ones
+    retired: Vec<Shard>,
+}
+
+impl Shards {
+    fn contains(&self, stream: &str) -> bool {
+        self.active
+            .iter()
+            .any(|shard| shard.streams.iter().any(|s| s == stream))
+    }
+}
+
+// One connection of a multiplexer, with the streams assigned to it
+struct Shard {
+    streams: Vec<String>,
+    control: Arc<ShardControl>,
+    reader: thread::JoinHandle<Result<()>>,
+}
+
+enum ShardCommand {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+    // Subscribe to streams moved from a connection being merged into others
+    TakeOver(Vec<String>, Arc<Handover>),
+}
+
+// A connection merged into others keeps reading until all of them have
+// subscribed to its streams, so that no event is missed
+struct Handover {
+    from: Arc<ShardControl>,
+    pending: AtomicUsize,
+}
+
+impl Handover {
+    fn subscribed(&self) {
+        if self.pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.from.retire();
+        }
+    }
+}
+
+struct ShardControl {
+    // Cleared to stop the reader, even while it is reconnecting
+    running: AtomicBool,
+    // Set to break out of the event loop, so that the reader picks up commands.
+    // A reconnect goes on, the commands are sent once connected again.
+    interrupted: AtomicBool,
+    commands: Mutex<Vec<ShardCommand>>,
+    // Number of `TakeOver` commands not subscribed yet
+    takeovers: AtomicUsize,
+}
+
+impl ShardControl {
+    fn send(&self, command: ShardCommand) {
+        self.commands
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(command);
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+
+    fn retire(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+
+    fn take_commands(&self) -> Vec<ShardCommand> {
+        std::mem::take(&mut *self.commands.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+// What the readers of a multiplexer share
+struct ShardContext {
+    config: Option<Config>,
+    reconnect_policy: Option<ReconnectPolicy>,
+    heartbeat_policy: HeartbeatPolicy,
+    queue: Arc<EventQueue>,
+    running: Arc<AtomicBool>,
+}
+
+impl ShardContext {
+    // Connect to the streams on a new thread, returns once subscribed
+    fn spawn(&self, streams: Vec<String>) -> Result<Shard> {
+        let (first, others) = match streams.split_first() {
+            Some((first, others)) => (first.clone(), others.to_vec()),
+            None => bail!("Not able to connect without a stream"),
+        };
+        let control = Arc::new(ShardControl {
+            running: AtomicBool::new(true),
+            interrupted: AtomicBool::new(false),
+            commands: Mutex::new(Vec::new()),
+            takeovers: AtomicUsize::new(0),
+        });
+        let (connected_tx, connected_rx) = mpsc::channel();
+
+        let config = self.config.clone();
+        let reconnect_policy = self.reconnect_policy.clone();
+        let heartbeat_policy = self.heartbeat_policy.clone();
+        let queue = self.queue.clone();
+        let running = self.running.clone();
+        let reader_control = control.clone();
+        let reader = thread::Builder::new()
+            .name("binance-websocket-shard".into())
+            .spawn(move || {
+                let producer = queue.clone();
+                let mut web_socket =
+                    WebSockets::new_with_stream_names(move |message| producer.push(message))
+                        .set_heartbeat_policy(heartbeat_policy);
+                if let Some(reconnect_policy) = reconnect_policy {
+                    web_socket = web_socket.set_reconnect_policy(reconnect_policy);
+                }
+
+                let mut last_request = None;
+                let connected = match &config {
+                    Some(config) => {
+                        web_socket.connect_multiple_streams_with_config(&[first], config)
                     }
-                    Message::Ping(_) | Message::Pong(_) | Message::Binary(_) => (),
-                    Message::Close(e) => bail!(ErrorKind::WebSocketDisconnected(e)),
+                    None => web_socket.connect_multiple_streams(&[first]),
+                }
+                .and_then(|_| {
+                    let subscribe = ShardCommand::Subscribe(others);
+                    send_paced(&mut web_socket, &subscribe, &mut last_request)
+                });
+                let connect_error = connected.as_ref().err().map(|e| e.to_string());
+                let _ = connected_tx.send(connect_error);
+
+                let result = connected.and_then(|_| {
+                    run_shard(
+                        &mut web_socket,
+                        &reader_control,
+                        &running,
+                        &mut last_request,
+                    )
+                });
+                if result.is_err() {
+                    // Ends the receiver, so that the consumer finds out
+                    queue.close();
+                }
+                let _ = web_socket.disconnect();
+                result
+            })?;
+
+        match connected_rx.recv() {
+            Ok(None) => Ok(Shard {
+                streams,
+                control,
+                reader,
+            }),
+            Ok(Some(e)) => bail!(e),
+            Err(_) => bail!("The websocket reader stopped before connecting"),
+        }
+    }
+}
+
+fn run_shard(
+    web_socket: &mut WebSockets, control: &ShardControl, running: &AtomicBool,
+    last_request: &mut Option<Instant>,
+) -> Result<()> {
+    loop {
+        control.interrupted.store(false, Ordering::SeqCst);
+        // Checked after the interruption is cleared, so that a stop in between
+        // is not missed
+        if !running.load(Ordering::SeqCst) || !control.running.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        for command in control.take_commands() {
+            send_paced(web_socket, &command, last_request)?;
+            if let ShardCommand::TakeOver(_, ref handover) = command {
+                control.takeovers.fetch_sub(1, Ordering::SeqCst);
+                handover.subscribed();
+            }
+        }
+        web_socket.interruptible_event_loop(&control.running, &control.interrupted)?;
+    }
+}
+
+// Send a command in batches, no faster than the control message rate limit
+fn send_paced(
+    web_socket: &mut WebSockets, command: &ShardCommand, last_request: &mut Option<Instant>,
+) -> Result<()> {
+    let streams = match command {
+        ShardCommand::Subscribe(streams)
+        | ShardCommand::Unsubscribe(streams)
+        | ShardCommand::TakeOver(streams, _) => streams,
+    };
+    for batch in streams.chunks(SUBSCRIPTION_BATCH_SIZE) {
+        if let Some(last_request) = *last_request {
+            let next_request = last_request + CONTROL_MESSAGE_INTERVAL;
+            let now = Instant::now();
+            if next_request > now {
+                thread::sleep(next_request - now);
+            }
+        }
+        *last_request = Some(Instant::now());
+        match command {
+            ShardCommand::Subscribe(_) | ShardCommand::TakeOver(..) => {
+                web_socket.subscribe(batch)?
+            }
+            ShardCommand::Unsubscribe(_) => web_socket.unsubscribe(batch)?,
+        }
+    }
+    Ok(())
+}
+
+// Event type, symbol and kline interval of an event which can be coalesced
+type CoalesceKey = (u8, String, String);
+
+struct EventQueueState {
+    messages: VecDeque<StreamMessage<WebsocketEvent>>,
+    // Sequence number of the first buffered message
+    head: u64,
+    // Sequence number of the latest buffered message of each key, with
+    // `OverflowPolicy::CoalescePerSymbol`
+    latest: HashMap<CoalesceKey, u64>,
+    dropped: u64,
+    closed: bool,
+}
+
+impl EventQueueState {
+    fn push_back(&mut self, message: StreamMessage<WebsocketEvent>, coalesce: bool) {
+        if coalesce {
+            if let Some(key) = coalesce_key(&message.event) {
+                let sequence = self.head + self.messages.len() as u64;
+                self.latest.insert(key, sequence);
+            }
+        }
+        self.messages.push_back(message);
+    }
+
+    fn pop_front(&mut self) -> Option<StreamMessage<WebsocketEvent>> {
+        let message = self.messages.pop_front()?;
+        if !self.latest.is_empty() {
+            if let Some(key) = coalesce_key(&message.event) {
+                if self.latest.get(&key) == Some(&self.head) {
+                    self.latest.remove(&key);
                 }
             }
         }
+        self.head += 1;
+        Some(message)
+    }
+
+    // Replace the latest buffered message with the same key where it is,
+    // otherwise give the message back
+    fn replace(
+        &mut self, message: StreamMessage<WebsocketEvent>,
+    ) -> std::result::Result<(), StreamMessage<WebsocketEvent>> {
+        let sequence = match coalesce_key(&message.event).and_then(|key| self.latest.get(&key)) {
+            Some(sequence) => *sequence,
+            None => return Err(message),
+        };
+        self.messages[(sequence - self.head) as usize] = message;
         Ok(())
     }
 }
+
+struct EventQueue {
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+    state: Mutex<EventQueueState>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl EventQueue {
+    fn new(capacity: usize, overflow_policy: OverflowPolicy) -> EventQueue {
+        EventQueue {
+            capacity,
+            overflow_policy,
+            state: Mutex::new(EventQueueState {
+                messages: VecDeque::with_capacity(capacity),
+                head: 0,
+                latest: HashMap::new(),
+                dropped: 0,
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, EventQueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Events are discarded once the receiver has stopped
+    fn push(&self, message: StreamMessage<WebsocketEvent>) -> Result<()> {
+        let mut state = self.state();
+        if state.closed {
+            return Ok(());
+        }
+
+        let coalesce = self.overflow_policy == OverflowPolicy::CoalescePerSymbol;
+        let mut message = message;
+        if coalesce && state.messages.len() >= self.capacity {
+            match state.replace(message) {
+                Ok(()) => {
+                    state.dropped += 1;
+                    return Ok(());
+                }
+                Err(unreplaced) => message = unreplaced,
+            }
+        }
+
+        while state.messages.len() >= self.capacity {
+            if self.overflow_policy != OverflowPolicy::Block {
+                state.pop_front();
+                state.dropped += 1;
+                break;
+            }
+            state = self.not_full.wait(state).unwrap_or_else(|e| e.into_inner());
+            if state.closed {
+                return Ok(());
+            }
+        }
+
+        state.push_back(message, coalesce);
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    fn pop(&self, timeout: Option<Duration>) -> Option<StreamMessage<WebsocketEvent>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut state = self.state();
+        loop {
+            if let Some(message) = state.pop_front() {
+                self.not_full.notify_one();
+                return Some(message);
+            }
+            if state.closed {
+                return None;
+            }
+            state = match deadline {
+                None => self
+                    .not_empty
+                    .wait(state)
+                    .unwrap_or_else(|e| e.into_inner()),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    self.not_empty
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+            };
+        }
+    }
+
+    // Buffered events are still delivered
+    fn close(&self) {
+        self.state().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+}
+
+// Only snapshot events have a key, each one supersedes the previous one
+fn coalesce_key(event: &WebsocketEvent) -> Option<CoalesceKey> {
+    let (kind, symbol, interval) = match event {
+        WebsocketEvent::DayTicker(event) => (0, &event.symbol, ""),
+        WebsocketEvent::Kline(event) => (1, &event.symbol, event.kline.interval.as_str()),
+        WebsocketEvent::BookTicker(event) => (2, &event.symbol, ""),
+        _ => return None,
+    };
+    Some((kind, symbol.clone(), interval.into()))
+}
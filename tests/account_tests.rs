@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison, clippy::let_unit_value)]
+
 use binance::api::*;
 use binance::config::*;
 use binance::account::*;
@@ -0,0 +1,75 @@
+#![cfg(feature = "async-websocket")]
+
+use binance::async_websockets::*;
+use binance::config::*;
+use binance::websockets::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    const BOOK_TICKER: &str = r#"{"u":400900217,"s":"BNBUSDT","b":"25.35190000","B":"31.21000000","a":"25.36520000","A":"40.66000000"}"#;
+
+    #[tokio::test]
+    async fn async_stream_and_live_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = Config::default()
+            .set_ws_endpoint(format!("ws://{}/ws", listener.local_addr().unwrap()));
+
+        // Send a ticker, then acknowledge every request and close after the first one
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            socket
+                .send(Message::Text(BOOK_TICKER.into()))
+                .await
+                .unwrap();
+
+            let request = match socket.next().await.unwrap().unwrap() {
+                Message::Text(msg) => serde_json::from_str::<Value>(&msg).unwrap(),
+                other => panic!("unexpected message {:?}", other),
+            };
+            let response = json!({ "result": null, "id": request["id"] });
+            socket
+                .send(Message::Text(response.to_string()))
+                .await
+                .unwrap();
+            socket.close(None).await.unwrap();
+            request
+        });
+
+        let web_socket = AsyncWebSockets::connect_with_config("bnbusdt@bookTicker", &config)
+            .await
+            .unwrap();
+        let (mut requests, mut events) = web_socket.split();
+
+        match events.next().await {
+            Some(Ok(WebsocketEvent::BookTicker(ticker))) => assert_eq!(ticker.symbol, "BNBUSDT"),
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        requests
+            .send(LiveRequest::subscribe(7, &["btcusdt@trade"]))
+            .await
+            .unwrap();
+        match events.next().await {
+            Some(Ok(WebsocketEvent::LiveResponse(response))) => {
+                assert_eq!(response.id, Some(7));
+                assert!(response.into_result().unwrap().is_null());
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        assert!(events.next().await.unwrap().is_err());
+        assert!(events.next().await.is_none());
+
+        assert_eq!(
+            server.await.unwrap(),
+            json!({ "method": "SUBSCRIBE", "params": ["btcusdt@trade"], "id": 7 })
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use binance::api::*;
+use binance::config::*;
+use binance::futures::account::ContractType;
+use binance::futures::coin::account::*;
+use binance::futures::coin::general::*;
+use binance::futures::coin::market::*;
+use binance::futures::coin::model::*;
+use binance::futures::coin::userstream::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binance::account::TimeInForce;
+    use float_cmp::*;
+    use mockito::{mock, Matcher};
+
+    fn config() -> Config {
+        Config::default()
+            .set_coin_futures_rest_api_endpoint(mockito::server_url())
+            .set_recv_window(1234)
+    }
+
+    #[test]
+    fn exchange_info() {
+        let mock_exchange_info = mock("GET", "/dapi/v1/exchangeInfo")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .with_body_from_file("tests/mocks/futures/coin/exchange_info.json")
+            .expect(2)
+            .create();
+
+        let general: CoinFuturesGeneral = Binance::new_with_config(None, None, &config());
+        let info = general.exchange_info().unwrap();
+        assert_eq!(info.symbols.len(), 2);
+
+        let symbol = general.get_symbol_info("ethusd_250328").unwrap();
+        mock_exchange_info.assert();
+
+        assert_eq!(symbol.pair, "ETHUSD");
+        assert_eq!(symbol.contract_type, "CURRENT_QUARTER");
+        assert_eq!(symbol.contract_size, 10);
+        assert_eq!(symbol.margin_asset, "ETH");
+        assert_eq!(info.symbols[0].contract_size, 100);
+        assert_eq!(info.symbols[0].filters.len(), 5);
+    }
+
+    #[test]
+    fn get_trades() {
+        let mock_trades = mock("GET", "/dapi/v1/trades")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("symbol=BTCUSD_PERP".into()))
+            .with_body_from_file("tests/mocks/futures/coin/trades.json")
+            .create();
+
+        let market: CoinFuturesMarket = Binance::new_with_config(None, None, &config());
+        let trades = market.get_trades("BTCUSD_PERP").unwrap();
+        mock_trades.assert();
+
+        assert_eq!(trades.len(), 1);
+        assert!(approx_eq!(f64, trades[0].qty, 1.0, ulps = 2));
+        assert!(approx_eq!(f64, trades[0].base_qty, 0.01037883, ulps = 2));
+        assert!(trades[0].is_buyer_maker);
+    }
+
+    #[test]
+    fn price_stats_of_symbol_and_pair() {
+        let mock_symbol = mock("GET", "/dapi/v1/ticker/24hr")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Exact("symbol=BTCUSD_PERP".into()))
+            .with_body_from_file("tests/mocks/futures/coin/ticker_24hr.json")
+            .create();
+        let mock_pair = mock("GET", "/dapi/v1/ticker/24hr")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Exact("pair=BTCUSD".into()))
+            .with_body_from_file("tests/mocks/futures/coin/ticker_24hr.json")
+            .create();
+
+        let market: CoinFuturesMarket = Binance::new_with_config(None, None, &config());
+        // A single symbol is answered with a list as well
+        let stats = market.get_24h_price_stats("BTCUSD_PERP").unwrap();
+        let pair_stats = market.get_24h_price_stats_for_pair("BTCUSD").unwrap();
+        mock_symbol.assert();
+        mock_pair.assert();
+
+        assert_eq!(stats.symbol, "BTCUSD_PERP");
+        assert!(approx_eq!(f64, stats.volume, 5432198.0, ulps = 2));
+        assert!(approx_eq!(f64, stats.base_volume, 8446.03216531, ulps = 2));
+        let symbols: Vec<_> = pair_stats
+            .iter()
+            .map(|stats| stats.symbol.as_str())
+            .collect();
+        assert_eq!(symbols, vec!["BTCUSD_PERP", "BTCUSD_240927"]);
+    }
+
+    #[test]
+    fn mark_prices_for_pair() {
+        let mock_premium_index = mock("GET", "/dapi/v1/premiumIndex")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Exact("pair=BTCUSD".into()))
+            .with_body_from_file("tests/mocks/futures/coin/premium_index.json")
+            .create();
+
+        let market: CoinFuturesMarket = Binance::new_with_config(None, None, &config());
+        let prices = market.get_mark_prices_for_pair("BTCUSD").unwrap();
+        mock_premium_index.assert();
+
+        assert_eq!(prices.len(), 2);
+        assert!(approx_eq!(
+            f64,
+            prices[0].mark_price,
+            64104.61023751,
+            ulps = 2
+        ));
+        assert_eq!(prices[0].last_funding_rate, "0.00010000");
+        // Delivery contracts have no funding
+        assert_eq!(prices[1].last_funding_rate, "");
+        assert_eq!(prices[1].next_funding_time, 0);
+    }
+
+    #[test]
+    fn open_interest_statistics() {
+        let mock_open_interest_statistics = mock("GET", "/futures/data/openInterestHist")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex(
+                "contractType=CURRENT_QUARTER&limit=10&pair=BTCUSD&period=5m".into(),
+            ))
+            .with_body_from_file("tests/mocks/futures/coin/open_interest_statistics.json")
+            .create();
+
+        let market: CoinFuturesMarket = Binance::new_with_config(None, None, &config());
+        let hists = market
+            .open_interest_statistics("BTCUSD", ContractType::CurrentQuarter, "5m", 10, None, None)
+            .unwrap();
+        mock_open_interest_statistics.assert();
+
+        assert_eq!(
+            hists,
+            vec![OpenInterestHist {
+                pair: "BTCUSD".into(),
+                contract_type: "CURRENT_QUARTER".into(),
+                sum_open_interest: "20403".into(),
+                sum_open_interest_value: "176196512.23400000".into(),
+                timestamp: 1584089700000,
+            }]
+        );
+    }
+
+    #[test]
+    fn limit_buy_contracts() {
+        let mock_limit_buy = mock("POST", "/dapi/v1/order")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex(
+                "price=60000&quantity=10&recvWindow=1234&side=BUY&symbol=BTCUSD_PERP&timeInForce=GTC&timestamp=\\d+&type=LIMIT&signature=.*"
+                    .into(),
+            ))
+            .with_body_from_file("tests/mocks/futures/coin/limit_buy.json")
+            .create();
+
+        let account: CoinFuturesAccount = Binance::new_with_config(None, None, &config());
+        let order = account
+            .limit_buy("BTCUSD_PERP", 10, 60000.0, TimeInForce::GTC)
+            .unwrap();
+        mock_limit_buy.assert();
+
+        assert_eq!(order.order_id, 22542179);
+        assert_eq!(order.pair, "BTCUSD");
+        assert_eq!(order.cum_qty, Some(0.0));
+        assert!(approx_eq!(f64, order.orig_qty, 10.0, ulps = 2));
+        assert_eq!(order.time, None);
+    }
+
+    #[test]
+    fn get_order() {
+        let mock_get_order = mock("GET", "/dapi/v1/order")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex(
+                "orderId=1917641&recvWindow=1234&symbol=BTCUSD_200925&timestamp=\\d+&signature=.*"
+                    .into(),
+            ))
+            .with_body_from_file("tests/mocks/futures/coin/get_order.json")
+            .create();
+
+        let account: CoinFuturesAccount = Binance::new_with_config(None, None, &config());
+        let order = account.get_order("BTCUSD_200925", 1917641).unwrap();
+        mock_get_order.assert();
+
+        assert_eq!(order.order_type, "TRAILING_STOP_MARKET");
+        assert_eq!(order.cum_qty, None);
+        assert_eq!(order.activate_price, Some(9020.0));
+        assert_eq!(order.time, Some(1579276756075));
+    }
+
+    #[test]
+    fn position_information_of_pair() {
+        let mock_position_risk = mock("GET", "/dapi/v1/positionRisk")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex(
+                "pair=BTCUSD&recvWindow=1234&timestamp=\\d+&signature=.*".into(),
+            ))
+            .with_body_from_file("tests/mocks/futures/coin/position_risk.json")
+            .create();
+
+        let account: CoinFuturesAccount = Binance::new_with_config(None, None, &config());
+        let positions = account.position_information("BTCUSD").unwrap();
+        mock_position_risk.assert();
+
+        assert_eq!(positions.len(), 1);
+        let position = &positions[0];
+        assert!(approx_eq!(f64, position.position_amount, -12.0, ulps = 2));
+        assert!(approx_eq!(f64, position.max_qty, 250.0, ulps = 2));
+        assert!(approx_eq!(
+            f64,
+            position.notional_value,
+            -0.01871939,
+            ulps = 2
+        ));
+        assert!(!position.is_auto_add_margin);
+    }
+
+    #[test]
+    fn account_balance() {
+        let mock_balance = mock("GET", "/dapi/v1/balance")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex(
+                "recvWindow=1234&timestamp=\\d+&signature=.*".into(),
+            ))
+            .with_body_from_file("tests/mocks/futures/coin/balance.json")
+            .create();
+
+        let account: CoinFuturesAccount = Binance::new_with_config(None, None, &config());
+        let balances = account.account_balance().unwrap();
+        mock_balance.assert();
+
+        assert_eq!(balances[0].asset, "BTC");
+        assert!(approx_eq!(
+            f64,
+            balances[0].withdraw_available,
+            0.0025,
+            ulps = 2
+        ));
+    }
+
+    #[test]
+    fn change_initial_leverage() {
+        let mock_change_leverage = mock("POST", "/dapi/v1/leverage")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex(
+                "leverage=21&recvWindow=1234&symbol=BTCUSD_200925&timestamp=\\d+&signature=.*"
+                    .into(),
+            ))
+            .with_body_from_file("tests/mocks/futures/coin/change_initial_leverage.json")
+            .create();
+
+        let account: CoinFuturesAccount = Binance::new_with_config(None, None, &config());
+        let response = account
+            .change_initial_leverage("BTCUSD_200925", 21)
+            .unwrap();
+        mock_change_leverage.assert();
+
+        assert_eq!(response.leverage, 21);
+        assert!(approx_eq!(f64, response.max_qty, 1000.0, ulps = 2));
+    }
+
+    #[test]
+    fn user_stream() {
+        let mock_start = mock("POST", "/dapi/v1/listenKey")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .with_body(r#"{"listenKey":"coin"}"#)
+            .create();
+        let mock_keep_alive = mock("PUT", "/dapi/v1/listenKey")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_body(Matcher::Exact("listenKey=coin".into()))
+            .with_body("{}")
+            .create();
+
+        let user_stream: CoinFuturesUserStream = Binance::new_with_config(None, None, &config());
+        let listen_key = user_stream.start().unwrap().listen_key;
+        user_stream.keep_alive(&listen_key).unwrap();
+        mock_start.assert();
+        mock_keep_alive.assert();
+
+        assert_eq!(listen_key, "coin");
+    }
+}
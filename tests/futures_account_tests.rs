@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison, clippy::useless_conversion)]
+
 use binance::api::*;
 use binance::config::*;
 use binance::futures::account::*;
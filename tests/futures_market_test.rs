@@ -1,12 +1,15 @@
 use binance::api::*;
 use binance::config::*;
 use binance::futures::market::FuturesMarket;
+use binance::futures::account::ContractType;
 use binance::futures::model::OpenInterestHist;
+use binance::market::{KlineGap, KlineInterval};
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use mockito::{mock, Matcher};
+    use std::time::Duration;
 
     #[test]
     fn open_interest_statistics() {
@@ -41,4 +44,79 @@ mod tests {
 
         assert_eq!(open_interest_hists, expectation)
     }
+
+    #[test]
+    fn continuous_klines_history() {
+        let mock_page_1 = mock("GET", "/fapi/v1/continuousKlines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex(
+                "contractType=PERPETUAL&endTime=1609459379999&interval=1m&limit=1500&pair=BTCUSDT&startTime=1609459200000"
+                    .into(),
+            ))
+            .with_body_from_file("tests/mocks/futures/market/continuous_klines.json")
+            .create();
+        // A page without new klines ends the download
+        let mock_page_2 = mock("GET", "/fapi/v1/continuousKlines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("startTime=1609459260001".into()))
+            .with_body("[]")
+            .create();
+
+        let config = Config::default().set_futures_rest_api_endpoint(mockito::server_url());
+        let market: FuturesMarket = Binance::new_with_config(None, None, &config);
+
+        let mut history = market
+            .get_continuous_klines_history(
+                "BTCUSDT",
+                ContractType::Perpetual,
+                KlineInterval::OneMinute,
+                1609459200000,
+                1609459380000,
+            )
+            .set_limit(1500)
+            .set_pause(Duration::from_millis(0));
+        let klines = history
+            .by_ref()
+            .collect::<binance::errors::Result<Vec<_>>>()
+            .unwrap();
+        mock_page_1.assert();
+        mock_page_2.assert();
+
+        assert_eq!(klines.len(), 2);
+        assert_eq!(klines[0].open_time, 1609459200000);
+        assert_eq!(klines[1].close_time, 1609459319999);
+        assert_eq!(
+            history.gaps(),
+            &[KlineGap {
+                from: 1609459320000,
+                to: 1609459380000
+            }]
+        );
+    }
+
+    #[test]
+    fn one_second_klines_history_is_rejected() {
+        let mock_klines = mock("GET", "/fapi/v1/klines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Any)
+            .with_body("[]")
+            .expect(0)
+            .create();
+
+        let config = Config::default().set_futures_rest_api_endpoint(mockito::server_url());
+        let market: FuturesMarket = Binance::new_with_config(None, None, &config);
+
+        let results: Vec<_> = market
+            .get_klines_history(
+                "BTCUSDT",
+                KlineInterval::OneSecond,
+                1609459200000,
+                1609459380000,
+            )
+            .collect();
+        mock_klines.assert();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
 }
@@ -0,0 +1,77 @@
+use binance::api::*;
+use binance::config::*;
+use binance::futures::coin::market::CoinFuturesMarket;
+use binance::futures::market::FuturesMarket;
+use binance::futures::orderbook::*;
+use binance::model::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{mock, Matcher};
+
+    fn depth_events() -> Vec<DepthOrderBookEvent> {
+        std::fs::read_to_string("tests/mocks/futures/orderbook/depth_events.jsonl")
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn futures_order_book_sync_and_resync() {
+        let mock_get_depth = mock("GET", "/fapi/v1/depth")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("limit=500&symbol=BTCUSDT".into()))
+            .with_body_from_file("tests/mocks/futures/orderbook/depth_snapshot.json")
+            .expect(2)
+            .create();
+
+        let config = Config::default().set_futures_rest_api_endpoint(mockito::server_url());
+        let market: FuturesMarket = Binance::new_with_config(None, None, &config);
+        let mut manager = FuturesOrderBookManager::new(market, "btcusdt").set_snapshot_depth(500);
+
+        let events = depth_events();
+        for event in &events[..3] {
+            manager.update(event).unwrap();
+        }
+
+        let book = manager.book().unwrap();
+        assert_eq!(book.last_update_id(), 118);
+        assert_eq!(
+            book.bids(),
+            vec![PriceLevel::new(3.995, 2.5), PriceLevel::new(3.99, 9.0)]
+        );
+        assert_eq!(book.asks(), vec![PriceLevel::new(4.000002, 6.0)]);
+
+        // pu 120 does not match the previous u 118
+        manager.update(&events[3]).unwrap();
+        mock_get_depth.assert();
+
+        assert!(manager.book().is_none());
+        assert_eq!(manager.resyncs(), 1);
+    }
+
+    #[test]
+    fn coin_futures_order_book_sync() {
+        let mock_get_depth = mock("GET", "/dapi/v1/depth")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("limit=1000&symbol=BTCUSD_PERP".into()))
+            .with_body_from_file("tests/mocks/futures/orderbook/depth_snapshot.json")
+            .create();
+
+        let config = Config::default().set_coin_futures_rest_api_endpoint(mockito::server_url());
+        let market: CoinFuturesMarket = Binance::new_with_config(None, None, &config);
+        let mut manager = FuturesOrderBookManager::new(market, "btcusd_perp");
+
+        for mut event in depth_events().into_iter().take(3) {
+            event.symbol = "BTCUSD_PERP".into();
+            manager.update(&event).unwrap();
+        }
+        mock_get_depth.assert();
+
+        let book = manager.book().unwrap();
+        assert_eq!(book.last_update_id(), 118);
+        assert_eq!(book.asks(), vec![PriceLevel::new(4.000002, 6.0)]);
+    }
+}
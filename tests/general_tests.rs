@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison)]
+
 use binance::api::*;
 use binance::config::*;
 use binance::general::*;
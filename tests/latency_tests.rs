@@ -0,0 +1,158 @@
+#![allow(clippy::result_large_err)]
+
+use binance::capture::*;
+use binance::futures::websockets::*;
+use binance::latency::*;
+use binance::streams::*;
+use binance::websockets::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const SPOT_EVENTS: &str = include_str!("mocks/websockets/spot_events.jsonl");
+
+    fn now_micros() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64
+    }
+
+    #[test]
+    fn histogram() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.percentile(50.0), None);
+        assert_eq!(histogram.mean(), None);
+
+        for latency in &[-300, 800, 900, 4_000, 30_000_000] {
+            histogram.record(*latency);
+        }
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.min(), Some(-300));
+        assert_eq!(histogram.max(), Some(30_000_000));
+        assert_eq!(histogram.mean(), Some(6_001_080));
+        assert_eq!(histogram.percentile(0.0), Some(250));
+        assert_eq!(histogram.percentile(50.0), Some(1_000));
+        assert_eq!(histogram.percentile(80.0), Some(5_000));
+        assert_eq!(histogram.percentile(100.0), Some(30_000_000));
+
+        let buckets = histogram.buckets();
+        assert_eq!(buckets[0], (Some(250), 1));
+        assert_eq!(buckets[2], (Some(1_000), 2));
+        assert_eq!(buckets.last(), Some(&(None, 1)));
+        assert_eq!(buckets.iter().map(|(_, count)| count).sum::<u64>(), 5);
+    }
+
+    #[test]
+    fn monitor_per_stream() {
+        let monitor = LatencyMonitor::new();
+        let watcher = monitor.clone();
+        monitor.record("bnbbtc@trade", 1_000);
+        monitor.record("bnbbtc@trade", 3_000);
+        monitor.record("ethbtc@trade", 2_000);
+
+        assert_eq!(
+            watcher.histogram("bnbbtc@trade").unwrap().mean(),
+            Some(2_000)
+        );
+        assert!(watcher.histogram("xrpbtc@trade").is_none());
+        let histograms = watcher.take_histograms();
+        assert_eq!(
+            histograms.keys().collect::<Vec<_>>(),
+            vec!["bnbbtc@trade", "ethbtc@trade"]
+        );
+        assert!(watcher.histograms().is_empty());
+    }
+
+    #[test]
+    fn received_events_carry_their_latency() {
+        let monitor = LatencyMonitor::new();
+        let mut messages = Vec::new();
+        let started = now_micros();
+        {
+            let mut web_socket =
+                WebSockets::new_with_stream_names(|message: StreamMessage<WebsocketEvent>| {
+                    messages.push(message);
+                    Ok(())
+                })
+                .set_latency_monitor(monitor.clone());
+            // Combined trade, book ticker without event time, array of tickers
+            for index in &[11, 7, 13] {
+                let msg = SPOT_EVENTS.lines().nth(*index).unwrap();
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+        }
+
+        let trade = &messages[0];
+        assert_eq!(trade.event_time, Some(123456789));
+        assert!(trade.received_at.unwrap() >= started);
+        assert_eq!(
+            trade.latency(),
+            Some(trade.received_at.unwrap() as i64 - 123_456_789_000)
+        );
+        assert_eq!(messages[1].event_time, None);
+        assert_eq!(messages[1].latency(), None);
+        assert_eq!(messages[2].event_time, Some(123456789));
+
+        // Only the combined stream message tells its stream
+        let histograms = monitor.histograms();
+        assert_eq!(histograms.len(), 1);
+        assert_eq!(
+            histograms["bnbbtc@trade"].max(),
+            Some(trade.latency().unwrap())
+        );
+    }
+
+    #[test]
+    fn replayed_events_keep_their_receive_time() {
+        let path =
+            std::env::temp_dir().join(format!("binance-latency-{}.jsonl", std::process::id()));
+        let frames = [
+            // Transaction time only
+            (
+                1_568_014_460_893_500,
+                r#"{"stream":"bnbusdt@depth5","data":{"e":"depthUpdate","T":1568014460891,"lastUpdateId":160,"bids":[],"asks":[]}}"#,
+            ),
+            (
+                1_568_014_460_894_250,
+                r#"{"stream":"bnbusdt@bookTicker","data":{"e":"bookTicker","u":400900217,"E":1568014460893,"T":1568014460891,"s":"BNBUSDT","b":"25.35190000","B":"31.21000000","a":"25.36520000","A":"40.66000000"}}"#,
+            ),
+        ];
+        let capture: String = frames
+            .iter()
+            .map(|(received_at, message)| {
+                let frame = CapturedFrame {
+                    received_at: *received_at,
+                    message: message.to_string(),
+                };
+                format!("{}\n", serde_json::to_string(&frame).unwrap())
+            })
+            .collect();
+        std::fs::write(&path, capture).unwrap();
+
+        let monitor = LatencyMonitor::new();
+        let mut latencies = Vec::new();
+        {
+            let mut web_socket = FuturesWebSockets::new_with_stream_names(
+                |message: StreamMessage<FuturesWebsocketEvent>| {
+                    latencies.push(message.latency());
+                    Ok(())
+                },
+            )
+            .set_latency_monitor(monitor.clone());
+            web_socket
+                .replay(&path, ReplaySpeed::AsFastAsPossible)
+                .unwrap();
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(latencies, vec![Some(2_500), Some(1_250)]);
+        assert_eq!(
+            monitor.histogram("bnbusdt@bookTicker").unwrap().min(),
+            Some(1_250)
+        );
+        assert_eq!(monitor.histogram("bnbusdt@depth5").unwrap().count(), 1);
+    }
+}
@@ -8,6 +8,7 @@ mod tests {
     use super::*;
     use mockito::{mock, Matcher};
     use float_cmp::*;
+    use std::time::Duration;
 
     #[test]
     fn get_depth() {
@@ -332,4 +333,381 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn get_klines_history() {
+        let mock_page_1 = mock("GET", "/api/v3/klines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex(
+                "endTime=1609459499999&interval=1m&limit=2&startTime=1609459200000&symbol=BTCUSDT"
+                    .into(),
+            ))
+            .with_body_from_file("tests/mocks/market/get_klines_history_page1.json")
+            .create();
+        let mock_page_2 = mock("GET", "/api/v3/klines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("startTime=1609459260001".into()))
+            .with_body_from_file("tests/mocks/market/get_klines_history_page2.json")
+            .create();
+        let mock_page_3 = mock("GET", "/api/v3/klines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("startTime=1609459440001".into()))
+            .with_body("[]")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+
+        let mut history = market
+            .get_klines_history(
+                "BTCUSDT",
+                KlineInterval::OneMinute,
+                1609459200000,
+                1609459500000,
+            )
+            .set_limit(2)
+            .set_pause(Duration::from_millis(0));
+
+        let open_times: Vec<i64> = history
+            .by_ref()
+            .map(|kline| kline.unwrap().open_time)
+            .collect();
+        mock_page_1.assert();
+        mock_page_2.assert();
+        mock_page_3.assert();
+
+        assert_eq!(open_times, vec![1609459200000, 1609459260000, 1609459440000]);
+        assert_eq!(
+            history.gaps(),
+            &[KlineGap {
+                from: 1609459320000,
+                to: 1609459440000
+            }]
+        );
+    }
+
+    #[test]
+    fn get_klines_history_gaps_at_range_ends() {
+        let mock_klines = mock("GET", "/api/v3/klines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("startTime=1609459200000".into()))
+            .with_body(
+                r#"[[1609459320000, "29000.00", "29010.00", "28990.00", "29005.00", "12.5", 1609459379999, "362500.0", 100, "6.2", "179800.0", "0"]]"#,
+            )
+            .create();
+        let mock_end = mock("GET", "/api/v3/klines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("startTime=1609459320001".into()))
+            .with_body("[]")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+
+        let mut history = market
+            .get_klines_history(
+                "BTCUSDT",
+                KlineInterval::OneMinute,
+                1609459200000,
+                1609459440000,
+            )
+            .set_pause(Duration::from_millis(0));
+
+        assert_eq!(history.by_ref().count(), 1);
+        mock_klines.assert();
+        mock_end.assert();
+        assert_eq!(
+            history.gaps(),
+            &[
+                KlineGap {
+                    from: 1609459200000,
+                    to: 1609459320000
+                },
+                KlineGap {
+                    from: 1609459380000,
+                    to: 1609459440000
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn get_klines_history_after_short_page() {
+        // The limit is capped at 1000 on spot, and a page shorter than the
+        // limit does not end the download
+        const KLINE_1: &str = r#"[1609462800000, "29000.00", "29010.00", "28990.00", "29005.00", "12.5", 1609462859999, "362500.0", 100, "6.2", "179800.0", "0"]"#;
+        const KLINE_2: &str = r#"[1609462860000, "29000.00", "29010.00", "28990.00", "29005.00", "12.5", 1609462919999, "362500.0", 100, "6.2", "179800.0", "0"]"#;
+        const KLINE_3: &str = r#"[1609463040000, "29000.00", "29010.00", "28990.00", "29005.00", "12.5", 1609463099999, "362500.0", 100, "6.2", "179800.0", "0"]"#;
+        let mock_page_1 = mock("GET", "/api/v3/klines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("limit=1000&startTime=1609462800000".into()))
+            .with_body(format!("[{}, {}]", KLINE_1, KLINE_2))
+            .create();
+        let mock_page_2 = mock("GET", "/api/v3/klines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("limit=1000&startTime=1609462860001".into()))
+            .with_body(format!("[{}]", KLINE_3))
+            .create();
+        let mock_page_3 = mock("GET", "/api/v3/klines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("startTime=1609463040001".into()))
+            .with_body("[]")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+
+        let mut history = market
+            .get_klines_history(
+                "BTCUSDT",
+                KlineInterval::OneMinute,
+                1609462800000,
+                1609463100000,
+            )
+            .set_limit(1500)
+            .set_limit(0)
+            .set_pause(Duration::from_millis(0));
+
+        let open_times: Vec<i64> = history
+            .by_ref()
+            .map(|kline| kline.unwrap().open_time)
+            .collect();
+        mock_page_1.assert();
+        mock_page_2.assert();
+        mock_page_3.assert();
+
+        assert_eq!(open_times, vec![1609462800000, 1609462860000, 1609463040000]);
+        assert_eq!(
+            history.gaps(),
+            &[KlineGap {
+                from: 1609462920000,
+                to: 1609463040000
+            }]
+        );
+    }
+
+    #[test]
+    fn kline_interval() {
+        assert_eq!(String::from(KlineInterval::FifteenMinutes), "15m");
+        assert_eq!("1M".parse::<KlineInterval>(), Ok(KlineInterval::OneMonth));
+        assert_eq!(KlineInterval::OneHour.duration_ms(), Some(3_600_000));
+        assert_eq!(KlineInterval::OneMonth.duration_ms(), None);
+        assert!("2m".parse::<KlineInterval>().is_err());
+    }
+
+    #[test]
+    fn get_trades() {
+        let mock_get_trades = mock("GET", "/api/v3/trades")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("limit=2&symbol=LTCBTC".into()))
+            .with_body_from_file("tests/mocks/market/get_trades.json")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+
+        let trades = market.get_trades("LTCBTC", 2).unwrap();
+        mock_get_trades.assert();
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].id, 28457);
+        assert!(approx_eq!(f64, trades[0].price, 4.00000100, ulps = 2));
+        assert!(approx_eq!(f64, trades[0].qty, 12.0, ulps = 2));
+        assert!(approx_eq!(f64, trades[0].quote_qty, 48.000012, ulps = 2));
+        assert_eq!(trades[0].time, 1499865578047);
+        assert!(trades[0].is_buyer_maker);
+        assert!(trades[0].is_best_match);
+    }
+
+    #[test]
+    fn get_historical_trades() {
+        let mock_get_historical_trades = mock("GET", "/api/v3/historicalTrades")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_header("x-mbx-apikey", "api_key")
+            .match_query(Matcher::Regex("fromId=28457&limit=2&symbol=LTCBTC".into()))
+            .with_body_from_file("tests/mocks/market/get_trades.json")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(Some("api_key".into()), None, &config);
+
+        let trades = market.get_historical_trades("LTCBTC", 28457, 2).unwrap();
+        mock_get_historical_trades.assert();
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[1].id, 28458);
+    }
+
+    #[test]
+    fn get_trades_history_forward_with_default_limit() {
+        let mock_page_1 = mock("GET", "/api/v3/historicalTrades")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("fromId=2&limit=1000&symbol=ETHBTC".into()))
+            .with_body_from_file("tests/mocks/market/get_historical_trades_page1.json")
+            .create();
+        let mock_page_2 = mock("GET", "/api/v3/historicalTrades")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("fromId=4&limit=1000&symbol=ETHBTC".into()))
+            .with_body("[]")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(Some("api_key".into()), None, &config);
+
+        // A limit of 0 keeps the default instead of being sent to the API
+        let ids: Vec<u64> = market
+            .get_trades_history("ETHBTC", 2, TradeDirection::Forward)
+            .set_limit(0)
+            .set_pause(Duration::from_millis(0))
+            .map(|trade| trade.unwrap().id)
+            .collect();
+        mock_page_1.assert();
+        mock_page_2.assert();
+
+        assert_eq!(ids, vec![2, 3]);
+    }
+
+    #[test]
+    fn get_trades_history_backward() {
+        let mock_page_1 = mock("GET", "/api/v3/historicalTrades")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("fromId=2&limit=2&symbol=ETHBTC".into()))
+            .with_body_from_file("tests/mocks/market/get_historical_trades_page1.json")
+            .create();
+        let mock_page_2 = mock("GET", "/api/v3/historicalTrades")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("fromId=0&limit=2&symbol=ETHBTC".into()))
+            .with_body_from_file("tests/mocks/market/get_historical_trades_page2.json")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(Some("api_key".into()), None, &config);
+
+        let ids: Vec<u64> = market
+            .get_trades_history("ETHBTC", 3, TradeDirection::Backward)
+            .set_limit(2)
+            .set_pause(Duration::from_millis(0))
+            .map(|trade| trade.unwrap().id)
+            .collect();
+        mock_page_1.assert();
+        mock_page_2.assert();
+
+        assert_eq!(ids, vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn get_multiple_prices() {
+        let mock_get_multiple_prices = mock("GET", "/api/v3/ticker/price")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::UrlEncoded(
+                "symbols".into(),
+                r#"["LTCBTC","ETHBTC"]"#.into(),
+            ))
+            .with_body_from_file("tests/mocks/market/get_all_prices.json")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+
+        let prices = market.get_multiple_prices(vec!["LTCBTC", "ETHBTC"]).unwrap();
+        mock_get_multiple_prices.assert();
+
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[1].symbol, "ETHBTC");
+        assert!(approx_eq!(f64, prices[1].price, 0.07946600, ulps = 2));
+    }
+
+    #[test]
+    fn get_multiple_book_tickers() {
+        let mock_get_multiple_book_tickers = mock("GET", "/api/v3/ticker/bookTicker")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::UrlEncoded(
+                "symbols".into(),
+                r#"["LTCBTC","ETHBTC"]"#.into(),
+            ))
+            .with_body_from_file("tests/mocks/market/get_all_book_tickers.json")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+
+        let tickers = market
+            .get_multiple_book_tickers(vec!["LTCBTC", "ETHBTC"])
+            .unwrap();
+        mock_get_multiple_book_tickers.assert();
+
+        assert_eq!(tickers.len(), 2);
+        assert_eq!(tickers[0].symbol, "LTCBTC");
+        assert!(approx_eq!(f64, tickers[0].bid_qty, 431.0, ulps = 2));
+    }
+
+    #[test]
+    fn get_multiple_24h_mini_price_stats() {
+        let mock_get_mini_price_stats = mock("GET", "/api/v3/ticker/24hr")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("symbols".into(), r#"["BNBBTC"]"#.into()),
+                Matcher::UrlEncoded("type".into(), "MINI".into()),
+            ]))
+            .with_body_from_file("tests/mocks/market/get_24h_mini_price_stats.json")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+
+        let stats = market
+            .get_multiple_24h_mini_price_stats(vec!["BNBBTC"])
+            .unwrap();
+        mock_get_mini_price_stats.assert();
+
+        assert_eq!(stats[0].symbol, "BNBBTC");
+        assert!(approx_eq!(f64, stats[0].last_price, 4.00000200, ulps = 2));
+        assert!(approx_eq!(f64, stats[0].quote_volume, 15.3, ulps = 2));
+        assert_eq!(stats[0].count, 18151);
+    }
+
+    #[test]
+    fn get_rolling_window_price_stats() {
+        let mock_get_rolling_window = mock("GET", "/api/v3/ticker")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("symbols".into(), r#"["BNBBTC"]"#.into()),
+                Matcher::UrlEncoded("windowSize".into(), "4h".into()),
+            ]))
+            .with_body_from_file("tests/mocks/market/get_rolling_window_price_stats.json")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+
+        let stats = market
+            .get_rolling_window_price_stats(vec!["BNBBTC"], "4h")
+            .unwrap();
+        mock_get_rolling_window.assert();
+
+        assert_eq!(stats[0].symbol, "BNBBTC");
+        assert_eq!(stats[0].price_change_percent, "-88.000");
+        assert!(approx_eq!(f64, stats[0].open_price, 99.0, ulps = 2));
+        assert_eq!(stats[0].last_id, 18150);
+    }
+
+    #[test]
+    fn get_ui_klines() {
+        let mock_get_ui_klines = mock("GET", "/api/v3/uiKlines")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("interval=1d&symbol=LTCBTC".into()))
+            .with_body_from_file("tests/mocks/market/get_klines.json")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+
+        let KlineSummaries::AllKlineSummaries(klines) = market
+            .get_ui_klines("LTCBTC", KlineInterval::OneDay, None, None, None)
+            .unwrap();
+        mock_get_ui_klines.assert();
+
+        assert_eq!(klines[0].open_time, 1499040000000);
+        assert_eq!(klines[0].number_of_trades, 308);
+    }
 }
@@ -0,0 +1,151 @@
+use binance::api::*;
+use binance::config::*;
+use binance::market::*;
+use binance::model::*;
+use binance::orderbook::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{mock, Matcher};
+    use float_cmp::*;
+
+    fn depth_events(path: &str) -> Vec<DepthOrderBookEvent> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn order_book_sync() {
+        let mock_get_depth = mock("GET", "/api/v3/depth")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("limit=1000&symbol=BNBUSDT".into()))
+            .with_body_from_file("tests/mocks/orderbook/depth_snapshot.json")
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+        let mut manager = OrderBookManager::new(market, "bnbusdt");
+
+        for event in depth_events("tests/mocks/orderbook/depth_events.jsonl") {
+            manager.update(&event).unwrap();
+        }
+        mock_get_depth.assert();
+
+        assert!(manager.is_synced());
+        let book = manager.book().unwrap();
+        assert_eq!(book.last_update_id(), 115);
+        assert_eq!(
+            book.bids(),
+            vec![
+                PriceLevel::new(99.90, 0.7),
+                PriceLevel::new(99.80, 1.2),
+                PriceLevel::new(99.50, 2.0),
+                PriceLevel::new(99.00, 3.0),
+            ]
+        );
+        assert_eq!(
+            book.asks(),
+            vec![
+                PriceLevel::new(100.20, 0.3),
+                PriceLevel::new(100.50, 1.0),
+                PriceLevel::new(101.00, 2.5),
+            ]
+        );
+        assert_eq!(book.best_bid(), Some(PriceLevel::new(99.90, 0.7)));
+        assert_eq!(book.best_ask(), Some(PriceLevel::new(100.20, 0.3)));
+        assert_eq!(book.top_asks(2).len(), 2);
+        assert!(approx_eq!(f64, book.spread().unwrap(), 0.3, epsilon = 1e-9));
+        assert!(approx_eq!(
+            f64,
+            book.bid_qty_above(99.5),
+            3.9,
+            epsilon = 1e-9
+        ));
+        assert!(approx_eq!(
+            f64,
+            book.ask_qty_below(100.5),
+            1.3,
+            epsilon = 1e-9
+        ));
+    }
+
+    #[test]
+    fn order_book_resync_on_gap() {
+        let mock_get_depth = mock("GET", "/api/v3/depth")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("limit=100&symbol=BNBUSDT".into()))
+            .with_body_from_file("tests/mocks/orderbook/depth_snapshot.json")
+            .expect(2)
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+        let mut manager = OrderBookManager::new(market, "BNBUSDT").set_snapshot_depth(100);
+
+        let events = depth_events("tests/mocks/orderbook/depth_events_gap.jsonl");
+        manager.update(&events[0]).unwrap();
+        manager.update(&events[1]).unwrap();
+        assert_eq!(manager.book().unwrap().last_update_id(), 115);
+
+        // 116 to 119 are missing, the snapshot is older than the buffered event
+        manager.update(&events[2]).unwrap();
+        // Buffered without fetching again until the retry delay has passed
+        manager.update(&depth_event("BNBUSDT", 122, 123)).unwrap();
+        mock_get_depth.assert();
+
+        assert!(!manager.is_synced());
+        assert!(manager.book().is_none());
+        assert_eq!(manager.resyncs(), 1);
+    }
+
+    #[test]
+    fn order_book_resync_on_gap_while_bridging() {
+        let mock_get_depth = mock("GET", "/api/v3/depth")
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_query(Matcher::Regex("symbol=ETHUSDT".into()))
+            .with_body_from_file("tests/mocks/orderbook/depth_snapshot.json")
+            .expect(2)
+            .create();
+
+        let config = Config::default().set_rest_api_endpoint(mockito::server_url());
+        let market: Market = Binance::new_with_config(None, None, &config);
+        let mut manager = OrderBookManager::new(market, "ETHUSDT");
+
+        // The snapshot (110) is stale, both events stay buffered
+        manager.update(&depth_event("ETHUSDT", 120, 121)).unwrap();
+        manager.update(&depth_event("ETHUSDT", 125, 126)).unwrap();
+
+        // 122 to 124 are missing between the buffered events
+        let snapshot = OrderBook {
+            last_update_id: 120,
+            bids: vec![PriceLevel::new(99.0, 1.0)],
+            asks: vec![PriceLevel::new(101.0, 1.0)],
+        };
+        manager.sync(snapshot).unwrap();
+        assert!(!manager.is_synced());
+        assert_eq!(manager.resyncs(), 1);
+
+        // The next event fetches a new snapshot right away
+        manager.update(&depth_event("ETHUSDT", 127, 128)).unwrap();
+        mock_get_depth.assert();
+    }
+
+    fn depth_event(
+        symbol: &str, first_update_id: u64, final_update_id: u64,
+    ) -> DepthOrderBookEvent {
+        serde_json::from_value(serde_json::json!({
+            "e": "depthUpdate",
+            "E": 1672515782536u64,
+            "s": symbol,
+            "U": first_update_id,
+            "u": final_update_id,
+            "b": [],
+            "a": []
+        }))
+        .unwrap()
+    }
+}
@@ -0,0 +1,148 @@
+use binance::futures::account::ContractType;
+use binance::market::KlineInterval;
+use binance::streams::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_names() {
+        let streams = vec![
+            (Stream::Trade("BTCUSDT".into()), "btcusdt@trade"),
+            (Stream::AggTrade("btcusdt".into()), "btcusdt@aggTrade"),
+            (
+                Stream::Kline("ETHBTC".into(), KlineInterval::OneMinute),
+                "ethbtc@kline_1m",
+            ),
+            (
+                Stream::ContinuousKline(
+                    "BTCUSDT".into(),
+                    ContractType::Perpetual,
+                    KlineInterval::FiveMinutes,
+                ),
+                "btcusdt_perpetual@continuousKline_5m",
+            ),
+            (
+                Stream::MarkPriceKline("BTCUSDT".into(), KlineInterval::OneHour),
+                "btcusdt@markPriceKline_1h",
+            ),
+            (
+                Stream::MarkPrice("BTCUSDT".into(), Some(PriceSpeed::Sec1)),
+                "btcusdt@markPrice@1s",
+            ),
+            (Stream::AllMarkPrices(None), "!markPrice@arr"),
+            (
+                Stream::Depth {
+                    symbol: "ETHUSDT".into(),
+                    levels: Some(DepthLevels::Twenty),
+                    speed: Some(DepthSpeed::Ms100),
+                },
+                "ethusdt@depth20@100ms",
+            ),
+            (
+                Stream::Depth {
+                    symbol: "BNBBTC".into(),
+                    levels: None,
+                    speed: None,
+                },
+                "bnbbtc@depth",
+            ),
+            (Stream::BookTicker("BNBUSDT".into()), "bnbusdt@bookTicker"),
+            (Stream::AllBookTickers, "!bookTicker"),
+            (Stream::AllMiniTickers, "!miniTicker@arr"),
+            (Stream::AllTickers, "!ticker@arr"),
+            (
+                Stream::RollingWindowTicker("BNBUSDT".into(), RollingWindow::FourHours),
+                "bnbusdt@ticker_4h",
+            ),
+            (
+                Stream::AllRollingWindowTickers(RollingWindow::OneDay),
+                "!ticker_1d@arr",
+            ),
+            (Stream::ForceOrder("BTCUSDT".into()), "btcusdt@forceOrder"),
+            (Stream::AllForceOrders, "!forceOrder@arr"),
+            (
+                Stream::CompositeIndex("DEFIUSDT".into()),
+                "defiusdt@compositeIndex",
+            ),
+            (Stream::ContractInfo, "!contractInfo"),
+            (Stream::AssetIndex("ADAUSD".into()), "adausd@assetIndex"),
+            (Stream::AllAssetIndexes, "!assetIndex@arr"),
+        ];
+
+        for (stream, name) in streams {
+            assert_eq!(String::from(stream), name);
+        }
+    }
+
+    #[test]
+    fn parse_stream_names() {
+        let names = vec![
+            "btcusdt@trade",
+            "btcusdt@aggTrade",
+            "ethbtc@kline_1m",
+            "btcusdt_perpetual@continuousKline_5m",
+            "btcusdt@indexPriceKline_1h",
+            "btcusdt@markPriceKline_1h",
+            "bnbbtc@avgPrice",
+            "btcusdt@markPrice",
+            "btcusdt@markPrice@1s",
+            "!markPrice@arr@1s",
+            "btcusd@indexPrice",
+            "bnbbtc@depth",
+            "bnbbtc@depth@100ms",
+            "bnbbtc@depth10@500ms",
+            "bnbusdt@bookTicker",
+            "!bookTicker",
+            "bnbusdt@miniTicker",
+            "!miniTicker@arr",
+            "bnbusdt@ticker",
+            "!ticker@arr",
+            "bnbusdt@ticker_4h",
+            "!ticker_1d@arr",
+            "btcusdt@forceOrder",
+            "!forceOrder@arr",
+            "defiusdt@compositeIndex",
+            "!contractInfo",
+            "adausd@assetIndex",
+            "!assetIndex@arr",
+            "pqeGZ7Xuy0UnGXLLwFh7zM2cIFGNdiQw9b3XoWh0QchDlvbN0pGbRTGCfPxl",
+        ];
+        for name in names {
+            let stream: Stream = name.parse().unwrap();
+            assert_eq!(stream.to_string(), name);
+        }
+
+        assert_eq!(
+            "btcusdt_perpetual@continuousKline_5m".parse::<Stream>(),
+            Ok(Stream::ContinuousKline(
+                "btcusdt".into(),
+                ContractType::Perpetual,
+                KlineInterval::FiveMinutes
+            ))
+        );
+        assert_eq!(
+            "bnbbtc@depth10@500ms".parse::<Stream>(),
+            Ok(Stream::Depth {
+                symbol: "bnbbtc".into(),
+                levels: Some(DepthLevels::Ten),
+                speed: Some(DepthSpeed::Ms500),
+            })
+        );
+
+        for invalid in [
+            "",
+            "btcusdt@unknown",
+            "btcusdt@trade@1s",
+            "btcusdt@depth@1s",
+            "btcusdt@markPrice@100ms",
+            "!unknown@arr",
+            "a@b@c@d",
+        ]
+        .iter()
+        {
+            assert!(invalid.parse::<Stream>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,467 @@
+#![allow(clippy::result_large_err)]
+
+use binance::capture::*;
+use binance::errors::*;
+use binance::futures::websockets::*;
+use binance::streams::*;
+use binance::test_support::*;
+use binance::websockets::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    const SPOT_EVENTS: &str = include_str!("mocks/websockets/spot_events.jsonl");
+    const FUTURES_EVENTS: &str = include_str!("mocks/websockets/futures_events.jsonl");
+
+    fn spot_event(index: usize) -> String {
+        SPOT_EVENTS.lines().nth(index).unwrap().to_string()
+    }
+
+    fn fast_policy() -> ReconnectPolicy {
+        ReconnectPolicy::default()
+            .set_initial_backoff(Duration::from_millis(10))
+            .set_max_backoff(Duration::from_millis(50))
+            .set_max_attempts(3)
+    }
+
+    #[test]
+    fn raw_stream_with_ping_and_close() {
+        let server = TestServer::start(vec![vec![
+            ServerAction::Send(spot_event(0)),
+            ServerAction::Ping,
+            ServerAction::Sleep(Duration::from_millis(50)),
+            ServerAction::Send(spot_event(6)),
+            ServerAction::Close,
+        ]])
+        .unwrap();
+
+        let mut events = Vec::new();
+        let result = {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                events.push(event);
+                Ok(())
+            });
+            web_socket
+                .connect_with_config("bnbbtc@ticker", &server.config())
+                .unwrap();
+            web_socket.event_loop(&AtomicBool::new(true))
+        };
+
+        match result {
+            Err(Error(ErrorKind::WebSocketDisconnected(_), _)) => (),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(matches!(events[0], WebsocketEvent::DayTicker(_)));
+        assert!(matches!(events[1], WebsocketEvent::DepthOrderBook(_)));
+        let log = server.join();
+        assert_eq!(log.connections, vec!["/ws/bnbbtc@ticker"]);
+        assert_eq!(log.pongs, 1);
+    }
+
+    #[test]
+    fn combined_streams_and_live_requests() {
+        let server = TestServer::start(vec![vec![
+            ServerAction::Event {
+                stream: "bnbbtc@ticker".into(),
+                data: spot_event(0),
+            },
+            ServerAction::Sleep(Duration::from_millis(200)),
+            ServerAction::Close,
+        ]])
+        .unwrap();
+
+        let mut streams = Vec::new();
+        let subscriptions = {
+            let mut web_socket =
+                WebSockets::new_with_stream_names(|message: StreamMessage<WebsocketEvent>| {
+                    streams.push(message.stream);
+                    Ok(())
+                });
+            web_socket
+                .connect_multiple_streams_with_config(
+                    &["bnbbtc@ticker", "ethbtc@ticker"],
+                    &server.config(),
+                )
+                .unwrap();
+            web_socket.subscribe(&["bnbbtc@trade"]).unwrap();
+            web_socket.unsubscribe(&["ethbtc@ticker"]).unwrap();
+            let subscriptions = web_socket.list_subscriptions().unwrap();
+            let _ = web_socket.event_loop(&AtomicBool::new(true));
+            subscriptions
+        };
+
+        assert_eq!(subscriptions, vec!["bnbbtc@ticker", "bnbbtc@trade"]);
+        assert_eq!(streams, vec![Some("bnbbtc@ticker".to_string())]);
+        let log = server.join();
+        assert_eq!(
+            log.connections,
+            vec!["/stream?streams=bnbbtc@ticker/ethbtc@ticker"]
+        );
+        let methods: Vec<_> = log
+            .requests
+            .iter()
+            .map(|request| request["method"].clone())
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                json!("SUBSCRIBE"),
+                json!("UNSUBSCRIBE"),
+                json!("LIST_SUBSCRIPTIONS")
+            ]
+        );
+    }
+
+    #[test]
+    fn reconnect_after_disconnect() {
+        let server = TestServer::start(vec![
+            vec![ServerAction::Send(spot_event(0)), ServerAction::Disconnect],
+            vec![ServerAction::Send(spot_event(0)), ServerAction::Close],
+        ])
+        .unwrap();
+
+        let mut names = Vec::new();
+        {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                names.push(
+                    format!("{:?}", event)
+                        .split('(')
+                        .next()
+                        .unwrap()
+                        .to_string(),
+                );
+                Ok(())
+            })
+            .set_reconnect_policy(fast_policy());
+            web_socket
+                .connect_with_config("bnbbtc@ticker", &server.config())
+                .unwrap();
+            // Connections beyond the scripts are refused
+            assert!(web_socket.event_loop(&AtomicBool::new(true)).is_err());
+        }
+
+        assert_eq!(names, vec!["DayTicker", "Reconnected", "DayTicker"]);
+        assert_eq!(server.join().connections.len(), 2);
+    }
+
+    #[test]
+    fn serve_recorded_frames() {
+        let path =
+            std::env::temp_dir().join(format!("binance-test-server-{}.jsonl", std::process::id()));
+        let capture: String = FUTURES_EVENTS
+            .lines()
+            .enumerate()
+            .map(|(i, message)| {
+                let frame = CapturedFrame {
+                    received_at: 1_700_000_000_000_000 + i as u64 * 1_000,
+                    message: message.into(),
+                };
+                format!("{}\n", serde_json::to_string(&frame).unwrap())
+            })
+            .collect();
+        std::fs::write(&path, capture).unwrap();
+        let mut script = script_from_capture(&path, ReplaySpeed::Recorded).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        script.push(ServerAction::Close);
+        let server = TestServer::start(vec![script]).unwrap();
+
+        let mut events = 0;
+        {
+            let mut web_socket = FuturesWebSockets::new(|_event: FuturesWebsocketEvent| {
+                events += 1;
+                Ok(())
+            })
+            .set_heartbeat_policy(
+                HeartbeatPolicy::default().set_read_timeout(Duration::from_millis(20)),
+            );
+            web_socket
+                .connect_with_config(FuturesMarket::USDM, "bnbusdt@aggTrade", &server.config())
+                .unwrap();
+            assert!(web_socket.event_loop(&AtomicBool::new(true)).is_err());
+        }
+
+        assert_eq!(events, FUTURES_EVENTS.lines().count());
+        server.join();
+    }
+
+    #[test]
+    fn multiplexer_spreads_streams_over_connections() {
+        let script = |event: usize| vec![ServerAction::Send(spot_event(event))];
+        let server = TestServer::start(vec![script(0), script(3), script(4), script(6)]).unwrap();
+        let connections = |streams: &[&str]| -> Vec<String> {
+            streams.iter().map(|stream| stream.to_string()).collect()
+        };
+
+        let receiver =
+            WebsocketMultiplexer::new(&["a@trade", "b@trade", "c@trade", "d@trade", "e@trade"])
+                .set_streams_per_connection(2)
+                .set_config(&server.config())
+                .set_heartbeat_policy(
+                    HeartbeatPolicy::default().set_read_timeout(Duration::from_millis(20)),
+                )
+                .spawn()
+                .unwrap();
+        assert_eq!(
+            receiver.connections(),
+            vec![
+                connections(&["a@trade", "d@trade"]),
+                connections(&["b@trade", "e@trade"]),
+                connections(&["c@trade"]),
+            ]
+        );
+
+        receiver
+            .add_streams(&["a@trade", "f@trade", "g@trade", "h@trade"])
+            .unwrap();
+        receiver
+            .remove_streams(&["a@trade", "b@trade", "e@trade"])
+            .unwrap();
+        assert_eq!(
+            receiver.connections(),
+            vec![
+                connections(&["d@trade"]),
+                connections(&["c@trade", "f@trade"]),
+                connections(&["g@trade", "h@trade"]),
+            ]
+        );
+
+        // Events of all connections end up in the same receiver
+        let mut names: Vec<String> = (0..4)
+            .map(|_| {
+                let message = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+                format!("{:?}", message.event)
+                    .split('(')
+                    .next()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["AggrTrades", "DayTicker", "DepthOrderBook", "Trade"]
+        );
+
+        // Commands are sent by the readers, wait for them to come through
+        let expected = vec![
+            json!({"method": "SUBSCRIBE", "params": ["d@trade"]}),
+            json!({"method": "SUBSCRIBE", "params": ["e@trade"]}),
+            json!({"method": "SUBSCRIBE", "params": ["f@trade"]}),
+            json!({"method": "SUBSCRIBE", "params": ["h@trade"]}),
+            json!({"method": "UNSUBSCRIBE", "params": ["a@trade"]}),
+        ];
+        let requests = || -> Vec<_> {
+            let mut requests: Vec<_> = server
+                .log()
+                .requests
+                .iter()
+                .map(|request| json!({"method": request["method"], "params": request["params"]}))
+                .collect();
+            requests.sort_by_key(|request| request.to_string());
+            requests
+        };
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while requests() != expected && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(requests(), expected);
+
+        receiver.join().unwrap();
+        let log = server.join();
+        assert_eq!(
+            log.connections,
+            vec![
+                "/stream?streams=a@trade",
+                "/stream?streams=b@trade",
+                "/stream?streams=c@trade",
+                "/stream?streams=g@trade"
+            ]
+        );
+    }
+
+    #[test]
+    fn multiplexer_merges_connections_after_removal() {
+        let server = TestServer::start(vec![vec![], vec![]]).unwrap();
+        let connections = |streams: &[&str]| -> Vec<String> {
+            streams.iter().map(|stream| stream.to_string()).collect()
+        };
+
+        let receiver = WebsocketMultiplexer::new(&["a@trade", "b@trade", "c@trade", "d@trade"])
+            .set_streams_per_connection(2)
+            .set_config(&server.config())
+            .set_heartbeat_policy(
+                HeartbeatPolicy::default().set_read_timeout(Duration::from_millis(20)),
+            )
+            .spawn()
+            .unwrap();
+        assert_eq!(
+            receiver.connections(),
+            vec![
+                connections(&["a@trade", "c@trade"]),
+                connections(&["b@trade", "d@trade"]),
+            ]
+        );
+
+        // Each connection is left with one stream, which fit on one connection
+        receiver.remove_streams(&["a@trade", "b@trade"]).unwrap();
+        assert_eq!(
+            receiver.connections(),
+            vec![connections(&["d@trade", "c@trade"])]
+        );
+
+        // The first connection subscribed to c, the second one to d, then to c
+        // in place of b
+        let expected = vec![
+            json!({"method": "SUBSCRIBE", "params": ["c@trade"]}),
+            json!({"method": "SUBSCRIBE", "params": ["c@trade"]}),
+            json!({"method": "SUBSCRIBE", "params": ["d@trade"]}),
+            json!({"method": "UNSUBSCRIBE", "params": ["b@trade"]}),
+        ];
+        let requests = || -> Vec<_> {
+            let mut requests: Vec<_> = server
+                .log()
+                .requests
+                .iter()
+                .map(|request| json!({"method": request["method"], "params": request["params"]}))
+                .collect();
+            requests.sort_by_key(|request| request.to_string());
+            requests
+        };
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while requests() != expected && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(requests(), expected);
+
+        receiver.join().unwrap();
+        server.join();
+    }
+
+    #[test]
+    fn multiplexer_merge_keeps_events() {
+        // Both connections play the same trades of c, each one only sends
+        // them while subscribed to c
+        let trades: Vec<ServerAction> = (1..=100)
+            .flat_map(|id| {
+                vec![
+                    ServerAction::Event {
+                        stream: "c@trade".into(),
+                        data: json!({
+                            "e": "trade", "E": 1, "s": "C", "t": id, "p": "0.001", "q": "100",
+                            "b": 1, "a": 2, "T": 1, "m": true, "M": true
+                        })
+                        .to_string(),
+                    },
+                    ServerAction::Sleep(Duration::from_millis(5)),
+                ]
+            })
+            .collect();
+        let server = TestServer::start(vec![trades.clone(), trades]).unwrap();
+
+        let receiver = WebsocketMultiplexer::new(&["a@trade", "b@trade", "c@trade", "d@trade"])
+            .set_streams_per_connection(2)
+            .set_config(&server.config())
+            .set_heartbeat_policy(
+                HeartbeatPolicy::default().set_read_timeout(Duration::from_millis(50)),
+            )
+            .spawn()
+            .unwrap();
+        let trade_id = |message: StreamMessage<WebsocketEvent>| match message.event {
+            WebsocketEvent::Trade(trade) => trade.trade_id,
+            other => panic!("unexpected event {:?}", other),
+        };
+        let first = trade_id(receiver.recv_timeout(Duration::from_secs(5)).unwrap());
+
+        // c moves from the first connection to the second one
+        receiver.remove_streams(&["a@trade", "b@trade"]).unwrap();
+        assert_eq!(receiver.connections().len(), 1);
+
+        let mut received = vec![first];
+        while let Some(message) = receiver.recv_timeout(Duration::from_secs(5)) {
+            let id = trade_id(message);
+            received.push(id);
+            if id == 100 {
+                break;
+            }
+        }
+        // Trades received on both connections during the handover come twice
+        received.sort_unstable();
+        received.dedup();
+        assert_eq!(received, (first..=100).collect::<Vec<_>>());
+
+        receiver.join().unwrap();
+        server.join();
+    }
+
+    #[test]
+    fn multiplexer_fails_to_connect() {
+        let server = TestServer::start(vec![]).unwrap();
+        let config = server.config();
+        drop(server);
+
+        let result = WebsocketMultiplexer::new(&["bnbbtc@trade"])
+            .set_config(&config)
+            .spawn();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn futures_endpoint_per_market_in_a_thread() {
+        let server = TestServer::start(vec![
+            vec![
+                ServerAction::Send(FUTURES_EVENTS.lines().nth(6).unwrap().into()),
+                ServerAction::Close,
+            ],
+            vec![ServerAction::Close],
+        ])
+        .unwrap();
+        // Nothing listens on the USD-M endpoint
+        let config = server
+            .config()
+            .set_futures_ws_endpoint("ws://127.0.0.1:1/ws");
+
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = received.clone();
+        let mut web_socket = FuturesWebSockets::new(move |event: FuturesWebsocketEvent| {
+            assert!(matches!(event, FuturesWebsocketEvent::AggrTrades(_)));
+            counter.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        });
+        assert!(web_socket
+            .connect_with_config(FuturesMarket::USDM, "btcusd_perp@aggTrade", &config)
+            .is_err());
+        web_socket
+            .connect_with_config(FuturesMarket::COINM, "btcusd_perp@aggTrade", &config)
+            .unwrap();
+        let reader = thread::spawn(move || web_socket.event_loop(&AtomicBool::new(true)));
+        assert!(reader.join().unwrap().is_err());
+        assert_eq!(received.load(Ordering::Relaxed), 1);
+
+        {
+            let mut web_socket = FuturesWebSockets::new(|_event: FuturesWebsocketEvent| Ok(()));
+            web_socket
+                .connect_multiple_streams_with_config(
+                    FuturesMarket::Vanilla,
+                    &["btc-240628-70000-c@trade", "btc-240628-70000-p@trade"],
+                    &config,
+                )
+                .unwrap();
+            assert!(web_socket.event_loop(&AtomicBool::new(true)).is_err());
+        }
+
+        let log = server.join();
+        assert_eq!(
+            log.connections,
+            vec![
+                "/ws/btcusd_perp@aggTrade",
+                "/stream?streams=btc-240628-70000-c@trade/btc-240628-70000-p@trade"
+            ]
+        );
+    }
+}
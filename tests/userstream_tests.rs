@@ -0,0 +1,303 @@
+#![allow(clippy::result_large_err)]
+
+use binance::config::*;
+use binance::userstream::*;
+use binance::websockets::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{mock, Matcher};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use tungstenite::handshake::server::{Request, Response};
+    use tungstenite::Message;
+
+    const USER_DATA_EVENTS: &str = include_str!("mocks/websockets/user_data_events.jsonl");
+
+    // Accept a client for each entry of `messages`, send it the messages and
+    // wait until it goes away. Returns the requested paths.
+    fn serve(listener: TcpListener, messages: Vec<Vec<String>>) -> thread::JoinHandle<Vec<String>> {
+        thread::spawn(move || {
+            let mut paths = Vec::new();
+            for connection in messages {
+                let (stream, _) = listener.accept().unwrap();
+                let mut path = String::new();
+                let mut socket =
+                    tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+                        path = request.uri().to_string();
+                        Ok(response)
+                    })
+                    .unwrap();
+                paths.push(path);
+                for message in connection {
+                    socket.write_message(Message::Text(message)).unwrap();
+                }
+                while socket.read_message().is_ok() {}
+            }
+            paths
+        })
+    }
+
+    fn mock_listen_key(method: &str, path: &str, body: &str) -> mockito::Mock {
+        mock(method, path)
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_header("x-mbx-apikey", "api-key")
+            .with_body(body)
+    }
+
+    #[test]
+    fn manager_renews_expired_listen_key() {
+        let events: Vec<String> = USER_DATA_EVENTS.lines().map(String::from).collect();
+        let start = mock_listen_key("POST", "/api/v3/userDataStream", r#"{"listenKey":"key"}"#)
+            .expect(2)
+            .create();
+        let close = mock_listen_key("DELETE", "/api/v3/userDataStream", "{}")
+            .match_body(Matcher::Exact("listenKey=key".into()))
+            .expect(1)
+            .create();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Config::default()
+            .set_rest_api_endpoint(mockito::server_url())
+            .set_ws_endpoint(format!("ws://{}/ws", listener.local_addr().unwrap()));
+        // The first connection gets the key expired, the second an execution report
+        let server = serve(
+            listener,
+            vec![vec![events[5].clone()], vec![events[2].clone()]],
+        );
+
+        let running = AtomicBool::new(true);
+        let mut received = Vec::new();
+        let mut manager = UserDataStreamManager::new_with_config(
+            UserDataStreamMarket::Spot,
+            Some("api-key".into()),
+            &config,
+            |event: UserDataEvent| {
+                if let UserDataEvent::Spot(WebsocketEvent::OrderTrade(_)) = event {
+                    running.store(false, Ordering::Relaxed);
+                }
+                received.push(event);
+                Ok(())
+            },
+        );
+        manager.event_loop(&running).unwrap();
+        assert_eq!(manager.listen_key(), Some("key"));
+        drop(manager);
+
+        assert_eq!(server.join().unwrap(), vec!["/ws/key", "/ws/key"]);
+        assert!(matches!(
+            received[0],
+            UserDataEvent::Spot(WebsocketEvent::ListenKeyExpired(_))
+        ));
+        assert!(matches!(
+            received[1],
+            UserDataEvent::Spot(WebsocketEvent::OrderTrade(_))
+        ));
+        start.assert();
+        close.assert();
+    }
+
+    // Hands out `<prefix>-1`, `<prefix>-2`, ... as listen keys
+    fn mock_numbered_listen_keys(path: &str, prefix: &'static str) -> mockito::Mock {
+        let started = AtomicUsize::new(0);
+        mock("POST", path)
+            .with_header("content-type", "application/json;charset=UTF-8")
+            .match_header("x-mbx-apikey", "api-key")
+            .with_body_from_fn(move |body| {
+                let number = started.fetch_add(1, Ordering::SeqCst) + 1;
+                write!(body, r#"{{"listenKey":"{}-{}"}}"#, prefix, number)
+            })
+    }
+
+    #[test]
+    fn manager_keeps_listen_key_alive_and_renews_it() {
+        let start = mock_numbered_listen_keys("/sapi/v1/userDataStream", "margin")
+            .expect(2)
+            .create();
+        let keep_alive = mock_listen_key("PUT", "/sapi/v1/userDataStream", "{}")
+            .match_body(Matcher::Regex("listenKey=margin-".into()))
+            .expect_at_least(2)
+            .create();
+        // The renewed key is closed once connected with the new one
+        let reconnected = Arc::new(AtomicBool::new(false));
+        let closed_after_reconnect = Arc::new(AtomicBool::new(false));
+        let close_renewed = {
+            let reconnected = reconnected.clone();
+            let closed_after_reconnect = closed_after_reconnect.clone();
+            mock("DELETE", "/sapi/v1/userDataStream")
+                .with_header("content-type", "application/json;charset=UTF-8")
+                .match_body(Matcher::Exact("listenKey=margin-1".into()))
+                .with_body_from_fn(move |body| {
+                    let reconnected = reconnected.load(Ordering::SeqCst);
+                    closed_after_reconnect.store(reconnected, Ordering::SeqCst);
+                    body.write_all(b"{}")
+                })
+                .expect(1)
+                .create()
+        };
+        let close = mock_listen_key("DELETE", "/sapi/v1/userDataStream", "{}")
+            .match_body(Matcher::Exact("listenKey=margin-2".into()))
+            .expect(1)
+            .create();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Config::default()
+            .set_rest_api_endpoint(mockito::server_url())
+            .set_ws_endpoint(format!("ws://{}/ws", listener.local_addr().unwrap()));
+        // The first connection stays quiet until the key is renewed
+        let balance_update = USER_DATA_EVENTS.lines().nth(1).unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            while socket.read_message().is_ok() {}
+
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            reconnected.store(true, Ordering::SeqCst);
+            // Leave the manager time to close the renewed key
+            thread::sleep(Duration::from_millis(100));
+            socket.write_message(Message::Text(balance_update)).unwrap();
+            while socket.read_message().is_ok() {}
+        });
+
+        let running = AtomicBool::new(true);
+        let mut manager = UserDataStreamManager::new_with_config(
+            UserDataStreamMarket::Margin,
+            Some("api-key".into()),
+            &config,
+            |_event: UserDataEvent| {
+                running.store(false, Ordering::Relaxed);
+                Ok(())
+            },
+        )
+        .set_keep_alive_interval(Duration::from_millis(50))
+        .set_renew_interval(Duration::from_millis(400));
+        manager.event_loop(&running).unwrap();
+        assert_eq!(manager.listen_key(), Some("margin-2"));
+        close_renewed.assert();
+        assert!(closed_after_reconnect.load(Ordering::SeqCst));
+        drop(manager);
+
+        server.join().unwrap();
+        start.assert();
+        keep_alive.assert();
+        close.assert();
+    }
+
+    #[test]
+    fn manager_renews_listen_key_while_reconnecting() {
+        let start = mock_numbered_listen_keys("/fapi/v1/listenKey", "futures")
+            .expect(2)
+            .create();
+        // Closed once connected with the new key and once on drop
+        let close = mock_listen_key("DELETE", "/fapi/v1/listenKey", "{}")
+            .expect(2)
+            .create();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Config::default()
+            .set_futures_rest_api_endpoint(mockito::server_url())
+            .set_futures_ws_endpoint(format!("ws://{}/ws", listener.local_addr().unwrap()));
+        // The first connection is closed right away, and the reconnect attempts
+        // with the first key fail until the key was renewed
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            socket.close(None).unwrap();
+            while socket.read_message().is_ok() {}
+
+            loop {
+                let (stream, _) = listener.accept().unwrap();
+                let mut path = String::new();
+                let accepted =
+                    tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+                        path = request.uri().to_string();
+                        if path.ends_with("futures-1") {
+                            return Err(Response::builder().status(503).body(None).unwrap());
+                        }
+                        Ok(response)
+                    });
+                let mut socket = match accepted {
+                    Ok(socket) => socket,
+                    Err(_) => continue,
+                };
+                let event = include_str!("mocks/websockets/futures_user_data_events.jsonl")
+                    .lines()
+                    .nth(2)
+                    .unwrap();
+                socket.write_message(Message::Text(event.into())).unwrap();
+                while socket.read_message().is_ok() {}
+                return path;
+            }
+        });
+
+        let running = AtomicBool::new(true);
+        let mut manager = UserDataStreamManager::new_with_config(
+            UserDataStreamMarket::UsdMFutures,
+            Some("api-key".into()),
+            &config,
+            |_event: UserDataEvent| {
+                running.store(false, Ordering::Relaxed);
+                Ok(())
+            },
+        )
+        .set_renew_interval(Duration::from_millis(400))
+        .set_reconnect_policy(
+            ReconnectPolicy::default()
+                .set_initial_backoff(Duration::from_millis(50))
+                .set_max_backoff(Duration::from_millis(50)),
+        );
+        manager.event_loop(&running).unwrap();
+        drop(manager);
+
+        assert_eq!(server.join().unwrap(), "/ws/futures-2");
+        start.assert();
+        close.assert();
+    }
+
+    #[test]
+    fn manager_streams_coin_futures_user_data() {
+        let start = mock_listen_key("POST", "/dapi/v1/listenKey", r#"{"listenKey":"coin"}"#)
+            .expect(1)
+            .create();
+        let close = mock_listen_key("DELETE", "/dapi/v1/listenKey", "{}")
+            .match_body(Matcher::Exact("listenKey=coin".into()))
+            .expect(1)
+            .create();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = Config::default()
+            .set_coin_futures_rest_api_endpoint(mockito::server_url())
+            .set_coin_futures_ws_endpoint(format!("ws://{}/ws", listener.local_addr().unwrap()));
+        let event = include_str!("mocks/websockets/futures_user_data_events.jsonl")
+            .lines()
+            .nth(2)
+            .unwrap()
+            .to_string();
+        let server = serve(listener, vec![vec![event]]);
+
+        let running = AtomicBool::new(true);
+        let mut received = Vec::new();
+        let mut manager = UserDataStreamManager::new_with_config(
+            UserDataStreamMarket::CoinMFutures,
+            Some("api-key".into()),
+            &config,
+            |event: UserDataEvent| {
+                running.store(false, Ordering::Relaxed);
+                received.push(event);
+                Ok(())
+            },
+        );
+        manager.event_loop(&running).unwrap();
+        drop(manager);
+
+        assert_eq!(server.join().unwrap(), vec!["/ws/coin"]);
+        assert!(matches!(received[0], UserDataEvent::Futures(_)));
+        start.assert();
+        close.assert();
+    }
+}
@@ -59,4 +59,10 @@ mod tests {
             ulps = 2
         ));
     }
+
+    #[test]
+    fn build_symbols_param() {
+        let result = binance::util::build_symbols_param(vec!["BTCUSDT", "BNBBTC"]);
+        assert_eq!(result, "%5B%22BTCUSDT%22%2C%22BNBBTC%22%5D");
+    }
 }
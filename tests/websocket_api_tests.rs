@@ -0,0 +1,240 @@
+use binance::api::*;
+use binance::config::*;
+use binance::errors::*;
+use binance::websocket_api::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hmac::{Hmac, Mac, NewMac};
+    use serde_json::{json, Value};
+    use sha2::Sha256;
+    use std::collections::BTreeMap;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+    use tungstenite::Message;
+
+    const ORDER_PLACE: &str = include_str!("mocks/websocket_api/order_place.json");
+    const SESSION_LOGON: &str = include_str!("mocks/websocket_api/session_logon.json");
+
+    // Answer the requests of a single client with `respond`.
+    // Returns the received requests.
+    fn serve(
+        listener: TcpListener, respond: fn(&Value) -> Vec<Value>,
+    ) -> thread::JoinHandle<Vec<Value>> {
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            let mut requests = Vec::new();
+            while let Ok(message) = socket.read_message() {
+                if let Message::Text(msg) = message {
+                    let request: Value = serde_json::from_str(&msg).unwrap();
+                    for response in respond(&request) {
+                        socket
+                            .write_message(Message::Text(response.to_string()))
+                            .unwrap();
+                    }
+                    requests.push(request);
+                }
+            }
+            requests
+        })
+    }
+
+    fn response_to(request: &Value, fixture: &str) -> Value {
+        let mut response: Value = serde_json::from_str(fixture).unwrap();
+        response["id"] = request["id"].clone();
+        response
+    }
+
+    fn connect(listener: &TcpListener) -> WebsocketApi {
+        let config = Config::default()
+            .set_ws_api_endpoint(format!("ws://{}", listener.local_addr().unwrap()))
+            .set_recv_window(1234);
+        let mut api: WebsocketApi =
+            Binance::new_with_config(Some("api-key".into()), Some("secret-key".into()), &config);
+        api.connect().unwrap();
+        api
+    }
+
+    fn signature(payload: &str) -> String {
+        let mut signed_key = Hmac::<Sha256>::new_from_slice(b"secret-key").unwrap();
+        signed_key.update(payload.as_bytes());
+        hex::encode(signed_key.finalize().into_bytes())
+    }
+
+    #[test]
+    fn place_signed_order() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = serve(listener.try_clone().unwrap(), |request| {
+            vec![response_to(request, ORDER_PLACE)]
+        });
+        let mut api = connect(&listener);
+
+        let transaction = api.limit_buy("BTCUSDT", 0.00847, 23416.1).unwrap();
+        api.disconnect().unwrap();
+
+        assert_eq!(transaction.order_id, 12569099453);
+        assert_eq!(transaction.status, "NEW");
+        let rate_limits = api.rate_limits();
+        assert_eq!(rate_limits.len(), 2);
+        assert_eq!(rate_limits[1].rate_limit_type, "REQUEST_WEIGHT");
+        assert_eq!(rate_limits[1].count, Some(12));
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request["id"], json!(1));
+        assert_eq!(request["method"], json!("order.place"));
+        let params = &request["params"];
+        assert_eq!(params["apiKey"], json!("api-key"));
+        assert_eq!(params["symbol"], json!("BTCUSDT"));
+        assert_eq!(params["side"], json!("BUY"));
+        assert_eq!(params["type"], json!("LIMIT"));
+        assert_eq!(params["timeInForce"], json!("GTC"));
+        assert_eq!(params["price"], json!("23416.1"));
+        assert_eq!(params["quantity"], json!("0.00847"));
+        assert_eq!(params["recvWindow"], json!(1234));
+        let timestamp = params["timestamp"].as_u64().unwrap();
+
+        let payload = format!(
+            "apiKey=api-key&price=23416.1&quantity=0.00847&recvWindow=1234&side=BUY&symbol=BTCUSDT&timeInForce=GTC&timestamp={}&type=LIMIT",
+            timestamp
+        );
+        assert_eq!(params["signature"], json!(signature(&payload)));
+    }
+
+    #[test]
+    fn correlate_responses_and_time_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = serve(listener.try_clone().unwrap(), |request| {
+            match request["id"].as_u64().unwrap() {
+                // Too late: only answered along with the next request
+                1 => vec![],
+                _ => vec![
+                    json!({"id": 1, "status": 200, "result": {}}),
+                    json!({"id": request["id"], "status": 200, "result": {"serverTime": 1656400526260u64}}),
+                ],
+            }
+        });
+        let mut api = connect(&listener).set_request_timeout(Duration::from_millis(200));
+
+        match api.ping() {
+            Err(Error(ErrorKind::WebSocketApiTimeout(method, timeout), _)) => {
+                assert_eq!(method, "ping");
+                assert_eq!(timeout, Duration::from_millis(200));
+            }
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        assert_eq!(api.get_server_time().unwrap().server_time, 1656400526260);
+        api.disconnect().unwrap();
+
+        let methods: Vec<Value> = server
+            .join()
+            .unwrap()
+            .iter()
+            .map(|request| request["method"].clone())
+            .collect();
+        assert_eq!(methods, vec![json!("ping"), json!("time")]);
+    }
+
+    #[test]
+    fn per_request_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        // Every response comes 300ms late
+        let server = serve(listener.try_clone().unwrap(), |request| {
+            thread::sleep(Duration::from_millis(300));
+            vec![json!({"id": request["id"], "status": 200, "result": {}})]
+        });
+        let mut api = connect(&listener).set_request_timeout(Duration::from_millis(200));
+
+        match api.request_with_timeout::<Value, _>(
+            "ping",
+            BTreeMap::new(),
+            Duration::from_millis(50),
+        ) {
+            Err(Error(ErrorKind::WebSocketApiTimeout(method, timeout), _)) => {
+                assert_eq!(method, "ping");
+                assert_eq!(timeout, Duration::from_millis(50));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        // Longer than the request timeout of the connection
+        let result: Value = api
+            .request_with_timeout("ping", BTreeMap::new(), Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(result, json!({}));
+        api.disconnect().unwrap();
+
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn error_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = serve(listener.try_clone().unwrap(), |request| {
+            vec![json!({
+                "id": request["id"],
+                "status": 400,
+                "error": {"code": -2011, "msg": "Unknown order sent."},
+                "rateLimits": [{"rateLimitType": "REQUEST_WEIGHT", "interval": "MINUTE", "intervalNum": 1, "limit": 6000, "count": 3}]
+            })]
+        });
+        let mut api = connect(&listener);
+
+        match api.cancel_order("BTCUSDT", 42) {
+            Err(Error(ErrorKind::BinanceError(response), _)) => {
+                assert_eq!(response.code, -2011);
+                assert_eq!(response.msg, "Unknown order sent.");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(api.rate_limits()[0].count, Some(3));
+        api.disconnect().unwrap();
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0]["method"], json!("order.cancel"));
+        assert_eq!(requests[0]["params"]["orderId"], json!("42"));
+    }
+
+    #[test]
+    fn session_logon() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = serve(listener.try_clone().unwrap(), |request| {
+            match request["method"].as_str().unwrap() {
+                "session.logon" => vec![response_to(request, SESSION_LOGON)],
+                _ => vec![json!({"id": request["id"], "status": 200, "result": []})],
+            }
+        });
+        let mut api = connect(&listener);
+
+        let mut signed_payload = String::new();
+        let status = api
+            .session_logon(|payload| {
+                signed_payload = payload.to_string();
+                "ed25519-signature".into()
+            })
+            .unwrap();
+        assert_eq!(status.api_key, Some("api-key".into()));
+        assert!(api.get_open_orders("BTCUSDT").unwrap().is_empty());
+        api.disconnect().unwrap();
+
+        let requests = server.join().unwrap();
+        let logon = &requests[0]["params"];
+        assert_eq!(logon["signature"], json!("ed25519-signature"));
+        assert_eq!(
+            signed_payload,
+            format!(
+                "apiKey=api-key&recvWindow=1234&timestamp={}",
+                logon["timestamp"]
+            )
+        );
+        // Requests of a logged on session are neither authenticated nor signed
+        let open_orders = &requests[1]["params"];
+        assert_eq!(open_orders["symbol"], json!("BTCUSDT"));
+        assert!(open_orders.get("apiKey").is_none());
+        assert!(open_orders.get("signature").is_none());
+        assert!(open_orders["timestamp"].is_u64());
+    }
+}
@@ -0,0 +1,1368 @@
+#![allow(clippy::result_large_err)]
+
+use binance::capture::*;
+use binance::config::*;
+use binance::futures::websockets::*;
+use binance::streams::*;
+use binance::websockets::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::Duration;
+    use tungstenite::handshake::server::{Request, Response};
+    use serde_json::{json, Value};
+    use tungstenite::Message;
+
+    const BOOK_TICKER: &str = r#"{"u":400900217,"s":"BNBUSDT","b":"25.35190000","B":"31.21000000","a":"25.36520000","A":"40.66000000"}"#;
+    const FUTURES_BOOK_TICKER: &str = r#"{"e":"bookTicker","u":400900217,"E":1568014460893,"T":1568014460891,"s":"BNBUSDT","b":"25.35190000","B":"31.21000000","a":"25.36520000","A":"40.66000000"}"#;
+
+    // Accept `connections` clients one after the other, send `message` to each
+    // and close the connection. Further connections are refused.
+    // Returns the requested paths.
+    fn serve(
+        listener: TcpListener, connections: usize, message: &'static str,
+    ) -> thread::JoinHandle<Vec<String>> {
+        thread::spawn(move || {
+            let mut listener = Some(listener);
+            let mut paths = Vec::new();
+            for i in 0..connections {
+                let (stream, _) = listener.as_ref().unwrap().accept().unwrap();
+                if i + 1 == connections {
+                    listener = None;
+                }
+                let mut path = String::new();
+                let mut socket =
+                    tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+                        path = request.uri().to_string();
+                        Ok(response)
+                    })
+                    .unwrap();
+                paths.push(path);
+
+                socket.write_message(Message::Text(message.into())).unwrap();
+                // The client may already be gone after renewing its connection
+                let _ = socket.close(None);
+                // Complete the closing handshake
+                while socket.read_message().is_ok() {}
+            }
+            paths
+        })
+    }
+
+    // Answer the live requests of `connections` clients one after the other with
+    // `respond`, which also tells whether to close the connection afterwards.
+    // Returns the received requests.
+    fn serve_requests(
+        listener: TcpListener, connections: usize, respond: fn(&Value) -> (Vec<String>, bool),
+    ) -> thread::JoinHandle<Vec<Value>> {
+        thread::spawn(move || {
+            let mut requests = Vec::new();
+            for _ in 0..connections {
+                let (stream, _) = listener.accept().unwrap();
+                let mut socket = tungstenite::accept(stream).unwrap();
+                while let Ok(message) = socket.read_message() {
+                    if let Message::Text(msg) = message {
+                        let request: Value = serde_json::from_str(&msg).unwrap();
+                        let (responses, close) = respond(&request);
+                        requests.push(request);
+                        for response in responses {
+                            socket.write_message(Message::Text(response)).unwrap();
+                        }
+                        if close {
+                            socket.close(None).unwrap();
+                        }
+                    }
+                }
+            }
+            requests
+        })
+    }
+
+    // Send `messages` to a single client and close the connection
+    fn serve_messages(listener: TcpListener, messages: Vec<String>) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            for message in messages {
+                socket.write_message(Message::Text(message)).unwrap();
+            }
+            socket.close(None).unwrap();
+            while socket.read_message().is_ok() {}
+        })
+    }
+
+    // Accept `connections` clients one after the other without sending anything,
+    // until each one goes away. Returns the number of pings received.
+    fn serve_silent(listener: TcpListener, connections: usize) -> thread::JoinHandle<usize> {
+        thread::spawn(move || {
+            let mut pings = 0;
+            for _ in 0..connections {
+                let (stream, _) = listener.accept().unwrap();
+                let mut socket = tungstenite::accept(stream).unwrap();
+                while let Ok(message) = socket.read_message() {
+                    if let Message::Ping(_) = message {
+                        pings += 1;
+                    }
+                }
+            }
+            pings
+        })
+    }
+
+    fn quiet_heartbeat() -> HeartbeatPolicy {
+        HeartbeatPolicy::default()
+            .set_read_timeout(Duration::from_millis(20))
+            .set_ping_interval(None)
+            .set_stale_timeout(None)
+    }
+
+    fn book_ticker(symbol: &str, update_id: u64) -> String {
+        json!({
+            "u": update_id, "s": symbol, "b": "25.35190000", "B": "31.21000000",
+            "a": "25.36520000", "A": "40.66000000"
+        })
+        .to_string()
+    }
+
+    fn book_ticker_ids(events: Vec<WebsocketEvent>) -> Vec<(String, u64)> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                WebsocketEvent::BookTicker(ticker) => (ticker.symbol, ticker.update_id),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    fn wait_for_dropped(receiver: &WebsocketEventReceiver, dropped: u64) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while receiver.dropped() < dropped && std::time::Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn local_listener() -> (TcpListener, Config) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("ws://{}/ws", listener.local_addr().unwrap());
+        let config = Config::default()
+            .set_ws_endpoint(endpoint.clone())
+            .set_futures_ws_endpoint(endpoint);
+        (listener, config)
+    }
+
+    fn fast_policy() -> ReconnectPolicy {
+        ReconnectPolicy::default()
+            .set_initial_backoff(Duration::from_millis(10))
+            .set_max_backoff(Duration::from_millis(50))
+            .set_max_attempts(3)
+    }
+
+    // Name of the variant of each event handled for the messages of a fixture
+    fn spot_event_names(fixture: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                names.push(variant_name(&event));
+                Ok(())
+            });
+            for msg in fixture.lines() {
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+        }
+        names
+    }
+
+    fn futures_event_names(fixture: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        {
+            let mut web_socket = FuturesWebSockets::new(|event: FuturesWebsocketEvent| {
+                names.push(variant_name(&event));
+                Ok(())
+            });
+            for msg in fixture.lines() {
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+        }
+        names
+    }
+
+    fn variant_name<E: std::fmt::Debug>(event: &E) -> String {
+        format!("{:?}", event)
+            .split('(')
+            .next()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn dispatch_spot_events() {
+        let names = spot_event_names(include_str!("mocks/websockets/spot_events.jsonl"));
+        assert_eq!(
+            names,
+            vec![
+                "DayTicker",
+                "AccountUpdate",
+                "OrderTrade",
+                "AggrTrades",
+                "Trade",
+                "Kline",
+                "DepthOrderBook",
+                "BookTicker",
+                "OrderBook",
+                "OrderBook",
+                "BookTicker",
+                "Trade",
+                "LiveResponse",
+                "DayTickerAll",
+            ]
+        );
+    }
+
+    #[test]
+    fn dispatch_futures_events() {
+        let names = futures_event_names(include_str!("mocks/websockets/futures_events.jsonl"));
+        assert_eq!(
+            names,
+            vec![
+                "DayTicker",
+                "BookTicker",
+                "MiniTicker",
+                "MarkPrice",
+                "IndexPrice",
+                "Trade",
+                "AggrTrades",
+                "Kline",
+                "ContinuousKline",
+                "IndexKline",
+                "Liquidation",
+                "DepthOrderBook",
+                "ListenKeyExpired",
+                "LeverageUpdate",
+                "MarginUpdate",
+                "MarkPrice",
+                "LiveResponse",
+                "MiniTickerAll",
+                "MarkPriceAll",
+                "CompositeIndex",
+                "ContractInfo",
+                "ContractInfo",
+                "AssetIndex",
+                "MarkPrice",
+                "AssetIndexAll",
+            ]
+        );
+    }
+
+    #[test]
+    fn spot_user_data_events() {
+        let mut events = Vec::new();
+        {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                events.push(event);
+                Ok(())
+            })
+            .set_strict(true);
+            for msg in include_str!("mocks/websockets/user_data_events.jsonl").lines() {
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+        }
+        assert_eq!(events.len(), 8);
+
+        match &events[0] {
+            WebsocketEvent::AccountUpdate(account) => {
+                assert_eq!(account.last_account_update_time, 1564034571073);
+                assert_eq!(account.balance[0].asset, "ETH");
+                assert_eq!(account.balance[0].free, "10000.000000");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[1] {
+            WebsocketEvent::BalanceUpdate(update) => {
+                assert_eq!(update.asset, "BTC");
+                assert_eq!(update.balance_delta, "100.00000000");
+                assert_eq!(update.clear_time, 1573200697068);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[2] {
+            WebsocketEvent::OrderTrade(trade) => {
+                assert_eq!(trade.commission_asset.as_deref(), Some("BNB"));
+                assert_eq!(trade.order_creation_time, 1499405658650);
+                assert_eq!(trade.cumulative_quote_qty, "0.10264410");
+                assert_eq!(trade.last_quote_qty, "0.10264410");
+                assert_eq!(trade.order_list_id, -1);
+                assert_eq!(trade.original_client_order_id.as_deref(), Some(""));
+                assert_eq!(trade.prevented_match_id, Some(3));
+                assert_eq!(trade.working_time, Some(1499405658650));
+                assert_eq!(
+                    trade.self_trade_prevention_mode.as_deref(),
+                    Some("EXPIRE_MAKER")
+                );
+                assert!(trade.is_buyer_maker);
+                assert!(!trade.is_working);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[3] {
+            WebsocketEvent::OrderTrade(trade) => {
+                assert_eq!(trade.commission_asset, None);
+                assert_eq!(trade.stop_price, "0.10000000");
+                assert_eq!(trade.trailing_delta, Some(4));
+                assert_eq!(trade.trailing_time, Some(1668680518494));
+                assert_eq!(trade.strategy_id, Some(1));
+                assert_eq!(trade.strategy_type, Some(1000000));
+                assert_eq!(trade.trade_id, -1);
+                assert_eq!(trade.working_time, None);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[4] {
+            WebsocketEvent::ListStatus(list) => {
+                assert_eq!(list.order_list_id, 2);
+                assert_eq!(list.contingency_type, "OCO");
+                assert_eq!(list.list_order_status, "EXECUTING");
+                assert_eq!(list.orders.len(), 2);
+                assert_eq!(list.orders[1].order_id, 18);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[5] {
+            WebsocketEvent::ListenKeyExpired(expired) => assert_eq!(
+                expired.listen_key.as_deref(),
+                Some("OfYGbUzi3PraNagEkdKuFwUHn48brFsItTdsuiIXrucEvD0rhRXZ7I6URWfE8YE8")
+            ),
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[6] {
+            WebsocketEvent::ExternalLockUpdate(update) => {
+                assert_eq!(update.asset, "NEO");
+                assert_eq!(update.delta, "10.00000000");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[7] {
+            WebsocketEvent::EventStreamTerminated(terminated) => {
+                assert_eq!(terminated.event_time, 1728973001334)
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn futures_user_data_events() {
+        let mut events = Vec::new();
+        {
+            let mut web_socket = FuturesWebSockets::new(|event: FuturesWebsocketEvent| {
+                events.push(event);
+                Ok(())
+            })
+            .set_strict(true);
+            for msg in include_str!("mocks/websockets/futures_user_data_events.jsonl").lines() {
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+        }
+        assert_eq!(events.len(), 6);
+
+        match &events[0] {
+            FuturesWebsocketEvent::OrderTrade(trade) => {
+                assert_eq!(trade.order.realized_profit, "0.12345");
+                assert_eq!(
+                    trade.order.self_trade_prevention_mode.as_deref(),
+                    Some("EXPIRE_TAKER")
+                );
+                assert_eq!(trade.order.price_match.as_deref(), Some("OPPONENT"));
+                assert_eq!(trade.order.good_till_date, Some(1568880000000));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        // Orders from before self trade prevention and price match
+        match &events[1] {
+            FuturesWebsocketEvent::OrderTrade(trade) => {
+                assert_eq!(trade.order.realized_profit, "0");
+                assert_eq!(trade.order.self_trade_prevention_mode, None);
+                assert_eq!(trade.order.price_match, None);
+                assert_eq!(trade.order.good_till_date, None);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[2] {
+            FuturesWebsocketEvent::TradeLite(trade) => {
+                assert_eq!(trade.symbol, "BTCUSDT");
+                assert_eq!(trade.price_last_filled_trade, "64089.20");
+                assert_eq!(trade.qty_last_filled_trade, "0.040");
+                assert_eq!(trade.trade_id, 109100866);
+                assert_eq!(trade.order_id, 8886774);
+                assert!(!trade.is_maker);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[3] {
+            FuturesWebsocketEvent::StrategyUpdate(update) => {
+                assert_eq!(update.strategy.strategy_id, 176054594);
+                assert_eq!(update.strategy.strategy_type, "GRID");
+                assert_eq!(update.strategy.strategy_status, "NEW");
+                assert_eq!(update.strategy.op_code, 8007);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[4] {
+            FuturesWebsocketEvent::GridUpdate(update) => {
+                assert_eq!(update.grid.strategy_status, "WORKING");
+                assert_eq!(update.grid.realized_profit, "-0.00300716");
+                assert_eq!(update.grid.unmatched_average_price, "16720");
+                assert_eq!(update.grid.unmatched_qty, "-0.001");
+                assert_eq!(update.grid.matched_profit, "0.0");
+                assert_eq!(update.grid.update_time, 1669262908197);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[5] {
+            FuturesWebsocketEvent::ConditionalOrderTriggerReject(reject) => {
+                assert_eq!(reject.order.symbol, "ETHUSDT");
+                assert_eq!(reject.order.order_id, 155618472834);
+                assert!(reject.order.reason.contains("immediate liquidation"));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn futures_index_and_contract_events() {
+        let mut events = Vec::new();
+        {
+            let mut web_socket = FuturesWebSockets::new(|event: FuturesWebsocketEvent| {
+                events.push(event);
+                Ok(())
+            })
+            .set_strict(true);
+            let fixture = include_str!("mocks/websockets/futures_events.jsonl");
+            for msg in fixture.lines().skip(19) {
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+        }
+
+        match &events[0] {
+            FuturesWebsocketEvent::CompositeIndex(index) => {
+                assert_eq!(index.symbol, "DEFIUSDT");
+                assert_eq!(index.composition_type.as_deref(), Some("baseAsset"));
+                assert_eq!(index.composition.len(), 2);
+                assert_eq!(index.composition[1].base_asset, "BAND");
+                assert_eq!(index.composition[1].weight_in_percentage, "0.03935200");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[1] {
+            FuturesWebsocketEvent::ContractInfo(info) => {
+                assert_eq!(info.contract_status, "TRADING");
+                let brackets = info.brackets.as_ref().unwrap();
+                assert_eq!(brackets[1].bracket, 2);
+                assert_eq!(brackets[1].notional_cap, 25000.0);
+                assert_eq!(brackets[1].maintenance_margin_ratio, 0.025);
+                assert_eq!(brackets[1].max_leverage, 20);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        // Brackets are left out when only the contract status changes
+        match &events[2] {
+            FuturesWebsocketEvent::ContractInfo(info) => {
+                assert_eq!(info.contract_type, "CURRENT_QUARTER");
+                assert_eq!(info.delivery_date, 1743148800000);
+                assert!(info.brackets.is_none());
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[3] {
+            FuturesWebsocketEvent::AssetIndex(index) => {
+                assert_eq!(index.symbol, "ADAUSD");
+                assert_eq!(index.index_price, "0.27462452");
+                assert_eq!(index.bid_rate, "0.24716207");
+                assert_eq!(index.auto_exchange_ask_rate, "0.28835575");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        match &events[5] {
+            FuturesWebsocketEvent::AssetIndexAll(indexes) => {
+                assert_eq!(indexes.len(), 2);
+                assert_eq!(indexes[1].symbol, "USDTUSD");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn spot_stream_events() {
+        let symbol = || "BNBBTC".to_string();
+        let streams = vec![
+            (Stream::Trade(symbol()), "Trade"),
+            (Stream::AggTrade(symbol()), "AggrTrades"),
+            (
+                Stream::Kline(symbol(), binance::market::KlineInterval::OneMinute),
+                "Kline",
+            ),
+            (Stream::AvgPrice(symbol()), "AvgPrice"),
+            (
+                Stream::Depth {
+                    symbol: symbol(),
+                    levels: None,
+                    speed: Some(DepthSpeed::Ms100),
+                },
+                "DepthOrderBook",
+            ),
+            (
+                Stream::Depth {
+                    symbol: symbol(),
+                    levels: Some(DepthLevels::Five),
+                    speed: None,
+                },
+                "OrderBook",
+            ),
+            (Stream::BookTicker(symbol()), "BookTicker"),
+            (Stream::AllBookTickers, "BookTicker"),
+            (Stream::MiniTicker(symbol()), "MiniTicker"),
+            (Stream::AllMiniTickers, "MiniTickerAll"),
+            (Stream::Ticker(symbol()), "DayTicker"),
+            (Stream::AllTickers, "DayTickerAll"),
+            (
+                Stream::RollingWindowTicker(symbol(), RollingWindow::OneHour),
+                "RollingWindowTicker",
+            ),
+            (
+                Stream::AllRollingWindowTickers(RollingWindow::OneDay),
+                "RollingWindowTickerAll",
+            ),
+            (Stream::UserData("listenkey".into()), "AccountUpdate"),
+        ];
+        let fixture = include_str!("mocks/websockets/spot_streams.jsonl");
+        assert_eq!(fixture.lines().count(), streams.len());
+
+        // Each fixture line is received on the stream at the same position
+        let mut events = Vec::new();
+        {
+            let mut web_socket =
+                WebSockets::new_with_stream_names(|message: StreamMessage<WebsocketEvent>| {
+                    events.push((message.stream.unwrap(), variant_name(&message.event)));
+                    Ok(())
+                })
+                .set_strict(true);
+            for ((stream, _), data) in streams.iter().zip(fixture.lines()) {
+                let msg = format!(r#"{{"stream":"{}","data":{}}}"#, stream, data);
+                web_socket.test_handle_msg(&msg).unwrap();
+            }
+        }
+
+        let expected: Vec<(String, String)> = streams
+            .iter()
+            .map(|(stream, name)| (stream.to_string(), name.to_string()))
+            .collect();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn stream_names_of_combined_messages() {
+        let mut messages = Vec::new();
+        {
+            let mut web_socket =
+                WebSockets::new_with_stream_names(|message: StreamMessage<WebsocketEvent>| {
+                    messages.push((message.stream.clone(), message.descriptor()));
+                    Ok(())
+                });
+            let fixture = include_str!("mocks/websockets/spot_events.jsonl");
+            for msg in fixture
+                .lines()
+                .filter(|msg| msg.starts_with(r#"{"stream""#))
+            {
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+            web_socket.test_handle_msg(BOOK_TICKER).unwrap();
+        }
+
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    Some("bnbusdt@depth5".to_string()),
+                    Some(Stream::Depth {
+                        symbol: "bnbusdt".into(),
+                        levels: Some(DepthLevels::Five),
+                        speed: None,
+                    })
+                ),
+                (
+                    Some("bnbusdt@bookTicker".to_string()),
+                    Some(Stream::BookTicker("bnbusdt".into()))
+                ),
+                (
+                    Some("bnbbtc@trade".to_string()),
+                    Some(Stream::Trade("bnbbtc".into()))
+                ),
+                // Raw streams carry no name
+                (None, None),
+            ]
+        );
+
+        let mut streams = Vec::new();
+        {
+            let mut web_socket = FuturesWebSockets::new_with_stream_names(
+                |message: StreamMessage<FuturesWebsocketEvent>| {
+                    streams.push(message.descriptor());
+                    Ok(())
+                },
+            );
+            let fixture = include_str!("mocks/websockets/futures_events.jsonl");
+            for msg in fixture
+                .lines()
+                .filter(|msg| msg.starts_with(r#"{"stream""#))
+            {
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+        }
+        assert_eq!(
+            streams,
+            vec![
+                Some(Stream::MarkPrice("btcusdt".into(), None)),
+                Some(Stream::MarkPrice("btcusdt".into(), Some(PriceSpeed::Sec1))),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_events() {
+        let messages = [
+            r#"{"e":"newEvent","E":1}"#,
+            // A trade without its price
+            r#"{"e":"trade","E":123456789,"s":"BNBBTC","t":12345,"q":"100","b":88,"a":50,"T":123456785,"m":true,"M":true}"#,
+            "not json",
+        ];
+        let mut unknown = Vec::new();
+        {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                if let WebsocketEvent::Unknown { raw, error } = event {
+                    unknown.push((raw, error));
+                }
+                Ok(())
+            });
+            for msg in messages.iter() {
+                web_socket.test_handle_msg(msg).unwrap();
+            }
+        }
+
+        assert_eq!(unknown.len(), 3);
+        assert_eq!(unknown[0].0, messages[0]);
+        assert_eq!(unknown[0].1, "unknown event type newEvent");
+        assert!(unknown[1].1.starts_with("missing field `p`"));
+        assert_eq!(unknown[2].0, "not json");
+    }
+
+    #[test]
+    fn strict_mode() {
+        let mut web_socket = WebSockets::new(|_event: WebsocketEvent| Ok(())).set_strict(true);
+        web_socket.test_handle_msg(BOOK_TICKER).unwrap();
+        let error = web_socket
+            .test_handle_msg(r#"{"e":"newEvent","E":1}"#)
+            .unwrap_err();
+        match error.kind() {
+            binance::errors::ErrorKind::WebSocketUnknownEvent(raw, error) => {
+                assert_eq!(raw, r#"{"e":"newEvent","E":1}"#);
+                assert_eq!(error, "unknown event type newEvent");
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        let mut futures_web_socket =
+            FuturesWebSockets::new(|_event: FuturesWebsocketEvent| Ok(())).set_strict(true);
+        futures_web_socket
+            .test_handle_msg(FUTURES_BOOK_TICKER)
+            .unwrap();
+        assert!(futures_web_socket.test_handle_msg(BOOK_TICKER).is_err());
+    }
+
+    #[test]
+    fn reconnect_after_close() {
+        let (listener, config) = local_listener();
+        let server = serve(listener, 2, BOOK_TICKER);
+
+        let keep_running = AtomicBool::new(true);
+        let mut tickers = 0;
+        let mut reconnects = Vec::new();
+        {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                match event {
+                    WebsocketEvent::BookTicker(_) => tickers += 1,
+                    WebsocketEvent::Reconnected(reconnected) => reconnects.push(reconnected),
+                    _ => (),
+                }
+                if tickers == 2 {
+                    keep_running.store(false, Ordering::Relaxed);
+                }
+                Ok(())
+            })
+            .set_reconnect_policy(fast_policy());
+
+            web_socket
+                .connect_with_config(Stream::BookTicker("BNBUSDT".into()), &config)
+                .unwrap();
+            web_socket.event_loop(&keep_running).unwrap();
+        }
+
+        assert_eq!(tickers, 2);
+        assert_eq!(reconnects.len(), 1);
+        assert_eq!(reconnects[0].attempts, 1);
+        assert!(reconnects[0].reason.starts_with("closed by server"));
+
+        // The subscription is replayed on the new connection
+        let paths = server.join().unwrap();
+        assert_eq!(paths, vec!["/ws/bnbusdt@bookTicker"; 2]);
+    }
+
+    #[test]
+    fn reconnect_before_max_connection_age() {
+        let (listener, config) = local_listener();
+        let server = serve(listener, 2, BOOK_TICKER);
+
+        let keep_running = AtomicBool::new(true);
+        let mut reasons = Vec::new();
+        {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                if let WebsocketEvent::Reconnected(reconnected) = event {
+                    reasons.push(reconnected.reason);
+                    keep_running.store(false, Ordering::Relaxed);
+                }
+                Ok(())
+            })
+            .set_reconnect_policy(fast_policy().set_max_connection_age(Duration::from_secs(0)));
+
+            web_socket
+                .connect_with_config("bnbusdt@bookTicker", &config)
+                .unwrap();
+            web_socket.event_loop(&keep_running).unwrap();
+            web_socket.disconnect().unwrap();
+        }
+
+        assert_eq!(reasons, vec!["connection age limit reached"]);
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn reconnect_gives_up_after_max_attempts() {
+        let (listener, config) = local_listener();
+        let server = serve(listener, 1, BOOK_TICKER);
+
+        let keep_running = AtomicBool::new(true);
+        let mut web_socket =
+            WebSockets::new(|_event: WebsocketEvent| Ok(())).set_reconnect_policy(fast_policy());
+        web_socket
+            .connect_with_config("bnbusdt@bookTicker", &config)
+            .unwrap();
+
+        let error = web_socket.event_loop(&keep_running).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Not able to reconnect after 3 attempts"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn event_loop_without_connection() {
+        let keep_running = AtomicBool::new(true);
+        let mut web_socket = WebSockets::new(|_event: WebsocketEvent| Ok(()));
+
+        let error = web_socket.event_loop(&keep_running).unwrap_err();
+        assert!(error.to_string().starts_with("Not able to reconnect"));
+    }
+
+    #[test]
+    fn futures_reconnect_after_close() {
+        let (listener, config) = local_listener();
+        let server = serve(listener, 2, FUTURES_BOOK_TICKER);
+
+        let keep_running = AtomicBool::new(true);
+        let mut tickers = 0;
+        let mut reconnects = 0;
+        {
+            let mut web_socket = FuturesWebSockets::new(|event: FuturesWebsocketEvent| {
+                match event {
+                    FuturesWebsocketEvent::BookTicker(_) => tickers += 1,
+                    FuturesWebsocketEvent::Reconnected(_) => reconnects += 1,
+                    _ => (),
+                }
+                if tickers == 2 {
+                    keep_running.store(false, Ordering::Relaxed);
+                }
+                Ok(())
+            })
+            .set_reconnect_policy(fast_policy());
+
+            web_socket
+                .connect_with_config(FuturesMarket::USDM, "bnbusdt@bookTicker", &config)
+                .unwrap();
+            web_socket.event_loop(&keep_running).unwrap();
+        }
+
+        assert_eq!((tickers, reconnects), (2, 1));
+        assert_eq!(server.join().unwrap(), vec!["/ws/bnbusdt@bookTicker"; 2]);
+    }
+
+    #[test]
+    fn shutdown_from_another_thread() {
+        let (listener, config) = local_listener();
+        let server = serve_silent(listener, 1);
+
+        let keep_running = AtomicBool::new(true);
+        let mut web_socket = WebSockets::new(|_event: WebsocketEvent| Ok(()))
+            .set_heartbeat_policy(quiet_heartbeat());
+        web_socket
+            .connect_with_config("bnbusdt@bookTicker", &config)
+            .unwrap();
+
+        let shutdown = web_socket.shutdown_handle();
+        let stopper = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            shutdown.shutdown();
+        });
+
+        // Nothing is received, the loop still notices the shutdown
+        web_socket.event_loop(&keep_running).unwrap();
+        assert!(web_socket.shutdown_handle().is_shutdown());
+        web_socket.disconnect().unwrap();
+
+        stopper.join().unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn client_ping() {
+        let (listener, config) = local_listener();
+        let server = serve_silent(listener, 1);
+
+        let keep_running = AtomicBool::new(true);
+        let mut web_socket = WebSockets::new(|_event: WebsocketEvent| Ok(())).set_heartbeat_policy(
+            quiet_heartbeat().set_ping_interval(Some(Duration::from_millis(30))),
+        );
+        web_socket
+            .connect_with_config("bnbusdt@bookTicker", &config)
+            .unwrap();
+
+        let shutdown = web_socket.shutdown_handle();
+        let stopper = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            shutdown.shutdown();
+        });
+        web_socket.event_loop(&keep_running).unwrap();
+        web_socket.disconnect().unwrap();
+
+        stopper.join().unwrap();
+        assert!(server.join().unwrap() >= 2);
+    }
+
+    #[test]
+    fn stale_connection() {
+        let (listener, config) = local_listener();
+        let server = serve_silent(listener, 1);
+
+        let keep_running = AtomicBool::new(true);
+        let mut web_socket = WebSockets::new(|_event: WebsocketEvent| Ok(())).set_heartbeat_policy(
+            quiet_heartbeat().set_stale_timeout(Some(Duration::from_millis(100))),
+        );
+        web_socket
+            .connect_with_config("bnbusdt@bookTicker", &config)
+            .unwrap();
+
+        let error = web_socket.event_loop(&keep_running).unwrap_err();
+        match error.kind() {
+            binance::errors::ErrorKind::WebSocketStale(elapsed) => {
+                assert!(*elapsed >= Duration::from_millis(100))
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn futures_stale_connection_reconnects() {
+        let (listener, config) = local_listener();
+        let server = serve_silent(listener, 2);
+
+        let keep_running = AtomicBool::new(true);
+        let mut reasons = Vec::new();
+        {
+            let mut web_socket = FuturesWebSockets::new(|event: FuturesWebsocketEvent| {
+                if let FuturesWebsocketEvent::Reconnected(reconnected) = event {
+                    reasons.push(reconnected.reason);
+                    keep_running.store(false, Ordering::Relaxed);
+                }
+                Ok(())
+            })
+            .set_reconnect_policy(fast_policy())
+            .set_heartbeat_policy(
+                quiet_heartbeat().set_stale_timeout(Some(Duration::from_millis(100))),
+            );
+
+            web_socket
+                .connect_with_config(FuturesMarket::USDM, "bnbusdt@bookTicker", &config)
+                .unwrap();
+            web_socket.event_loop(&keep_running).unwrap();
+            web_socket.disconnect().unwrap();
+        }
+
+        assert_eq!(reasons.len(), 1);
+        assert!(reasons[0].starts_with("no data received"));
+        server.join().unwrap();
+    }
+
+    fn respond_to_live_requests(request: &Value) -> (Vec<String>, bool) {
+        let id = &request["id"];
+        let responses = match request["method"].as_str().unwrap() {
+            // A stream event arrives before the acknowledgement
+            "SUBSCRIBE" => vec![
+                BOOK_TICKER.to_string(),
+                json!({ "result": null, "id": id }).to_string(),
+            ],
+            "LIST_SUBSCRIPTIONS" => {
+                vec![
+                    json!({ "result": ["bnbusdt@bookTicker", "btcusdt@trade"], "id": id })
+                        .to_string(),
+                ]
+            }
+            "GET_PROPERTY" => vec![json!({ "result": true, "id": id }).to_string()],
+            "SET_PROPERTY" => {
+                vec![json!({ "code": 0, "msg": "Unknown property", "id": id }).to_string()]
+            }
+            _ => vec![json!({ "result": null, "id": id }).to_string()],
+        };
+        (responses, false)
+    }
+
+    #[test]
+    fn live_requests() {
+        let (listener, config) = local_listener();
+        let server = serve_requests(listener, 1, respond_to_live_requests);
+
+        let mut tickers = 0;
+        {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                if let WebsocketEvent::BookTicker(_) = event {
+                    tickers += 1;
+                }
+                Ok(())
+            });
+            web_socket
+                .connect_with_config("bnbusdt@bookTicker", &config)
+                .unwrap();
+
+            web_socket
+                .subscribe(&[Stream::Trade("BTCUSDT".into())])
+                .unwrap();
+            assert_eq!(
+                web_socket.list_subscriptions().unwrap(),
+                vec!["bnbusdt@bookTicker", "btcusdt@trade"]
+            );
+            assert_eq!(web_socket.get_property("combined").unwrap(), json!(true));
+            match web_socket.set_property("fast", true) {
+                Err(binance::errors::Error(
+                    binance::errors::ErrorKind::BinanceError(response),
+                    _,
+                )) => {
+                    assert_eq!(response.code, 0);
+                    assert_eq!(response.msg, "Unknown property");
+                }
+                other => panic!("unexpected response {:?}", other),
+            }
+            web_socket.unsubscribe(&["btcusdt@trade"]).unwrap();
+            web_socket.disconnect().unwrap();
+        }
+
+        assert_eq!(tickers, 1);
+        let requests = server.join().unwrap();
+        assert_eq!(
+            requests,
+            vec![
+                json!({ "method": "SUBSCRIBE", "params": ["btcusdt@trade"], "id": 1 }),
+                json!({ "method": "LIST_SUBSCRIPTIONS", "params": [], "id": 2 }),
+                json!({ "method": "GET_PROPERTY", "params": ["combined"], "id": 3 }),
+                json!({ "method": "SET_PROPERTY", "params": ["fast", true], "id": 4 }),
+                json!({ "method": "UNSUBSCRIBE", "params": ["btcusdt@trade"], "id": 5 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn live_request_timeout() {
+        let (listener, config) = local_listener();
+        let server = serve_silent(listener, 1);
+
+        // Without a heartbeat policy the reads are not bounded by themselves
+        let mut web_socket = WebSockets::new(|_event: WebsocketEvent| Ok(()))
+            .set_live_request_timeout(Duration::from_millis(200));
+        web_socket
+            .connect_with_config("bnbusdt@bookTicker", &config)
+            .unwrap();
+
+        match web_socket.subscribe(&["btcusdt@trade"]) {
+            Err(binance::errors::Error(
+                binance::errors::ErrorKind::WebSocketLiveRequestTimeout(method, id, timeout),
+                _,
+            )) => {
+                assert_eq!((method.as_str(), id), ("SUBSCRIBE", 1));
+                assert_eq!(timeout, Duration::from_millis(200));
+            }
+            other => panic!("unexpected response {:?}", other),
+        }
+        web_socket.disconnect().unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn live_subscriptions_replayed_after_reconnect() {
+        fn respond(request: &Value) -> (Vec<String>, bool) {
+            let ack = json!({ "result": null, "id": request["id"] }).to_string();
+            match request["id"].as_u64() {
+                // The first connection is closed once subscribed
+                Some(1) => (vec![ack], true),
+                _ => (vec![ack, FUTURES_BOOK_TICKER.to_string()], false),
+            }
+        }
+
+        let (listener, config) = local_listener();
+        let server = serve_requests(listener, 2, respond);
+
+        let keep_running = AtomicBool::new(true);
+        let mut reconnects = 0;
+        {
+            let mut web_socket = FuturesWebSockets::new(|event: FuturesWebsocketEvent| {
+                match event {
+                    FuturesWebsocketEvent::Reconnected(_) => reconnects += 1,
+                    FuturesWebsocketEvent::BookTicker(_) => {
+                        keep_running.store(false, Ordering::Relaxed)
+                    }
+                    _ => (),
+                }
+                Ok(())
+            })
+            .set_reconnect_policy(fast_policy());
+
+            web_socket
+                .connect_with_config(FuturesMarket::USDM, "bnbusdt@bookTicker", &config)
+                .unwrap();
+            web_socket.subscribe(&["btcusdt@aggTrade"]).unwrap();
+            web_socket.event_loop(&keep_running).unwrap();
+            web_socket.disconnect().unwrap();
+        }
+
+        assert_eq!(reconnects, 1);
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["params"], requests[1]["params"]);
+        assert_eq!(requests[1]["method"], "SUBSCRIBE");
+    }
+
+    #[test]
+    fn multiplexer_add_streams_while_reconnecting() {
+        let (listener, config) = local_listener();
+        let refused = Arc::new(AtomicUsize::new(0));
+        let added = Arc::new(AtomicBool::new(false));
+        let (requests, received) = mpsc::channel();
+        let server = {
+            let (refused, added) = (refused.clone(), added.clone());
+            thread::spawn(move || {
+                // The first connection is closed at once, the next ones are
+                // refused until streams were added
+                let (stream, _) = listener.accept().unwrap();
+                let mut socket = tungstenite::accept(stream).unwrap();
+                socket.close(None).unwrap();
+                while socket.read_message().is_ok() {}
+                let mut socket = loop {
+                    let (stream, _) = listener.accept().unwrap();
+                    if added.load(Ordering::SeqCst) {
+                        break tungstenite::accept(stream).unwrap();
+                    }
+                    refused.fetch_add(1, Ordering::SeqCst);
+                };
+                while let Ok(message) = socket.read_message() {
+                    if let Message::Text(msg) = message {
+                        let request: Value = serde_json::from_str(&msg).unwrap();
+                        let ack = json!({ "result": null, "id": request["id"] }).to_string();
+                        socket.write_message(Message::Text(ack)).unwrap();
+                        let _ = requests.send(request);
+                    }
+                }
+            })
+        };
+
+        let receiver = WebsocketMultiplexer::new(&["a@trade"])
+            .set_config(&config)
+            .set_reconnect_policy(
+                ReconnectPolicy::default()
+                    .set_initial_backoff(Duration::from_millis(20))
+                    .set_max_backoff(Duration::from_millis(20))
+                    .set_max_attempts(None),
+            )
+            .set_heartbeat_policy(quiet_heartbeat())
+            .spawn()
+            .unwrap();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while refused.load(Ordering::SeqCst) == 0 && std::time::Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(refused.load(Ordering::SeqCst) > 0);
+
+        receiver.add_streams(&["b@trade"]).unwrap();
+        added.store(true, Ordering::SeqCst);
+
+        // The subscription is sent once reconnected
+        let request = received.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(request["method"], "SUBSCRIBE");
+        assert_eq!(request["params"], json!(["b@trade"]));
+        receiver.join().unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn channel_block() {
+        let (listener, config) = local_listener();
+        let messages = (1..=3).map(|id| book_ticker("BNBUSDT", id)).collect();
+        let server = serve_messages(listener, messages);
+
+        let receiver = WebsocketChannel::new(&["bnbusdt@bookTicker"])
+            .set_config(&config)
+            .set_capacity(1)
+            .spawn()
+            .unwrap();
+
+        // The reader waits for the consumer, the stream ends with the connection
+        let events: Vec<WebsocketEvent> = receiver.map(|message| message.event).collect();
+        assert_eq!(
+            book_ticker_ids(events),
+            vec![
+                ("BNBUSDT".to_string(), 1),
+                ("BNBUSDT".to_string(), 2),
+                ("BNBUSDT".to_string(), 3)
+            ]
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn channel_drop_oldest() {
+        let (listener, config) = local_listener();
+        let messages = (1..=5).map(|id| book_ticker("BNBUSDT", id)).collect();
+        let server = serve_messages(listener, messages);
+
+        let receiver = WebsocketChannel::new(&["bnbusdt@bookTicker"])
+            .set_config(&config)
+            .set_capacity(2)
+            .set_overflow_policy(OverflowPolicy::DropOldest)
+            .spawn()
+            .unwrap();
+        wait_for_dropped(&receiver, 3);
+
+        assert_eq!(receiver.dropped(), 3);
+        let events: Vec<WebsocketEvent> = receiver.map(|message| message.event).collect();
+        assert_eq!(
+            book_ticker_ids(events),
+            vec![("BNBUSDT".to_string(), 4), ("BNBUSDT".to_string(), 5)]
+        );
+        server.join().unwrap();
+    }
+
+    fn combined_book_tickers(tickers: &[(&str, u64)]) -> Vec<String> {
+        tickers
+            .iter()
+            .map(|(symbol, update_id)| {
+                format!(
+                    r#"{{"stream":"{}@bookTicker","data":{}}}"#,
+                    symbol.to_lowercase(),
+                    book_ticker(symbol, *update_id)
+                )
+            })
+            .collect()
+    }
+
+    fn stream_book_ticker_ids(
+        receiver: WebsocketEventReceiver,
+    ) -> Vec<(Option<String>, String, u64)> {
+        receiver
+            .map(|message| match message.event {
+                WebsocketEvent::BookTicker(ticker) => {
+                    (message.stream, ticker.symbol, ticker.update_id)
+                }
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn channel_coalesce_per_symbol() {
+        let (listener, config) = local_listener();
+        let messages = combined_book_tickers(&[("BNBUSDT", 1), ("ETHUSDT", 2), ("BNBUSDT", 3)]);
+        let server = serve_messages(listener, messages);
+
+        let receiver = WebsocketChannel::new(&["bnbusdt@bookTicker"])
+            .set_config(&config)
+            .set_capacity(2)
+            .set_overflow_policy(OverflowPolicy::CoalescePerSymbol)
+            .spawn()
+            .unwrap();
+        wait_for_dropped(&receiver, 1);
+
+        // The latest BNBUSDT ticker takes the place of the first one
+        assert_eq!(
+            stream_book_ticker_ids(receiver),
+            vec![
+                (Some("bnbusdt@bookTicker".into()), "BNBUSDT".into(), 3),
+                (Some("ethusdt@bookTicker".into()), "ETHUSDT".into(), 2),
+            ]
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn channel_coalesce_only_when_full() {
+        let (listener, config) = local_listener();
+        let messages = combined_book_tickers(&[("BNBUSDT", 1), ("ETHUSDT", 2), ("BNBUSDT", 3)]);
+        let server = serve_messages(listener, messages);
+
+        let receiver = WebsocketChannel::new(&["bnbusdt@bookTicker"])
+            .set_config(&config)
+            .set_capacity(3)
+            .set_overflow_policy(OverflowPolicy::CoalescePerSymbol)
+            .spawn()
+            .unwrap();
+        let reader = thread::spawn(move || stream_book_ticker_ids(receiver));
+
+        assert_eq!(
+            reader.join().unwrap(),
+            vec![
+                (Some("bnbusdt@bookTicker".into()), "BNBUSDT".into(), 1),
+                (Some("ethusdt@bookTicker".into()), "ETHUSDT".into(), 2),
+                (Some("bnbusdt@bookTicker".into()), "BNBUSDT".into(), 3),
+            ]
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn channel_coalesce_keeps_trades() {
+        let (listener, config) = local_listener();
+        let messages = (1..=3)
+            .map(|id| {
+                json!({
+                    "stream": "bnbbtc@trade",
+                    "data": {
+                        "e": "trade", "E": 123456789, "s": "BNBBTC", "t": id, "p": "0.001",
+                        "q": "100", "b": 88, "a": 50, "T": 123456785, "m": true, "M": true
+                    }
+                })
+                .to_string()
+            })
+            .collect();
+        let server = serve_messages(listener, messages);
+
+        let receiver = WebsocketChannel::new(&["bnbbtc@trade"])
+            .set_config(&config)
+            .set_capacity(2)
+            .set_overflow_policy(OverflowPolicy::CoalescePerSymbol)
+            .spawn()
+            .unwrap();
+        wait_for_dropped(&receiver, 1);
+
+        // Trades are not replaced, the oldest one is dropped instead
+        let trade_ids: Vec<u64> = receiver
+            .map(|message| match message.event {
+                WebsocketEvent::Trade(trade) => trade.trade_id,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(trade_ids, vec![2, 3]);
+        server.join().unwrap();
+    }
+
+    fn capture_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("binance-{}-{}.jsonl", name, std::process::id()))
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let fixture = include_str!("mocks/websockets/spot_events.jsonl");
+        let messages: Vec<String> = fixture.lines().map(String::from).collect();
+        let path = capture_path("record-and-replay");
+        let (listener, config) = local_listener();
+        let server = serve_messages(listener, messages.clone());
+
+        let keep_running = AtomicBool::new(true);
+        let mut received = Vec::new();
+        {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                received.push(variant_name(&event));
+                Ok(())
+            })
+            .set_capture(Capture::create(&path).unwrap());
+            web_socket
+                .connect_with_config("bnbusdt@bookTicker", &config)
+                .unwrap();
+            // The server closes the connection after the last message
+            assert!(web_socket.event_loop(&keep_running).is_err());
+        }
+        server.join().unwrap();
+
+        let frames: Vec<CapturedFrame> = CaptureReader::open(&path)
+            .unwrap()
+            .map(|frame| frame.unwrap())
+            .collect();
+        let captured: Vec<String> = frames.iter().map(|frame| frame.message.clone()).collect();
+        assert_eq!(captured, messages);
+        assert!(frames
+            .windows(2)
+            .all(|pair| pair[0].received_at <= pair[1].received_at));
+
+        let mut replayed = Vec::new();
+        {
+            let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+                replayed.push(variant_name(&event));
+                Ok(())
+            });
+            let count = web_socket
+                .replay(&path, ReplaySpeed::AsFastAsPossible)
+                .unwrap();
+            assert_eq!(count, messages.len());
+        }
+        assert_eq!(replayed, received);
+        assert_eq!(replayed, spot_event_names(fixture));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn replay_at_recorded_speed() {
+        let path = capture_path("recorded-speed");
+        let capture: String = include_str!("mocks/websockets/futures_events.jsonl")
+            .lines()
+            .take(3)
+            .enumerate()
+            .map(|(i, message)| {
+                let frame = CapturedFrame {
+                    received_at: 1_700_000_000_000_000 + i as u64 * 100_000,
+                    message: message.into(),
+                };
+                format!("{}\n", serde_json::to_string(&frame).unwrap())
+            })
+            .collect();
+        std::fs::write(&path, capture).unwrap();
+
+        let mut events = 0;
+        let started = std::time::Instant::now();
+        {
+            let mut web_socket = FuturesWebSockets::new(|_event: FuturesWebsocketEvent| {
+                events += 1;
+                Ok(())
+            });
+            web_socket.replay(&path, ReplaySpeed::Recorded).unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert_eq!(events, 3);
+        std::fs::remove_file(&path).unwrap();
+    }
+}